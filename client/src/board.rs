@@ -6,7 +6,7 @@ use web_sys::HtmlCanvasElement;
 use yew::services::{RenderService, Task};
 use yew::{html, Component, ComponentLink, Html, NodeRef, Properties, ShouldRender};
 
-use shared::game::{GameStateView, Visibility};
+use shared::game::{GameStateView, Point, Visibility};
 use shared::message::{ClientMessage, GameAction};
 
 use crate::game_view::GameView;
@@ -69,6 +69,8 @@ pub struct Board {
     render_loop: Option<Box<dyn Task>>,
     mouse_pos: Option<(f64, f64)>,
     selection_pos: Option<(u32, u32)>,
+    /// The last point we tried to place on. Clicking it again confirms a self-atari.
+    last_placed: Option<(u32, u32)>,
     width: u32,
     height: u32,
     edge_size: i32,
@@ -103,6 +105,7 @@ impl Component for Board {
             render_loop: None,
             mouse_pos: None,
             selection_pos: None,
+            last_placed: None,
             width: 0,
             height: 0,
             edge_size: 40,
@@ -273,9 +276,15 @@ impl Component for Board {
                 self.selection_pos = coord;
                 if let Some(selection_pos) = self.selection_pos {
                     if send {
+                        let confirm_self_atari = self.last_placed == Some(selection_pos);
+                        self.last_placed = Some(selection_pos);
                         networking::send(ClientMessage::GameAction {
                             room_id: None,
-                            action: GameAction::Place(selection_pos.0, selection_pos.1),
+                            action: GameAction::Place(
+                                selection_pos.0,
+                                selection_pos.1,
+                                confirm_self_atari,
+                            ),
                         });
                     }
                 }
@@ -545,11 +554,12 @@ impl Board {
         let last_stone = match (&game.state, &game.history) {
             (_, Some(h)) => h.last_stone.as_ref(),
             (GameStateView::Play(state), _) => state.last_stone.as_ref(),
+            (GameStateView::Simultaneous(state), _) => Some(&state.last_stones),
             _ => None,
         };
 
         if let Some(points) = last_stone {
-            for &(x, y) in points {
+            for &Point { x, y } in points {
                 let mut color = board[y as usize * game.size.0 as usize + x as usize];
 
                 if color == 0 {
@@ -574,7 +584,7 @@ impl Board {
                             continue;
                         }
 
-                        for &(x, y) in &group.points {
+                        for &Point { x, y } in &group.points {
                             context.set_line_width(2.0);
                             context.set_stroke_style(&JsValue::from_str(
                                 dead_mark_color[group.team.0 as usize - 1],
@@ -608,7 +618,7 @@ impl Board {
                         }
                     }
 
-                    for (idx, &color) in scoring.points.points.iter().enumerate() {
+                    for (idx, &color) in scoring.territory.points.iter().enumerate() {
                         let x = (idx % board_size) as f64;
                         let y = (idx / board_size) as f64;
 
@@ -309,7 +309,7 @@ impl Component for CreateGameView {
                     seats: self.seats.clone(),
                     komis: self.komis.clone(),
                     size: (self.size, self.size),
-                    mods: self.mods.clone(),
+                    mods: Box::new(self.mods.clone()),
                 });
                 self.oncreate.emit(());
                 false
@@ -18,7 +18,7 @@ use crate::{
 };
 use game_store::GameStore;
 use message::GameAction;
-use shared::{game, message};
+use shared::{game, message, states};
 
 pub struct GamePane {
     link: ComponentLink<Self>,
@@ -37,6 +37,9 @@ pub enum Msg {
     Pass,
     Cancel,
     Resign,
+    AcceptScore,
+    RejectScore,
+    ResumePlay,
     GetBoardAt(u32),
     ScanBoard(i32),
     ResetHistory,
@@ -58,6 +61,9 @@ struct Callbacks {
     pass: Callback<()>,
     cancel: Callback<()>,
     resign: Callback<()>,
+    accept: Callback<()>,
+    reject: Callback<()>,
+    resume: Callback<()>,
 }
 
 impl Component for GamePane {
@@ -69,6 +75,9 @@ impl Component for GamePane {
             pass: link.callback(|_| Msg::Pass),
             cancel: link.callback(|_| Msg::Cancel),
             resign: link.callback(|_| Msg::Resign),
+            accept: link.callback(|_| Msg::AcceptScore),
+            reject: link.callback(|_| Msg::RejectScore),
+            resume: link.callback(|_| Msg::ResumePlay),
         };
 
         // Currently the state is passed back through props so we don't care about the output
@@ -114,6 +123,9 @@ impl Component for GamePane {
             Msg::Pass => networking::send(GameAction::Pass),
             Msg::Cancel => networking::send(GameAction::Cancel),
             Msg::Resign => networking::send(GameAction::Resign),
+            Msg::AcceptScore => networking::send(GameAction::AcceptScore),
+            Msg::RejectScore => networking::send(GameAction::RejectScore(None)),
+            Msg::ResumePlay => networking::send(GameAction::ResumePlay),
             Msg::GetBoardAt(turn) => {
                 self.game_store.get_board_at(turn);
             }
@@ -155,6 +167,9 @@ impl Component for GamePane {
             pass,
             cancel,
             resign,
+            accept,
+            reject,
+            resume,
         } = &self.callbacks;
 
         // FIXME: Reforming the callbacks prevents yew from optimizing for equality.
@@ -162,6 +177,9 @@ impl Component for GamePane {
         let pass = pass.reform(|_| ());
         let cancel = cancel.reform(|_| ());
         let resign = resign.reform(|_| ());
+        let accept = accept.reform(|_| ());
+        let reject = reject.reform(|_| ());
+        let resume = resume.reform(|_| ());
 
         let userlist = game
             .members
@@ -183,11 +201,55 @@ impl Component for GamePane {
             })
             .collect::<Html>();
 
-        let status = match game.state {
-            game::GameStateView::FreePlacement(_) => "Free placement",
-            game::GameStateView::Play(_) => "Active",
-            game::GameStateView::Scoring(_) => "Scoring",
-            game::GameStateView::Done(_) => "Game over!",
+        let status = match &game.state {
+            game::GameStateView::Waiting(_) => "Waiting for players".to_string(),
+            game::GameStateView::KomiAuction(state) => match state.proposal {
+                Some(komi) => format!(
+                    "Komi auction, {:.1} proposed for white",
+                    komi as f32 / game.mods.point_unit() as f32
+                ),
+                None => "Komi auction".to_string(),
+            },
+            game::GameStateView::FreePlacement(_) => "Free placement".to_string(),
+            game::GameStateView::Play(_) => "Active".to_string(),
+            game::GameStateView::Simultaneous(_) => "Active, everyone moves at once".to_string(),
+            game::GameStateView::Scoring(_) => "Scoring".to_string(),
+            game::GameStateView::Done(state) => match &state.result {
+                Some(game::GameResult {
+                    winner: Some(color),
+                    margin,
+                    kind,
+                    ..
+                }) => {
+                    let reason = match kind {
+                        game::ResultKind::Counted => "",
+                        game::ResultKind::ScoringTimeout => " (scoring timed out)",
+                        game::ResultKind::Resignation => " (resignation)",
+                        game::ResultKind::FirstCapture => " (first capture)",
+                        game::ResultKind::PassForfeit => " (passing loses)",
+                        game::ResultKind::Void => " (void)",
+                        game::ResultKind::Agreement => " (agreement)",
+                        game::ResultKind::Provisional => " (estimated)",
+                        game::ResultKind::MostCaptures => " (most captures)",
+                    };
+                    format!(
+                        "Game over! {} won by {:.1}{}",
+                        game::Color::name(*color),
+                        *margin as f32 / game.mods.point_unit() as f32,
+                        reason
+                    )
+                }
+                Some(game::GameResult {
+                    kind: game::ResultKind::Void,
+                    ..
+                }) => "Game over! Void".to_string(),
+                Some(game::GameResult {
+                    kind: game::ResultKind::Agreement,
+                    ..
+                }) => "Game over! Ended by passing".to_string(),
+                Some(_) => "Game over! Draw".to_string(),
+                None => "Game over!".to_string(),
+            },
         };
 
         let game_done = matches!(game.state, game::GameStateView::Done(_));
@@ -198,25 +260,88 @@ impl Component for GamePane {
             html!()
         };
 
+        let user_id = user.as_ref().map(|u| u.user_id);
+        let swing_warned = |state: &states::ScoringState| {
+            state.events.iter().any(|e| {
+                matches!(e, states::ScoringEvent::LargeSwingWarning { player_id, .. }
+                    if Some(*player_id) == user_id)
+            })
+        };
+
+        let disputed_warning = match &game.state {
+            game::GameStateView::Scoring(state) if swing_warned(state) => {
+                html!(<>{"Many of your stones are marked dead, accept again to confirm."}</>)
+            }
+            game::GameStateView::Scoring(state) if !state.all_dead.is_empty() => {
+                let colors = state
+                    .all_dead
+                    .iter()
+                    .map(|c| game::Color::name(c.0))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                html!(<>{format!("Every {} stone is marked dead, is this intended?", colors)}</>)
+            }
+            game::GameStateView::Scoring(state) if !state.disputed.is_empty() => {
+                html!(<>{"Some territory has a false eye, check the dead stones!"}</>)
+            }
+            game::GameStateView::Play(state) if state.no_progress => {
+                html!(<>{"Nothing has changed in a while, pass to end the game."}</>)
+            }
+            _ => html!(),
+        };
+
+        let rejection_notice = match &game.state {
+            game::GameStateView::Scoring(states::ScoringState {
+                rejection: Some(rejection),
+                ..
+            }) => {
+                let nick = profiles
+                    .get(&rejection.player_id)
+                    .map_or("Someone", |p| p.nick_or("Someone"));
+                let reason = rejection
+                    .reason
+                    .as_ref()
+                    .map(|r| format!(": {}", r))
+                    .unwrap_or_default();
+                html!(<>{format!("{} rejected the score{}", nick, reason)}</>)
+            }
+            _ => html!(),
+        };
+
         let pass_button = match game.state {
-            game::GameStateView::FreePlacement(_) => html!(<button onclick=pass>{"Ready"}</button>),
-            game::GameStateView::Play(_) => html!(<button onclick=pass>{"Pass"}</button>),
-            game::GameStateView::Scoring(_) => html!(<button onclick=pass>{"Accept"}</button>),
-            game::GameStateView::Done(_) => html!(),
+            game::GameStateView::Waiting(_) | game::GameStateView::FreePlacement(_) => {
+                html!(<button onclick=pass>{"Ready"}</button>)
+            }
+            game::GameStateView::Play(_) | game::GameStateView::Simultaneous(_) => {
+                html!(<button onclick=pass>{"Pass"}</button>)
+            }
+            game::GameStateView::Scoring(_) => html! {
+                <>
+                    <button onclick=accept>{"Accept"}</button>
+                    <button onclick=reject>{"Reject"}</button>
+                    <button onclick=resume>{"Resume play"}</button>
+                </>
+            },
+            game::GameStateView::Done(_) | game::GameStateView::KomiAuction(_) => html!(),
         };
 
         let cancel_button = match game.state {
+            game::GameStateView::Waiting(_) => html!(<button onclick=cancel>{"Not ready"}</button>),
             game::GameStateView::FreePlacement(_) => {
                 html!(<button onclick=cancel>{"Clear"}</button>)
             }
             game::GameStateView::Play(_) => html!(<button onclick=cancel>{"Undo"}</button>),
+            game::GameStateView::Simultaneous(_) => {
+                html!(<button onclick=cancel>{"Take back"}</button>)
+            }
             game::GameStateView::Scoring(_) => html!(<button onclick=cancel>{"Cancel"}</button>),
             _ => html!(),
         };
 
         let resign_button = match game.state {
-            game::GameStateView::Play(_) => html!(<button onclick=resign>{"Resign"}</button>),
-            game::GameStateView::Scoring(_) => html!(<button onclick=resign>{"Resign"}</button>),
+            game::GameStateView::Play(_)
+            | game::GameStateView::Simultaneous(_)
+            | game::GameStateView::Scoring(_) => html!(<button onclick=resign>{"Resign"}</button>),
             _ => html!(),
         };
 
@@ -313,7 +438,7 @@ impl Component for GamePane {
                     <div style=game_container_style>
                         <div style="display: flex;">
                             <div style="flex-grow: 1;">
-                                {"Status:"} {status} {pass_button} {cancel_button} {resign_button} {hidden_stones_left}
+                                {"Status:"} {status} {pass_button} {cancel_button} {resign_button} {hidden_stones_left} {disputed_warning} {rejection_notice}
                             </div>
                             {hidden_move_toggle}
                         </div>
@@ -46,7 +46,7 @@ impl Component for ModeList {
 
         let ponnuki = if_html!(let Some(p) = mods.ponnuki_is_points =>
             <label class="tooltip">
-                {format!("Ponnuki is {} points", (p as f64) / 2.0)}
+                {format!("Ponnuki is {} points", (p as f64) / mods.point_unit() as f64)}
                 <span class=tooltip_class>{"Ponnuki requires a capture and all diagonals must be empty or different color"}</span>
             </label>
         );
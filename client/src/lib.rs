@@ -132,6 +132,7 @@ impl Component for GameApp {
                     points,
                     move_number,
                     clock,
+                    ..
                 }) => {
                     game.emit(GameView {
                         room_id,
@@ -398,6 +399,12 @@ impl Component for GameApp {
                 message::Error::GameStartTimer(x) => {
                     format!("You can only create a game every 2 minutes ({}s left)", x)
                 }
+                message::Error::Game {
+                    error:
+                        message::GameError::Action(game::MakeActionError::SelfAtariNeedsConfirm),
+                    ..
+                } => "This move puts your own stones in atari. Click the same point again to play it."
+                    .to_string(),
                 message::Error::Game { error, .. } => format!("{:?}", error),
                 message::Error::RateLimit => "You're too fast!".to_string(),
                 message::Error::Other(x) => x.to_string(),
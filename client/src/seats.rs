@@ -8,7 +8,7 @@ use crate::game_view::*;
 use crate::message;
 use crate::networking;
 use shared::game::clock::PlayerClock;
-use shared::game::Color;
+use shared::game::{Color, ColorScores};
 
 use crate::if_html;
 
@@ -174,11 +174,30 @@ impl Component for SeatList {
 
                 let scoretext = match scores {
                     Some(scores) => {
-                        format!(" - Score: {:.1}", scores[*color as usize - 1] as f32 / 2.)
+                        let unit = game.mods.point_unit() as f32;
+                        format!(" - Score: {:.1}", scores.score_for(Color(*color)) as f32 / unit)
                     }
                     None => "".to_owned(),
                 };
 
+                // With two colors the captures are obvious from the score.
+                let capturetext = match &game.state {
+                    GameStateView::Play(state)
+                        if game.points.len() > 2 && state.captures.total_by(Color(*color)) > 0 =>
+                    {
+                        let captured = (1..=game.points.len() as u8)
+                            .map(Color)
+                            .filter(|&from| state.captures.get(Color(*color), from) > 0)
+                            .map(|from| {
+                                format!("{} {}", Color::name(from), state.captures.get(Color(*color), from))
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        format!(" - Captured: {}", captured)
+                    }
+                    _ => "".to_owned(),
+                };
+
                 let kick = if self.props.user.as_ref().map(|x| x.user_id) == Some(game.owner) {
                     html! {
                         <button onclick=self.link.callback(move |_| Msg::KickSeat(idx))>
@@ -243,7 +262,11 @@ impl Component for SeatList {
                         GameStateView::FreePlacement(state) if state.players_ready[idx] => {
                             " - ready!"
                         }
+                        GameStateView::Waiting(state) if state.players_ready[idx] => " - ready!",
                         GameStateView::Play(state) if state.players_passed[idx] => " - passed!",
+                        GameStateView::Simultaneous(state) if state.players_moved[idx] => {
+                            " - moved!"
+                        }
                         GameStateView::Scoring(state) if state.players_accepted[idx] => {
                             " - accepted!"
                         }
@@ -252,7 +275,7 @@ impl Component for SeatList {
 
                     html! {
                         <div class=class style="margin: 5px 0; padding: 0px 5px; padding-top: 5px;">
-                            {format!("{}: {} {}{}{}", colorname, nick, scoretext, passed, resigned_text)}
+                            {format!("{}: {} {}{}{}{}", colorname, nick, scoretext, capturetext, passed, resigned_text)}
                             {leave}
                             {if_html!(let Some(t) = time_left =>
                                 <div style="padding: 10px; font-size: large;">{t}</div>
@@ -186,9 +186,17 @@ impl Handler<GameAction> for GameRoom {
 
         self.last_action = Instant::now();
         let res = match action {
-            message::GameAction::Place(x, y) => self
+            message::GameAction::Place(x, y, confirm_self_atari) => self
                 .game
-                .make_action(user_id, game::ActionKind::Place(x, y), current_time)
+                .make_action_expecting(
+                    user_id,
+                    game::ActionKind::Place(x, y),
+                    current_time,
+                    Some(&game::MoveExpectation {
+                        confirm_self_atari,
+                        ..Default::default()
+                    }),
+                )
                 .map_err(Into::into),
             message::GameAction::Pass => self
                 .game
@@ -202,6 +210,30 @@ impl Handler<GameAction> for GameRoom {
                 .game
                 .make_action(user_id, game::ActionKind::Resign, current_time)
                 .map_err(Into::into),
+            message::GameAction::AcceptScore => self
+                .game
+                .make_action(user_id, game::ActionKind::AcceptScore, current_time)
+                .map_err(Into::into),
+            message::GameAction::RejectScore(reason) => self
+                .game
+                .make_action(user_id, game::ActionKind::RejectScore(reason), current_time)
+                .map_err(Into::into),
+            message::GameAction::ResumePlay => self
+                .game
+                .make_action(user_id, game::ActionKind::ResumePlay, current_time)
+                .map_err(Into::into),
+            message::GameAction::ProposeKomi(komi) => self
+                .game
+                .make_action(user_id, game::ActionKind::ProposeKomi(komi), current_time)
+                .map_err(Into::into),
+            message::GameAction::ChooseColor(color) => self
+                .game
+                .make_action(
+                    user_id,
+                    game::ActionKind::ChooseColor(game::Color(color)),
+                    current_time,
+                )
+                .map_err(Into::into),
             message::GameAction::TakeSeat(seat_id) => {
                 if self.kicked_players.contains(&user_id) {
                     return MessageResult(Err(Error::other("Kicked from game")));
@@ -234,7 +266,7 @@ impl Handler<GameAction> for GameRoom {
                 return MessageResult(Ok(()));
             }
             message::GameAction::RequestSGF => {
-                let game_done = matches!(self.game.state, game::GameState::Done(_));
+                let game_done = self.game.state.is_terminal();
                 if !game_done {
                     return MessageResult(Err(Error::other("Game not finished")));
                 }
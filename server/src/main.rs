@@ -132,6 +132,8 @@ impl Handler<game_room::Message> for ClientWebSocket {
                         points: view.points.to_vec(),
                         move_number: view.move_number,
                         clock: view.clock,
+                        last_move: view.last_move,
+                        groups: view.groups,
                     }
                     .pack(),
                 );
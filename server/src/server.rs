@@ -526,7 +526,7 @@ impl Handler<CreateRoom> for GameServer {
 
         let komis = komis.as_slice().into();
         let seed = self.rng.next_u64();
-        let game = match game::Game::standard(&seats, komis, size, mods, seed) {
+        let game = match game::Game::standard(&seats, komis, size, *mods, seed) {
             Some(g) => g,
             None => return ActorResponse::reply(Err(Error::other("Rules not accepted"))),
         };
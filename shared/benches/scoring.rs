@@ -0,0 +1,99 @@
+//! Scoring work: grouping the stones of random 19x19 boards, flooding the
+//! empty regions of large empty boards, and dead stone toggling rescored from
+//! scratch against updating the region cache.
+//! Run with `cargo bench --bench scoring`.
+
+use std::time::Instant;
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+use shared::game::{find_groups, Board, Color, Point};
+use shared::states::scoring::{score_board, RegionCache};
+
+const TOGGLES: usize = 2000;
+const BOARDS: usize = 2000;
+
+fn random_board(rng: &mut Pcg64) -> Board {
+    let mut board = Board::empty(19, 19, false);
+    for y in 0..19 {
+        for x in 0..19 {
+            *board.point_mut(Point::new(x, y)) = match rng.gen_range(0, 10) {
+                0..=2 => Color(1),
+                3..=5 => Color(2),
+                _ => Color::empty(),
+            };
+        }
+    }
+    board
+}
+
+fn find_groups_bench(rng: &mut Pcg64) {
+    let boards = (0..BOARDS).map(|_| random_board(rng)).collect::<Vec<_>>();
+    let start = Instant::now();
+    let groups = boards.iter().map(|b| find_groups(b).len()).sum::<usize>();
+    println!(
+        "find_groups on {} 19x19 boards: {:?} ({} groups)",
+        BOARDS,
+        start.elapsed(),
+        groups
+    );
+}
+
+/// Empty boards are a single region reaching every point, the worst case for the flood.
+fn score_board_bench() {
+    for &(size, toroidal) in &[(19, false), (25, true)] {
+        let board = Board::empty(size, size, toroidal);
+        let start = Instant::now();
+        for _ in 0..BOARDS {
+            score_board(&board, &[]);
+        }
+        println!(
+            "score_board on {} empty {}x{}{} boards: {:?}",
+            BOARDS,
+            size,
+            size,
+            if toroidal { " toroidal" } else { "" },
+            start.elapsed()
+        );
+    }
+}
+
+fn toggle_bench(rng: &mut Pcg64) {
+    let board = random_board(rng);
+    let groups = find_groups(&board);
+    let order = (0..TOGGLES)
+        .map(|_| rng.gen_range(0, groups.len()))
+        .collect::<Vec<_>>();
+
+    let mut full_groups = groups.clone();
+    let start = Instant::now();
+    let mut full = RegionCache::new(&board, &full_groups);
+    for &idx in &order {
+        full_groups[idx].alive = !full_groups[idx].alive;
+        full = RegionCache::new(&board, &full_groups);
+    }
+    let full_time = start.elapsed();
+
+    let mut toggled_groups = groups;
+    let start = Instant::now();
+    let mut cache = RegionCache::new(&board, &toggled_groups);
+    for &idx in &order {
+        toggled_groups[idx].alive = !toggled_groups[idx].alive;
+        cache.toggle(&toggled_groups[idx]);
+    }
+    let toggle_time = start.elapsed();
+
+    assert_eq!(cache.paint(), full.paint());
+    println!(
+        "{} toggles on 19x19: full rescore {:?}, incremental {:?}",
+        TOGGLES, full_time, toggle_time
+    );
+}
+
+fn main() {
+    let mut rng = Pcg64::seed_from_u64(19);
+    find_groups_bench(&mut rng);
+    score_board_bench();
+    toggle_bench(&mut rng);
+}
@@ -0,0 +1,101 @@
+//! A weak bot for filling empty seats and for testing.
+//! It only knows plain go rules, so variants like hidden moves or n+1 are ignored.
+
+#[cfg(test)]
+mod tests;
+
+use rand::prelude::*;
+use rand_pcg::Lcg64Xsh32;
+
+use crate::game::{find_groups, ActionKind, Board, Color, Point, SharedState};
+
+/// What a move would do to the board, as far as the bot cares.
+struct Candidate {
+    point: Point,
+    captures: usize,
+    ataris: usize,
+    fills_own_eye: bool,
+}
+
+/// Picks a capture if there is one, else an atari, else a random legal move.
+/// Passes when only moves filling its own eyes are left.
+/// `None` if no seat plays `color`.
+pub fn greedy_move(shared: &SharedState, color: Color) -> Option<ActionKind> {
+    if !shared.seats.iter().any(|s| s.team == color) {
+        return None;
+    }
+
+    let board = &shared.board;
+    let candidates = (0..board.points.len())
+        .filter_map(|idx| board.idx_to_coord(idx))
+        .filter(|&p| board.get_point(p).is_empty())
+        .filter_map(|p| evaluate(shared, color, p))
+        .filter(|c| !c.fills_own_eye)
+        .collect::<Vec<_>>();
+
+    let best = candidates
+        .iter()
+        .filter(|c| c.captures > 0)
+        .max_by_key(|c| c.captures)
+        .or_else(|| {
+            candidates
+                .iter()
+                .filter(|c| c.ataris > 0)
+                .max_by_key(|c| c.ataris)
+        })
+        .or_else(|| {
+            // Same position, same move. Keeps games reproducible.
+            let mut rng = Lcg64Xsh32::seed_from_u64(board.hash());
+            candidates.choose(&mut rng)
+        });
+
+    Some(match best {
+        Some(c) => ActionKind::Place(c.point.x, c.point.y),
+        None => ActionKind::Pass,
+    })
+}
+
+/// Plays `point` on a copy of the board, `None` if it's suicide or repeats a position.
+fn evaluate(shared: &SharedState, color: Color, point: Point) -> Option<Candidate> {
+    let mut board = shared.board.clone();
+    let fills_own_eye = board
+        .surrounding_points(point)
+        .all(|p| board.get_point(p) == color);
+    *board.point_mut(point) = color;
+
+    let mut captures = 0;
+    for group in find_groups(&board) {
+        if group.team != color && group.liberties == 0 {
+            captures += group.points.len();
+            for p in &group.points {
+                *board.point_mut(*p) = Color::empty();
+            }
+        }
+    }
+
+    let groups = find_groups(&board);
+    if groups.iter().any(|g| g.team == color && g.liberties == 0) {
+        return None;
+    }
+
+    let hash = board.hash();
+    if shared.board_history.iter().any(|h| h.hash == hash) {
+        return None;
+    }
+
+    let ataris = groups
+        .iter()
+        .filter(|g| g.team != color && g.liberties == 1 && touches(&board, &g.points, point))
+        .count();
+
+    Some(Candidate {
+        point,
+        captures,
+        ataris,
+        fills_own_eye,
+    })
+}
+
+fn touches(board: &Board, points: &[Point], point: Point) -> bool {
+    board.surrounding_points(point).any(|p| points.contains(&p))
+}
@@ -0,0 +1,46 @@
+use super::*;
+use crate::game::clock::Millisecond;
+use crate::test_support::game_with_position;
+
+#[test]
+fn bot_takes_a_capture() {
+    let mut game = game_with_position(
+        &[
+            ".....", //
+            ".xo..", //
+            "xo...", //
+            ".x...", //
+            ".....", //
+        ],
+        false,
+    );
+    let action = greedy_move(&game.shared, Color(1)).unwrap();
+    assert_eq!(action, ActionKind::Place(2, 2));
+
+    game.make_action(100, action, Millisecond(0)).unwrap();
+    assert_eq!(
+        game.shared.board.get_point(Point::new(1, 2)),
+        Color::empty()
+    );
+}
+
+#[test]
+fn bot_passes_on_a_full_board() {
+    // Only black's own eyes are left.
+    let game = game_with_position(
+        &[
+            ".x.xo", //
+            "xxxxo", //
+            "ooooo", //
+            "o.o.o", //
+        ],
+        false,
+    );
+    assert_eq!(greedy_move(&game.shared, Color(1)), Some(ActionKind::Pass));
+}
+
+#[test]
+fn bot_needs_a_seat() {
+    let game = game_with_position(&["...", "...", "..."], false);
+    assert_eq!(greedy_move(&game.shared, Color(3)), None);
+}
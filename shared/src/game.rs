@@ -1,21 +1,42 @@
 mod board;
+mod builder;
 pub mod clock;
+mod driver;
+mod events;
 pub mod export;
+mod hints;
+pub mod import;
+mod review;
+mod snapshot;
+mod state_stack;
 #[cfg(test)]
 mod tests;
 
 use clock::{ClockRule, GameClock, Millisecond};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashSet, VecDeque};
+use std::collections::VecDeque;
+use std::sync::Arc;
 
 use bitmaps::Bitmap;
 use tinyvec::TinyVec;
 
 use crate::states::play::traitor::TraitorState;
-pub use crate::states::GameState;
 use crate::states::PlayState;
 use crate::states::ScoringState;
-pub use board::{Board, Point};
+use crate::states::WaitingState;
+pub use crate::states::{GameResult, GameState, ResultKind};
+use crate::states::{KomiAuctionResult, KomiAuctionState};
+pub use board::{Board, CoordOrigin, Point, PointClass};
+pub use builder::{
+    handicap_points, nigiri, suggest_handicap_komi, validate_seats, BuildError, GameBuilder,
+    Nigiri, SeatError,
+};
+pub use driver::GameDriver;
+pub use events::EventSink;
+pub use hints::{CornerBook, HintProvider};
+pub use review::{ReviewNode, ReviewSession};
+pub use snapshot::{BoardPublisher, BoardReader, BoardSnapshot};
+pub use state_stack::GameStateStack;
 
 ///////////////////////////////////////////////////////////////////////////////
 //                                    Data                                   //
@@ -75,9 +96,84 @@ impl From<u8> for Color {
     }
 }
 
+/// A shape drawn on stones, so colors can be told apart without seeing color.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Marker {
+    Circle,
+    Triangle,
+    Square,
+    Cross,
+    Stripes,
+}
+
+/// How a client should draw the stones of one color.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorStyle {
+    pub color: Color,
+    pub rgb: [u8; 3],
+    pub marker: Marker,
+}
+
+/// Stone styles for every color of a game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorScheme {
+    pub colors: Vec<ColorStyle>,
+}
+
+impl ColorScheme {
+    /// The usual stone colors. Markers go from the plainest shape up, so any
+    /// two to four colors get different outlines.
+    pub fn standard(color_count: u8) -> Self {
+        const RGB: [[u8; 3]; 4] = [
+            [0x00, 0x00, 0x00],
+            [0xee, 0xee, 0xee],
+            [0x50, 0x74, 0xbc],
+            [0xe0, 0x65, 0x8f],
+        ];
+        const MARKERS: [Marker; 5] = [
+            Marker::Circle,
+            Marker::Triangle,
+            Marker::Square,
+            Marker::Cross,
+            Marker::Stripes,
+        ];
+        let colors = (0..color_count as usize)
+            .map(|idx| ColorStyle {
+                color: Color(idx as u8 + 1),
+                rgb: RGB[idx % RGB.len()],
+                marker: MARKERS[idx % MARKERS.len()],
+            })
+            .collect();
+        ColorScheme { colors }
+    }
+
+    pub fn style(&self, color: Color) -> Option<&ColorStyle> {
+        self.colors.iter().find(|s| s.color == color)
+    }
+}
+
+/// Points are stored per color without a slot for the empty color, so
+/// `Color(1)` is at index 0.
+pub trait ColorScores {
+    fn score_for(&self, color: Color) -> i32;
+    fn score_for_mut(&mut self, color: Color) -> &mut i32;
+}
+
+impl ColorScores for [i32] {
+    fn score_for(&self, color: Color) -> i32 {
+        assert!(!color.is_empty(), "The empty color has no score");
+        self[color.as_usize() - 1]
+    }
+
+    fn score_for_mut(&mut self, color: Color) -> &mut i32 {
+        assert!(!color.is_empty(), "The empty color has no score");
+        &mut self[color.as_usize() - 1]
+    }
+}
+
 // Seat ///////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct Seat {
     pub player: Option<u64>,
     pub team: Color,
@@ -104,6 +200,17 @@ pub struct Group {
     pub alive: bool,
 }
 
+/// Identifies a group by its first point in board order, so it can be tracked
+/// across recomputations as long as the group's stones don't change.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct GroupId(pub Point);
+
+impl Group {
+    pub fn id(&self) -> GroupId {
+        GroupId(*self.points.iter().min().expect("Group without points"))
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 //                                Game action                                //
 ///////////////////////////////////////////////////////////////////////////////
@@ -114,6 +221,16 @@ pub enum ActionKind {
     Pass,
     Cancel,
     Resign,
+    /// Accepts the score during scoring. `Pass` does the same.
+    AcceptScore,
+    /// Rejects the score during scoring, clearing everyone's acceptance.
+    RejectScore(Option<String>),
+    /// Asks to go back to play during scoring. Play resumes once every player asks.
+    ResumePlay,
+    /// Proposes white's komi in half points during a komi auction.
+    ProposeKomi(i32),
+    /// Takes a color with the proposed komi during a komi auction.
+    ChooseColor(Color),
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -121,6 +238,10 @@ pub enum ReplayActionKind {
     Play(ActionKind),
     TakeSeat(u32),
     LeaveSeat(u32),
+    ScoringTimeout,
+    /// An undo in `GameModifier::teaching_mode`, with how the reverted move had
+    /// changed the board. Replayed like `ActionKind::Cancel`.
+    Undo(MoveInfo),
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -139,6 +260,31 @@ impl GameAction {
     }
 }
 
+/// Where an imported game stopped matching the engine.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReplayMismatch {
+    /// The action for this move was rejected.
+    Illegal(u32),
+    /// The board after this move differs from the recorded one.
+    Board(u32),
+}
+
+impl ReplayMismatch {
+    pub fn move_number(&self) -> u32 {
+        match *self {
+            ReplayMismatch::Illegal(n) | ReplayMismatch::Board(n) => n,
+        }
+    }
+}
+
+/// Why a strict replay of an imported game stopped.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayError {
+    /// The move that couldn't be replayed.
+    pub move_index: u32,
+    pub reason: MakeActionError,
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 //                               Game modifiers                              //
 ///////////////////////////////////////////////////////////////////////////////
@@ -193,6 +339,131 @@ pub struct TraitorGo {
     pub traitor_count: u32,
 }
 
+/// Every seated player has to be ready before the game starts.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ReadyCheck {
+    /// Wait for every seat to be taken, not just the occupied ones.
+    pub empty_seats_block: bool,
+}
+
+/// The first color to capture a stone wins. Meant for teaching beginners.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct AtariGo {
+    /// Passing loses the game instead of ending it, so players have to keep playing
+    /// until someone captures.
+    pub pass_loses: bool,
+    /// Everyone passing ends the game without a count, as a draw. Nobody captured,
+    /// so counting would decide a game the rules don't score.
+    #[serde(default)]
+    pub pass_ends: bool,
+    /// Captures don't end the game. Play goes on until `GameModifier::max_moves` or
+    /// everyone passing, and the color that captured the most stones wins.
+    #[serde(default)]
+    pub capture_count: bool,
+}
+
+/// Komi decided by the count: every color trailing in area gets a share of the
+/// leader's lead, so one-sided games end closer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DynamicKomi {
+    /// Percentage of the area lead given to a trailing color.
+    pub percent: u32,
+    /// Most komi a color can get this way, in half points, or whole points
+    /// with `GameModifier::integer_scoring`.
+    pub cap: i32,
+}
+
+/// Every seat moves at the same time, see `SimultaneousPlayState`.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct SimultaneousGo {
+    pub collision: CollisionRule,
+}
+
+/// What happens when several seats pick the same point.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum CollisionRule {
+    /// Nobody gets the point.
+    #[default]
+    BothRejected,
+    /// One of them is picked at random.
+    CoinFlip,
+}
+
+/// Notices when the stone counts and the territory estimate haven't changed in
+/// `moves` moves, so the players can be offered to end the game by passing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoProgress {
+    pub moves: u32,
+}
+
+/// Accepting the score with more than `stones` of your own stones marked dead only
+/// warns the first time, so beginners don't lose a game to a misclick.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmDeadStones {
+    pub stones: u32,
+}
+
+/// Extra stones for one color, to balance games between players of different strength.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorHandicap {
+    pub color: Color,
+    pub stones: u32,
+    pub placement: HandicapPlacement,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum HandicapPlacement {
+    /// The stones are put on the star points before the game starts.
+    #[default]
+    Fixed,
+    /// The color places all of its stones on its first turn.
+    Free,
+}
+
+/// Decides who gets the dame when area scoring without filling them.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum DamePolicy {
+    /// Dame are not counted for anyone.
+    #[default]
+    Ignore,
+    /// Dame are given out in turn order starting from the player to move, as if they were filled.
+    Parity,
+    /// The first player to pass at the end of the game gets half a point instead.
+    Button,
+    /// Every color next to a dame point gets half a point for it.
+    Split,
+}
+
+/// What happens to a color once every seat of it resigned, or all of its stones
+/// were captured. The other colors keep playing in turn order either way.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EliminationPolicy {
+    /// The color's stones are taken off the board.
+    Remove,
+    /// The color's stones stay, but they and the territory they surround count for nobody.
+    Neutralize,
+}
+
+/// Decides who gets the empty points shared by the groups of a seki.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum SekiTerritory {
+    /// Shared points are not counted for anyone.
+    #[default]
+    Nobody,
+    /// Every color next to a shared point gets half a point for it.
+    Split,
+}
+
+/// Decides whose acceptance is needed to finish scoring in team games.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum TeamAcceptPolicy {
+    /// One teammate accepting accepts for every seat of the team.
+    AnyMember,
+    /// Every seat has to accept separately.
+    #[default]
+    AllMembers,
+}
+
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct GameModifier {
     /// Pixel go is a game mode where you place 2x2 blobs instead of a single stone.
@@ -240,16 +511,280 @@ pub struct GameModifier {
 
     #[serde(default)]
     pub traitor: Option<TraitorGo>,
+
+    #[serde(default)]
+    pub team_accept_policy: TeamAcceptPolicy,
+
+    /// Fixed handicap stones placed for black before the game starts. White moves first.
+    #[serde(default)]
+    pub handicap: u32,
+
+    #[serde(default)]
+    pub seki_territory: SekiTerritory,
+
+    /// Scores, komi and other point values are in whole points instead of half points.
+    #[serde(default)]
+    pub integer_scoring: bool,
+
+    /// Color that moves first. Defaults to black, or white in handicap games.
+    #[serde(default)]
+    pub first_player: Option<Color>,
+
+    #[serde(default)]
+    pub ready_check: Option<ReadyCheck>,
+
+    #[serde(default)]
+    pub dame_policy: DamePolicy,
+
+    /// The game clock keeps running while scoring. Players who run out of time
+    /// accept the current count.
+    #[serde(default)]
+    pub scoring_uses_clock: bool,
+
+    /// Handicap stones for any color. Each stone costs the color a point.
+    /// Can't be combined with `handicap`.
+    #[serde(default)]
+    pub color_handicaps: Vec<ColorHandicap>,
+
+    #[serde(default)]
+    pub no_progress: Option<NoProgress>,
+
+    #[serde(default)]
+    pub simultaneous: Option<SimultaneousGo>,
+
+    /// Groups that are obviously dead start out marked dead in scoring, so
+    /// forgotten dead stones don't void territory.
+    #[serde(default)]
+    pub auto_dead_stones: bool,
+
+    #[serde(default)]
+    pub confirm_dead_stones: Option<ConfirmDeadStones>,
+
+    #[serde(default)]
+    pub atari_go: Option<AtariGo>,
+
+    /// The first two seats swap colors if the seat holding black loses nigiri.
+    /// Only for two colors.
+    #[serde(default)]
+    pub nigiri: bool,
+
+    /// Black proposes the komi and white picks a color before the game starts.
+    /// Only for two colors.
+    #[serde(default)]
+    pub komi_auction: bool,
+
+    /// A move may kill its own group as long as the group had stones before the move.
+    /// A lone stone killing itself is still suicide.
+    #[serde(default)]
+    pub self_capture: bool,
+
+    /// Teaching rule: while a group of the color to move is in atari, the move has to
+    /// give one of those groups more liberties or capture stones next to one of them.
+    /// Passing is still allowed.
+    #[serde(default)]
+    pub must_defend_atari: bool,
+
+    /// A placement that puts its own group in atari is rejected with
+    /// `MakeActionError::SelfAtariNeedsConfirm` unless it comes with
+    /// `MoveExpectation::confirm_self_atari`, so a misclick doesn't throw away stones.
+    #[serde(default)]
+    pub confirm_self_atari: bool,
+
+    /// Losing every stone to captures eliminates a color like resigning does,
+    /// and decides what is left of an eliminated color.
+    #[serde(default)]
+    pub on_elimination: Option<EliminationPolicy>,
+
+    /// Strict area scoring: when everyone has passed the board is counted as it is,
+    /// with every stone alive, and the game ends without a dead stone phase.
+    #[serde(default)]
+    pub tromp_taylor: bool,
+
+    /// Play ends after this many moves, counted like `SharedState::move_number`,
+    /// and goes to scoring. Keeps a server's game histories bounded.
+    #[serde(default)]
+    pub max_moves: Option<u32>,
+
+    /// A game stopped by `max_moves` ends void, without a count or winner.
+    #[serde(default)]
+    pub max_moves_void: bool,
+
+    /// A color with no legal placement passes automatically when its turn comes,
+    /// see `PlayState::legal_moves`.
+    #[serde(default)]
+    pub auto_pass_when_stuck: bool,
+
+    /// Extra komi from the area difference, added on top of the fixed komi when counting.
+    #[serde(default)]
+    pub dynamic_komi: Option<DynamicKomi>,
+
+    /// For three or more colors. A color's pass stands until it places a stone again,
+    /// and play ends once most of the playing colors have passed. Otherwise every
+    /// playing seat has to pass in a row.
+    #[serde(default)]
+    pub pass_majority_ends: bool,
+
+    /// Any player can undo during play, whoever's turn it is. Every undo is kept
+    /// in the game's actions with the move it reverted, see `ReplayActionKind::Undo`.
+    #[serde(default)]
+    pub teaching_mode: bool,
+}
+
+impl GameModifier {
+    /// Points given for a single stone or territory point.
+    pub fn point_unit(&self) -> i32 {
+        if self.integer_scoring {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Short human readable name of the enabled variants, eg. "Toroidal go, Pixel go".
+    pub fn rules_name(&self) -> String {
+        let mut names = Vec::new();
+        if self.pixel {
+            names.push("Pixel go".to_string());
+        }
+        if let Some(p) = self.ponnuki_is_points {
+            names.push(format!(
+                "Ponnuki is {} points",
+                (p as f64) / self.point_unit() as f64
+            ));
+        }
+        if self.zen_go.is_some() {
+            names.push("Zen go".to_string());
+        }
+        if let Some(r) = &self.hidden_move {
+            names.push(format!("{} hidden moves", r.placement_count));
+        }
+        if let Some(r) = &self.traitor {
+            names.push(format!("{} traitor stones", r.traitor_count));
+        }
+        if let Some(VisibilityMode::OneColor) = &self.visibility_mode {
+            names.push("One color go".to_string());
+        }
+        if self.no_history {
+            names.push("No history".to_string());
+        }
+        if let Some(r) = &self.n_plus_one {
+            names.push(format!("{}+1 go", r.length));
+        }
+        if self.tetris.is_some() {
+            names.push("Tetris go".to_string());
+        }
+        if self.toroidal.is_some() {
+            names.push("Toroidal go".to_string());
+        }
+        if self.phantom.is_some() {
+            names.push("Phantom go".to_string());
+        }
+        if self.captures_give_points.is_some() {
+            names.push("Captures give points".to_string());
+        }
+        if self.handicap > 0 {
+            names.push(format!("{} handicap", self.handicap));
+        }
+        if self.seki_territory == SekiTerritory::Split {
+            names.push("Split seki points".to_string());
+        }
+        if self.integer_scoring {
+            names.push("Integer scoring".to_string());
+        }
+        if self.ready_check.is_some() {
+            names.push("Ready check".to_string());
+        }
+        match self.dame_policy {
+            DamePolicy::Ignore => {}
+            DamePolicy::Parity => names.push("Dame by parity".to_string()),
+            DamePolicy::Button => names.push("Button go".to_string()),
+            DamePolicy::Split => names.push("Split dame".to_string()),
+        }
+        if self.simultaneous.is_some() {
+            names.push("Simultaneous go".to_string());
+        }
+        if !self.color_handicaps.is_empty() {
+            names.push("Color handicaps".to_string());
+        }
+        if self.scoring_uses_clock && self.clock.is_some() {
+            names.push("Timed scoring".to_string());
+        }
+        if self.auto_dead_stones {
+            names.push("Auto dead stones".to_string());
+        }
+        match self.on_elimination {
+            Some(EliminationPolicy::Remove) => names.push("Eliminated colors removed".to_string()),
+            Some(EliminationPolicy::Neutralize) => {
+                names.push("Eliminated colors neutral".to_string())
+            }
+            None => {}
+        }
+        match &self.atari_go {
+            Some(AtariGo {
+                pass_loses: true, ..
+            }) => names.push("Atari go (passing loses)".to_string()),
+            Some(AtariGo {
+                capture_count: true,
+                ..
+            }) => names.push("Atari go (most captures)".to_string()),
+            Some(AtariGo {
+                pass_ends: true, ..
+            }) => names.push("Atari go (passing ends)".to_string()),
+            Some(_) => names.push("Atari go".to_string()),
+            None => {}
+        }
+        if self.komi_auction {
+            names.push("Komi auction".to_string());
+        }
+        if self.self_capture {
+            names.push("Self-capture".to_string());
+        }
+        if let Some(moves) = self.max_moves {
+            if self.max_moves_void {
+                names.push(format!("Void after {} moves", moves));
+            } else {
+                names.push(format!("Max {} moves", moves));
+            }
+        }
+        if self.tromp_taylor {
+            names.push("Tromp-Taylor".to_string());
+        }
+        if self.must_defend_atari {
+            names.push("Must defend atari".to_string());
+        }
+        if self.confirm_self_atari {
+            names.push("Confirm self-atari".to_string());
+        }
+        if self.auto_pass_when_stuck {
+            names.push("Auto-pass when stuck".to_string());
+        }
+        if self.teaching_mode {
+            names.push("Teaching undo".to_string());
+        }
+        if self.pass_majority_ends {
+            names.push("Majority of passes ends".to_string());
+        }
+        if let Some(dynamic) = &self.dynamic_komi {
+            names.push(format!("Dynamic komi {}%", dynamic.percent));
+        }
+
+        if names.is_empty() {
+            "Standard".to_string()
+        } else {
+            names.join(", ")
+        }
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
 //                                   State                                   //
 ///////////////////////////////////////////////////////////////////////////////
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BoardHistory {
     pub hash: u64,
     pub board: Board,
+    #[serde(with = "visibility_serde")]
     pub board_visibility: Option<VisibilityBoard>,
     pub state: GameState,
     pub points: GroupVec<i32>,
@@ -257,37 +792,247 @@ pub struct BoardHistory {
     pub traitor: Option<TraitorState>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SharedState {
     pub seats: GroupVec<Seat>,
     pub points: GroupVec<i32>,
     pub turn: usize,
     pub pass_count: usize,
     pub board: Board,
+    #[serde(with = "visibility_serde")]
     pub board_visibility: Option<VisibilityBoard>,
     pub board_history: Vec<BoardHistory>,
+    /// Point adjustments per color, in half points (15 = 7.5). Standard komi
+    /// only gives points to white, but any color can be adjusted.
     pub komis: GroupVec<i32>,
     pub mods: GameModifier,
     pub clock: Option<GameClock>,
     pub traitor: Option<TraitorState>,
+    /// How the colors were decided, if by nigiri.
+    #[serde(default)]
+    pub nigiri: Option<Nigiri>,
+    /// The komi and colors agreed on, if by komi auction.
+    #[serde(default)]
+    pub komi_auction: Option<KomiAuctionResult>,
+    /// Told about moves, captures and phase changes. Not serialized, so it has to be
+    /// installed again after loading a game.
+    #[serde(skip)]
+    pub event_sink: Option<Arc<dyn EventSink>>,
+    #[serde(skip)]
+    pub board_publisher: BoardPublisher,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Game {
     pub state: GameState,
-    pub state_stack: Vec<GameState>,
+    pub state_stack: GameStateStack,
     pub shared: SharedState,
     pub actions: Vec<GameAction>,
     pub seed: u64,
+    /// Stones put on the board before the first move, see `GameBuilder::setup`.
+    #[serde(default)]
+    pub setup: Vec<(Point, Color)>,
+}
+
+/// Bitmaps don't implement serde, so visibility is stored as plain integers.
+mod visibility_serde {
+    use super::{Bitmap, Board, VisibilityBoard};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        board: &Option<VisibilityBoard>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        board
+            .as_ref()
+            .map(|b| {
+                b.with_points(
+                    b.points
+                        .iter()
+                        .map(|x| x.into_value())
+                        .collect::<Vec<u16>>(),
+                )
+            })
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<VisibilityBoard>, D::Error> {
+        let board = Option::<Board<u16>>::deserialize(deserializer)?;
+        Ok(board.map(|b| {
+            let points = b.points.iter().copied().map(Bitmap::from_value).collect();
+            b.with_points(points)
+        }))
+    }
 }
 
 impl SharedState {
+    /// Moves played so far. Fixed handicap stones are part of the starting position
+    /// and don't count, while each free handicap stone counts as a move of its own.
+    pub fn move_number(&self) -> u32 {
+        self.board_history.len() as u32 - 1
+    }
+
+    /// Whether `GameModifier::max_moves` moves have been made, after which play is over.
+    pub fn max_moves_reached(&self) -> bool {
+        self.mods
+            .max_moves
+            .is_some_and(|max| self.move_number() >= max)
+    }
+
+    /// How move `move_number` (counting from 1) changed the board.
+    /// Includes hidden stones, so don't show it to players of phantom go.
+    pub fn move_info(&self, move_number: u32) -> Option<MoveInfo> {
+        let idx = move_number as usize;
+        let before = &self.board_history.get(idx.checked_sub(1)?)?.board;
+        let after = &self.board_history.get(idx)?.board;
+        let mut info = MoveInfo::default();
+        for (idx, (old, new)) in before.points.iter().zip(&after.points).enumerate() {
+            let point = after.idx_to_coord(idx).expect("Index in board");
+            if old.is_empty() && !new.is_empty() {
+                info.placed.push(point);
+            } else if !old.is_empty() && new.is_empty() {
+                info.captured.push(point);
+            }
+        }
+        Some(info)
+    }
+
+    /// The point the next player can't retake right away, for marking a ko.
+    /// Only a true ko shape counts: the last move captured exactly one stone and
+    /// the placed stone is alone with a single liberty, which is then the captured point.
+    /// Moves capturing more, or leaving the new stone other liberties, set none.
+    ///
+    /// Only for displays. The rules don't read it, ko is enforced by the
+    /// superko check in `PlayState`, which also covers longer cycles.
+    pub fn ko_point(&self) -> Option<Point> {
+        let info = self.move_info(self.move_number())?;
+        let (&placed, &captured) = match (&info.placed[..], &info.captured[..]) {
+            ([placed], [captured]) => (placed, captured),
+            _ => return None,
+        };
+        let group = find_groups(&self.board)
+            .into_iter()
+            .find(|g| g.points.contains(&placed))?;
+        if group.points.len() == 1 && group.liberties == 1 {
+            Some(captured)
+        } else {
+            None
+        }
+    }
+
+    /// A handle that always sees the board of the last committed action.
+    /// Snapshots are only taken once a reader has been asked for.
+    pub fn board_reader(&mut self) -> BoardReader {
+        let current = BoardSnapshot::of(self);
+        self.board_publisher.reader(current)
+    }
+
     pub fn get_active_seat(&self) -> Seat {
         self.seats
             .get(self.turn)
             .expect("Game turn number invalid")
             .clone()
     }
+
+    /// A lightweight description of the game for listings.
+    pub fn summary(&self, state: &GameState) -> GameSummary {
+        let phase = GamePhase::of(state);
+        let clock = self.clock.as_ref().map(|_| {
+            if phase == GamePhase::Play {
+                ClockStatus::Running
+            } else {
+                ClockStatus::Stopped
+            }
+        });
+
+        GameSummary {
+            seats: self
+                .seats
+                .iter()
+                .map(|s| (s.player, s.team.0, s.resigned))
+                .collect(),
+            size: (self.board.width() as u8, self.board.height() as u8),
+            rules: self.mods.rules_name(),
+            move_number: self.move_number(),
+            phase,
+            clock,
+        }
+    }
+
+    /// The status of each seat, in seat order.
+    pub fn seat_status(&self, state: &GameState) -> Vec<SeatStatus> {
+        let accepted = match state {
+            GameState::Scoring(state) => Some(&state.players_accepted),
+            _ => None,
+        };
+        self.seats
+            .iter()
+            .enumerate()
+            .map(|(idx, seat)| SeatStatus {
+                player: seat.player,
+                team: seat.team,
+                resigned: seat.resigned,
+                accepted: accepted.map(|a| a[idx]),
+                time_left: self.clock.as_ref().map(|c| c.time_left(idx)),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GamePhase {
+    FreePlacement,
+    Play,
+    Scoring,
+    Done,
+    Waiting,
+}
+
+impl GamePhase {
+    pub fn of(state: &GameState) -> Self {
+        match state {
+            GameState::FreePlacement(_) => GamePhase::FreePlacement,
+            GameState::Play(_) => GamePhase::Play,
+            GameState::Scoring(_) => GamePhase::Scoring,
+            GameState::Done(_) => GamePhase::Done,
+            GameState::Waiting(_) | GameState::KomiAuction(_) => GamePhase::Waiting,
+            GameState::Simultaneous(_) => GamePhase::Play,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClockStatus {
+    Running,
+    Stopped,
+}
+
+/// A seat together with the parts of its status kept in the game state and clocks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeatStatus {
+    /// `None` if the seat is empty.
+    pub player: Option<u64>,
+    pub team: Color,
+    pub resigned: bool,
+    /// Whether the seat has accepted the score, `None` outside of scoring.
+    pub accepted: Option<bool>,
+    /// Time left as of the last move, `None` without a clock.
+    pub time_left: Option<Millisecond>,
+}
+
+/// What a game list needs to know about a game, without the board.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameSummary {
+    /// (player, color, resigned) of each seat, like in `ServerMessage::GameStatus`.
+    pub seats: Vec<(Option<u64>, u8, bool)>,
+    pub size: (u8, u8),
+    pub rules: String,
+    pub move_number: u32,
+    pub phase: GamePhase,
+    /// `None` if the game is played without a clock.
+    pub clock: Option<ClockStatus>,
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -311,6 +1056,40 @@ pub enum MakeActionError {
     Ko,
     Illegal,
     GameDone,
+    /// The move didn't match the `MoveExpectation` given with it.
+    UnexpectedResult,
+    /// Tried to go back from the first state of the game.
+    NoPreviousState,
+    /// A group is in atari and the move didn't help it, see `GameModifier::must_defend_atari`.
+    MustDefend,
+    /// The game is past the point where the action makes sense, eg. playing after `GameModifier::max_moves`.
+    WrongPhase,
+    /// The move puts its own group in atari, see `GameModifier::confirm_self_atari`.
+    SelfAtariNeedsConfirm,
+}
+
+/// What a client expects a placement to result in. Lets the server catch
+/// desynced or tampered clients.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MoveExpectation {
+    /// Stones removed by the move.
+    pub captures: Option<usize>,
+    /// `Board::hash` of the resulting board.
+    pub hash: Option<u64>,
+    /// Plays a self-atari with `GameModifier::confirm_self_atari`.
+    #[serde(default)]
+    pub confirm_self_atari: bool,
+}
+
+impl MoveExpectation {
+    /// Expects nothing and confirms a self-atari, for moves that were already
+    /// accepted once, eg. when replaying.
+    pub fn confirmed() -> Self {
+        MoveExpectation {
+            confirm_self_atari: true,
+            ..MoveExpectation::default()
+        }
+    }
 }
 
 pub enum ActionChange {
@@ -331,12 +1110,23 @@ pub struct FreePlacementView {
     pub players_ready: Vec<bool>,
 }
 
+/// The pending moves stay secret, only who has moved is shown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimultaneousView {
+    pub players_moved: Vec<bool>,
+    pub last_stones: GroupVec<Point>,
+    pub collisions: Vec<Point>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GameStateView {
     FreePlacement(FreePlacementView),
     Play(PlayState),
     Scoring(ScoringState),
     Done(ScoringState),
+    Waiting(WaitingState),
+    Simultaneous(SimultaneousView),
+    KomiAuction(KomiAuctionState),
 }
 
 impl From<GameState> for GameStateView {
@@ -346,12 +1136,62 @@ impl From<GameState> for GameStateView {
                 players_ready: state.players_ready,
             }),
             GameState::Play(state) => GameStateView::Play(state),
-            GameState::Scoring(state) => GameStateView::Scoring(state),
-            GameState::Done(state) => GameStateView::Done(state),
+            // The region cache is only needed for toggling groups.
+            GameState::Scoring(state) => GameStateView::Scoring(ScoringState {
+                region_cache: None,
+                ..state
+            }),
+            GameState::Done(state) => GameStateView::Done(ScoringState {
+                region_cache: None,
+                ..state
+            }),
+            GameState::Waiting(state) => GameStateView::Waiting(state),
+            GameState::Simultaneous(state) => GameStateView::Simultaneous(SimultaneousView {
+                players_moved: state.pending.iter().map(|p| p.is_some()).collect(),
+                last_stones: state.last_stones,
+                collisions: state.collisions,
+            }),
+            GameState::KomiAuction(state) => GameStateView::KomiAuction(state),
         }
     }
 }
 
+/// A stone placement as broadcast to clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoveEvent {
+    pub point: Point,
+    /// Human readable coordinate for logs, text clients and screen readers.
+    #[serde(default)]
+    pub coord: Option<String>,
+}
+
+/// A group's liberties as broadcast to clients, for overlays like highlighting
+/// groups in atari. See `group_liberties`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupLiberties {
+    /// The group's first stone, see `Group::id`.
+    pub point: Point,
+    pub color: Color,
+    pub liberties: u32,
+}
+
+impl GroupLiberties {
+    pub fn in_atari(&self) -> bool {
+        self.liberties == 1
+    }
+}
+
+/// How one move changed the board, enough to follow a capture sequence like a
+/// ladder without replaying the game. See `Game::move_log`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MoveInfo {
+    /// Points that were empty before the move and hold a stone after it.
+    /// Empty for a pass.
+    pub placed: Vec<Point>,
+    /// Points whose stones the move removed.
+    pub captured: Vec<Point>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct GameView {
     // TODO: we need a separate state view since we have hidden information
@@ -367,13 +1207,16 @@ pub struct GameView {
     pub points: GroupVec<i32>,
     pub move_number: u32,
     pub clock: Option<GameClock>,
+    pub last_move: Vec<MoveEvent>,
+    /// Groups of `board`, so only stones the player can see are included.
+    pub groups: Vec<GroupLiberties>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GameHistory {
     pub board: Vec<u8>,
     pub board_visibility: Option<Vec<u16>>,
-    pub last_stone: Option<GroupVec<(u32, u32)>>,
+    pub last_stone: Option<GroupVec<Point>>,
     pub move_number: u32,
 }
 
@@ -386,6 +1229,11 @@ struct GameReplay {
     size: (u8, u8),
     #[serde(default)]
     seed: u64,
+    #[serde(default)]
+    setup: Vec<(Point, Color)>,
+    /// Older dumps draw the nigiri again from the seed.
+    #[serde(default)]
+    nigiri: Option<Nigiri>,
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -400,141 +1248,182 @@ impl Game {
         mods: GameModifier,
         seed: u64,
     ) -> Option<Game> {
-        if !seats.iter().all(|&t| t > 0 && t <= 4) {
-            return None;
-        }
+        GameBuilder::new()
+            .seats(seats)
+            .komi(komis)
+            .board_size(size.0, size.1)
+            .rules(mods)
+            .seed(seed)
+            .build()
+            .ok()
+    }
 
-        // 7 = 3 colors, rengo
-        // 4 = 4 colors
-        if !(1..=7).contains(&seats.len()) || !(1..=4).contains(&komis.len()) {
-            return None;
+    /// Loads a game from a replay dump. Can fail at any point due to changed rules...
+    /// Such is life.
+    pub fn load(dump: &[u8]) -> Option<Game> {
+        let replay: GameReplay = serde_cbor::from_slice(dump).ok()?;
+        Game::from_replay(replay)
+    }
+
+    /// Replays the game from the start up to `moves` moves, for analysis.
+    /// Seat changes don't count as moves, the ones before the next move are kept.
+    pub fn replay_to(&self, moves: usize) -> Option<Game> {
+        let mut replay = self.replay();
+        let mut played = 0;
+        let end = replay
+            .actions
+            .iter()
+            .position(|a| {
+                let is_move = !matches!(
+                    a.action,
+                    ReplayActionKind::TakeSeat(_) | ReplayActionKind::LeaveSeat(_)
+                );
+                if is_move {
+                    played += 1;
+                }
+                played > moves
+            })
+            .unwrap_or(replay.actions.len());
+        replay.actions.truncate(end);
+        Game::from_replay(replay)
+    }
+
+    fn from_replay(mut replay: GameReplay) -> Option<Game> {
+        // TODO: PUZZLE make replays conserve clocks
+        replay.mods.clock = None;
+        let mut builder = GameBuilder::new()
+            .seats(&replay.seats)
+            .komi(replay.komis)
+            .board_size(replay.size.0, replay.size.1)
+            .rules(replay.mods)
+            .seed(replay.seed)
+            .setup(replay.setup);
+        if let Some(nigiri) = replay.nigiri {
+            builder = builder.nigiri_result(nigiri);
         }
+        let mut game = builder.build_stored().ok()?;
 
-        // Don't allow huge boards
-        if size.0 > 25 || size.1 > 25 {
-            return None;
+        for action in replay.actions {
+            game.replay_action(action)?;
         }
 
-        let board = Board::empty(size.0 as _, size.1 as _, mods.toroidal.is_some());
-        let state = if let Some(rules) = &mods.hidden_move {
-            GameState::free_placement(
-                seats.len(),
-                komis.len(),
-                board.clone(),
-                rules.teams_share_stones,
-            )
-        } else {
-            GameState::play(seats.len())
-        };
+        Some(game)
+    }
 
-        let mut clock = mods
-            .clock
-            .as_ref()
-            .map(|r| GameClock::new(r.rule.clone(), seats.len()));
-
-        // TODO: PUZZLE use the original game creation time
-        if let Some(clock) = &mut clock {
-            use std::time;
-            clock.initialize_clocks(clock::Millisecond(
-                time::SystemTime::now()
-                    .duration_since(time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as i128,
-            ));
+    fn replay_action(&mut self, action: GameAction) -> Option<()> {
+        use ReplayActionKind::*;
+        match action.action {
+            TakeSeat(seat_id) => {
+                self.seat_player(action.user_id, seat_id as _).ok()?;
+            }
+            LeaveSeat(seat_id) => {
+                self.leave_seat(action.user_id, seat_id as _).ok()?;
+            }
+            Play(play) => {
+                let confirmed = MoveExpectation::confirmed();
+                self.make_action_expecting(action.user_id, play, Millisecond(0), Some(&confirmed))
+                    .ok()?;
+            }
+            ScoringTimeout => {
+                self.scoring_timeout().ok()?;
+            }
+            Undo(_) => {
+                self.make_action(action.user_id, ActionKind::Cancel, Millisecond(0))
+                    .ok()?;
+            }
         }
+        Some(())
+    }
 
-        let board_visibility = if mods.phantom.is_some() {
-            Some(VisibilityBoard::empty(
-                size.0 as _,
-                size.1 as _,
-                mods.toroidal.is_some(),
-            ))
-        } else {
-            None
+    /// Replays imported actions, checking the board against `expected_boards` given as
+    /// (move number, board) pairs. Stops at the first move that is illegal or diverges,
+    /// which usually means the source used different rules. Colors are decided when
+    /// the game is built, so build it with the source's seed or `GameBuilder::nigiri_result`.
+    pub fn verify_replay(
+        &mut self,
+        history: &[GameAction],
+        expected_boards: &[(u32, Board)],
+    ) -> Result<(), ReplayMismatch> {
+        let move_number = |game: &Game| game.shared.move_number();
+        let check = |game: &Game| {
+            let number = move_number(game);
+            let diverged = expected_boards
+                .iter()
+                .any(|(n, board)| *n == number && !board.same_position(&game.shared.board));
+            if diverged {
+                Err(ReplayMismatch::Board(number))
+            } else {
+                Ok(())
+            }
         };
 
-        let traitor = mods.traitor.as_ref().map(|rule| {
-            let mut stone_count = (size.0 as usize * size.1 as usize / komis.len()) as u32;
-            if mods.pixel {
-                stone_count /= 4;
+        check(self)?;
+        for action in history {
+            let before = move_number(self);
+            self.replay_action(action.clone())
+                .ok_or(ReplayMismatch::Illegal(before + 1))?;
+            if move_number(self) != before {
+                check(self)?;
             }
-
-            TraitorState::new(komis.len(), stone_count, seed, rule)
-        });
-
-        Some(Game {
-            state,
-            state_stack: Vec::new(),
-            shared: SharedState {
-                seats: seats.iter().map(|&t| Seat::new(Color(t))).collect(),
-                points: komis.clone(),
-                turn: 0,
-                pass_count: 0,
-                board: board.clone(),
-                board_visibility: board_visibility.clone(),
-                board_history: vec![BoardHistory {
-                    hash: board.hash(),
-                    board,
-                    board_visibility,
-                    state: GameState::play(seats.len()),
-                    points: komis.clone(),
-                    turn: 0,
-                    traitor: traitor.clone(),
-                }],
-                komis,
-                mods,
-                clock,
-                traitor,
-            },
-            actions: vec![],
-            seed,
-        })
+        }
+        Ok(())
     }
 
-    /// Loads a game from a replay dump. Can fail at any point due to changed rules...
-    /// Such is life.
-    pub fn load(dump: &[u8]) -> Option<Game> {
-        let mut replay: GameReplay = serde_cbor::from_slice(dump).ok()?;
-        // TODO: PUZZLE make replays conserve clocks
-        replay.mods.clock = None;
-        let mut game = Game::standard(
-            &replay.seats,
-            replay.komis,
-            replay.size,
-            replay.mods,
-            replay.seed,
-        )?;
-
-        for action in replay.actions {
-            use ReplayActionKind::*;
-            match action.action {
-                TakeSeat(seat_id) => {
-                    game.take_seat(action.user_id, seat_id as _).ok()?;
+    /// Replays imported actions, rejecting any placement onto an occupied point even
+    /// where our rules would allow it (eg. revealing a hidden stone). A record doing
+    /// that usually resolved a capture we didn't.
+    pub fn replay_strict(&mut self, history: &[GameAction]) -> Result<(), ReplayError> {
+        for action in history {
+            let move_index = self.shared.move_number() + 1;
+            let error = |reason| ReplayError { move_index, reason };
+            if let ReplayActionKind::Play(ActionKind::Place(x, y)) = action.action {
+                let point = Point::new(x, y);
+                let board = &self.shared.board;
+                // Pixel placements cover several points, the engine already checks those.
+                if !self.shared.mods.pixel
+                    && board.point_within(point)
+                    && !board.get_point(point).is_empty()
+                {
+                    return Err(error(MakeActionError::PointOccupied));
                 }
-                LeaveSeat(seat_id) => {
-                    game.leave_seat(action.user_id, seat_id as _).ok()?;
+            }
+            match &action.action {
+                ReplayActionKind::Play(play) => {
+                    let confirmed = MoveExpectation::confirmed();
+                    self.make_action_expecting(
+                        action.user_id,
+                        play.clone(),
+                        Millisecond(0),
+                        Some(&confirmed),
+                    )
+                    .map_err(error)?;
                 }
-                Play(play) => {
-                    game.make_action(action.user_id, play, Millisecond(0))
-                        .ok()?;
+                _ => {
+                    self.replay_action(action.clone())
+                        .ok_or(error(MakeActionError::Illegal))?;
                 }
             }
         }
+        Ok(())
+    }
 
-        Some(game)
+    /// Serializes the whole game as it is right now, including clocks and
+    /// scoring acceptances, so it can be resumed exactly with `deserialize_full`.
+    /// Unlike `dump` this is tied to the current data layout.
+    pub fn serialize_full(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        self.serialize(&mut serde_cbor::Serializer::new(&mut vec).packed_format())
+            .expect("Game serialization failed");
+        vec
+    }
+
+    pub fn deserialize_full(data: &[u8]) -> Result<Game, serde_cbor::Error> {
+        serde_cbor::from_slice(data)
     }
 
     /// Dumps the game to a (hopefully somewhat) stable replay format.
     pub fn dump(&self) -> Vec<u8> {
-        let shared = &self.shared;
-        let replay = GameReplay {
-            actions: self.actions.clone(),
-            komis: shared.komis.clone(),
-            size: (shared.board.width as _, shared.board.height as _),
-            seats: shared.seats.iter().map(|x| x.team.0).collect(),
-            mods: shared.mods.clone(),
-            seed: self.seed,
-        };
+        let replay = self.replay();
 
         let mut vec = Vec::new();
         replay
@@ -543,17 +1432,42 @@ impl Game {
         vec
     }
 
-    pub fn take_seat(&mut self, player_id: u64, seat_id: usize) -> Result<(), TakeSeatError> {
-        let shared = &mut self.shared;
+    fn replay(&self) -> GameReplay {
+        let shared = &self.shared;
+        // The builder does the nigiri swap again on load and the auction is replayed.
+        let swapped = shared.nigiri.is_some_and(|n| n.guesser == Color(2))
+            != shared.komi_auction.is_some_and(|a| a.swapped);
+        GameReplay {
+            actions: self.actions.clone(),
+            komis: shared.komis.clone(),
+            size: (shared.board.width() as _, shared.board.height() as _),
+            seats: shared
+                .seats
+                .iter()
+                .map(|x| if swapped { 3 - x.team.0 } else { x.team.0 })
+                .collect(),
+            mods: shared.mods.clone(),
+            seed: self.seed,
+            setup: self.setup.clone(),
+            nigiri: shared.nigiri,
+        }
+    }
 
-        if shared.mods.hidden_move.is_some() {
-            let held = shared.seats.iter().any(|x| x.player == Some(player_id));
+    pub fn take_seat(&mut self, player_id: u64, seat_id: usize) -> Result<(), TakeSeatError> {
+        let shared = &self.shared;
+        if builder::one_seat_per_player(&shared.mods) {
+            let held = shared.seats.iter().any(|s| s.player == Some(player_id));
             if held {
                 return Err(TakeSeatError::CanOnlyHoldOne);
             }
         }
+        self.seat_player(player_id, seat_id)
+    }
 
-        let seat = shared
+    /// Takes the seat without the checks for new players, stored games are replayed as they were.
+    fn seat_player(&mut self, player_id: u64, seat_id: usize) -> Result<(), TakeSeatError> {
+        let seat = self
+            .shared
             .seats
             .get_mut(seat_id)
             .ok_or(TakeSeatError::DoesNotExist)?;
@@ -578,6 +1492,9 @@ impl Game {
             return Err(TakeSeatError::NotOpen);
         }
         seat.player = None;
+        if let GameState::Waiting(state) = &mut self.state {
+            state.seat_left(seat_id);
+        }
         self.actions.push(GameAction::new(
             player_id,
             ReplayActionKind::LeaveSeat(seat_id as _),
@@ -586,10 +1503,21 @@ impl Game {
     }
 
     pub fn make_action(
+        &mut self,
+        player_id: u64,
+        action: ActionKind,
+        time: Millisecond,
+    ) -> Result<(), MakeActionError> {
+        self.make_action_expecting(player_id, action, time, None)
+    }
+
+    /// Makes an action, rejecting a placement whose result doesn't match `expected`.
+    pub fn make_action_expecting(
         &mut self,
         player_id: u64,
         mut action: ActionKind,
         time: Millisecond,
+        expected: Option<&MoveExpectation>,
     ) -> Result<(), MakeActionError> {
         if !self
             .shared
@@ -600,6 +1528,22 @@ impl Game {
             return Err(MakeActionError::NotPlayer);
         }
 
+        let teaching_undo = self.shared.mods.teaching_mode
+            && action == ActionKind::Cancel
+            && matches!(self.state, GameState::Play(_));
+        let reverted = if teaching_undo {
+            self.shared.move_info(self.shared.move_number())
+        } else {
+            None
+        };
+
+        // Only kept around when someone is listening.
+        let before = self
+            .shared
+            .event_sink
+            .as_ref()
+            .map(|_| (self.shared.board.clone(), GamePhase::of(&self.state)));
+
         let res = match &mut self.state {
             GameState::FreePlacement(state) => {
                 state.make_action(&mut self.shared, player_id, action.clone())
@@ -621,9 +1565,17 @@ impl Game {
 
                 if time_left.0 < -1000 {
                     action = ActionKind::Resign;
+                    if let Some(sink) = &self.shared.event_sink {
+                        sink.clock_ran_out(seat_idx);
+                    }
                 }
 
-                let res = state.make_action(&mut self.shared, player_id, action.clone());
+                let res = state.make_action_expecting(
+                    &mut self.shared,
+                    player_id,
+                    action.clone(),
+                    expected,
+                );
 
                 if res.is_ok() && !start_clock {
                     if let Some(clock) = &mut self.shared.clock {
@@ -633,37 +1585,119 @@ impl Game {
                 res
             }
             GameState::Scoring(state) => {
+                if self.shared.mods.scoring_uses_clock {
+                    if let Some(clock) = &mut self.shared.clock {
+                        // Each player only pays for the time they spent since their last action.
+                        let mut flagged = false;
+                        for (seat_idx, seat) in self.shared.seats.iter().enumerate() {
+                            if seat.player == Some(player_id)
+                                && clock.spend_time(seat_idx, time).0 < -1000
+                            {
+                                flagged = true;
+                                if let Some(sink) = &self.shared.event_sink {
+                                    sink.clock_ran_out(seat_idx);
+                                }
+                            }
+                        }
+                        if flagged {
+                            action = ActionKind::AcceptScore;
+                        }
+                    }
+                }
                 state.make_action(&mut self.shared, player_id, action.clone())
             }
             GameState::Done(_) => Err(MakeActionError::GameDone),
+            GameState::Waiting(state) => {
+                state.make_action(&mut self.shared, player_id, action.clone())
+            }
+            // TODO: clocks for simultaneous moves
+            GameState::Simultaneous(state) => {
+                state.make_action(&mut self.shared, player_id, action.clone())
+            }
+            GameState::KomiAuction(state) => {
+                state.make_action(&mut self.shared, player_id, action.clone())
+            }
         };
 
-        match res {
-            Ok(change) => {
-                match change {
-                    ActionChange::SwapState(new_state) => {
-                        self.state = new_state;
-                    }
-                    ActionChange::PushState(new_state) => {
-                        let old_state = std::mem::replace(&mut self.state, new_state);
-                        self.state_stack.push(old_state);
-                    }
-                    ActionChange::PopState => {
-                        self.state = self.state_stack.pop().expect("Empty state stack popped");
+        self.apply_change(res?, time)?;
+        self.shared.board_publisher.publish(&self.shared);
+        if let Some((board, phase)) = before {
+            self.emit_events(player_id, &action, &board, phase);
+        }
+        match reverted {
+            Some(info) => self
+                .actions
+                .push(GameAction::new(player_id, ReplayActionKind::Undo(info))),
+            None => self.actions.push(GameAction::play(player_id, action)),
+        }
 
-                        if let Some(clock) = &mut self.shared.clock {
-                            clock.initialize_clocks(time);
-                        }
+        Ok(())
+    }
+
+    /// Tells the event sink what an accepted action did, compared to the board
+    /// and phase before it.
+    fn emit_events(&self, player_id: u64, action: &ActionKind, board: &Board, phase: GamePhase) {
+        let sink = match &self.shared.event_sink {
+            Some(sink) => sink,
+            None => return,
+        };
+        sink.action_made(player_id, action);
+
+        // Undoing also removes stones, only placements capture.
+        if let ActionKind::Place(..) = action {
+            let mut captured = GroupVec::<u32>::new();
+            for (old, new) in board.points.iter().zip(&self.shared.board.points) {
+                if !old.is_empty() && new.is_empty() {
+                    let idx = old.as_usize() - 1;
+                    if captured.len() <= idx {
+                        captured.resize(idx + 1, 0);
                     }
-                    ActionChange::None => {}
+                    captured[idx] += 1;
                 }
+            }
+            for (idx, &stones) in captured.iter().enumerate() {
+                if stones > 0 {
+                    sink.stones_captured(Color(idx as u8 + 1), stones);
+                }
+            }
+        }
 
-                self.actions.push(GameAction::play(player_id, action));
+        let now = GamePhase::of(&self.state);
+        if now != phase {
+            sink.phase_changed(phase, now);
+        }
+    }
 
-                Ok(())
+    /// Moves to the state an action asked for.
+    fn apply_change(&mut self, change: ActionChange, time: Millisecond) -> MakeActionResult<()> {
+        let popped = self.state_stack.apply(&mut self.state, change)?;
+        if popped {
+            if let Some(clock) = &mut self.shared.clock {
+                clock.initialize_clocks(time);
             }
-            Err(e) => Err(e),
         }
+        Ok(())
+    }
+
+    /// Finishes scoring with the current count when players have run out of time to agree.
+    pub fn scoring_timeout(&mut self) -> Result<(), MakeActionError> {
+        let state = match &mut self.state {
+            GameState::Scoring(state) => state,
+            GameState::Done(_) => return Err(MakeActionError::GameDone),
+            _ => return Err(MakeActionError::Illegal),
+        };
+
+        // Scoring only ever swaps to the finished state, so the time is never used.
+        let change = state.timeout(&mut self.shared)?;
+        self.apply_change(change, Millisecond(0))?;
+        self.actions
+            .push(GameAction::new(0, ReplayActionKind::ScoringTimeout));
+        if let Some(sink) = &self.shared.event_sink {
+            sink.scoring_timed_out();
+            sink.phase_changed(GamePhase::Scoring, GamePhase::of(&self.state));
+        }
+
+        Ok(())
     }
 
     fn get_board_view(
@@ -761,7 +1795,11 @@ impl Game {
                     (board, None, 0)
                 }
             }
-            GameState::Scoring(_) | GameState::Done(_) => (board.points.clone(), None, 0),
+            GameState::Scoring(_)
+            | GameState::Done(_)
+            | GameState::Waiting(_)
+            | GameState::Simultaneous(_)
+            | GameState::KomiAuction(_) => (board.points.clone(), None, 0),
         };
 
         (board, board_visibility, hidden_stones_left)
@@ -769,7 +1807,7 @@ impl Game {
 
     pub fn get_view(&self, player_id: u64) -> GameView {
         let shared = &self.shared;
-        let game_done = matches!(self.state, GameState::Done(_));
+        let game_done = self.state.is_terminal();
         let game_active = matches!(self.state, GameState::Play(_));
         let (board, board_visibility, hidden_stones_left) = self.get_board_view(
             player_id,
@@ -778,6 +1816,7 @@ impl Game {
             &shared.board_visibility,
             game_done,
         );
+        let visible = shared.board.with_points(board.clone());
         GameView {
             state: self.state.clone().into(),
             seats: shared.seats.clone(),
@@ -785,15 +1824,72 @@ impl Game {
             board,
             board_visibility,
             hidden_stones_left,
-            size: (shared.board.width as u8, shared.board.height as u8),
+            size: (shared.board.width() as u8, shared.board.height() as u8),
             mods: shared.mods.clone(),
             points: shared.points.clone(),
-            move_number: shared.board_history.len() as u32 - 1,
+            move_number: shared.move_number(),
             clock: if game_active {
                 shared.clock.clone()
             } else {
                 None
             },
+            last_move: self.last_move_events(),
+            groups: group_liberties(&visible),
+        }
+    }
+
+    pub fn summary(&self) -> GameSummary {
+        self.shared.summary(&self.state)
+    }
+
+    pub fn move_number(&self) -> u32 {
+        self.shared.move_number()
+    }
+
+    pub fn seat_status(&self) -> Vec<SeatStatus> {
+        self.shared.seat_status(&self.state)
+    }
+
+    /// See `states::scoring::preview_score`.
+    pub fn preview_score(&self) -> (GroupVec<i32>, GameResult) {
+        crate::states::scoring::preview_score(&self.shared)
+    }
+
+    /// See `states::scoring::estimate_result`.
+    pub fn estimate_result(&self) -> GameResult {
+        crate::states::scoring::estimate_result(&self.shared)
+    }
+
+    /// `SharedState::move_info` of every move so far, in order.
+    pub fn move_log(&self) -> Vec<MoveInfo> {
+        (1..=self.move_number())
+            .filter_map(|n| self.shared.move_info(n))
+            .collect()
+    }
+
+    /// Describes the stones placed by the last move, if the game is being played.
+    pub fn last_move_events(&self) -> Vec<MoveEvent> {
+        let board = &self.shared.board;
+        match &self.state {
+            GameState::Play(PlayState {
+                last_stone: Some(points),
+                ..
+            }) => points
+                .iter()
+                .map(|&point| MoveEvent {
+                    point,
+                    coord: Some(board.format_coord(point)),
+                })
+                .collect(),
+            GameState::Simultaneous(state) => state
+                .last_stones
+                .iter()
+                .map(|&point| MoveEvent {
+                    point,
+                    coord: Some(board.format_coord(point)),
+                })
+                .collect(),
+            _ => Vec::new(),
         }
     }
 
@@ -806,7 +1902,7 @@ impl Game {
             ..
         } = &shared.board_history.get(turn as usize)?;
 
-        let game_done = matches!(self.state, GameState::Done(_));
+        let game_done = self.state.is_terminal();
 
         if !game_done && self.shared.mods.no_history {
             return None;
@@ -818,14 +1914,45 @@ impl Game {
         Some(GameHistory {
             board: board.iter().map(|x| x.0).collect(),
             board_visibility: board_visibility.map(|b| b.iter().map(|x| x.into_value()).collect()),
-            last_stone: state.assume::<PlayState>().last_stone.clone(),
+            last_stone: match state {
+                GameState::Simultaneous(state) => Some(state.last_stones.clone()),
+                state => state.assume::<PlayState>().last_stone.clone(),
+            },
             move_number: turn,
         })
     }
 }
 
+/// Groups of each color, indexed like points.
+/// Ends at the highest color that has stones on the board.
+pub fn find_groups_by_color(board: &Board) -> GroupVec<Vec<Group>> {
+    let mut by_color = GroupVec::<Vec<Group>>::new();
+    for group in find_groups(board) {
+        let idx = group.team.as_usize() - 1;
+        if by_color.len() <= idx {
+            by_color.resize(idx + 1, Vec::new());
+        }
+        by_color[idx].push(group);
+    }
+    by_color
+}
+
+/// Every group on `board` with its liberty count, in `Group::id` order.
+pub fn group_liberties(board: &Board) -> Vec<GroupLiberties> {
+    let mut groups = find_groups(board)
+        .iter()
+        .map(|g| GroupLiberties {
+            point: g.id().0,
+            color: g.team,
+            liberties: g.liberties as u32,
+        })
+        .collect::<Vec<_>>();
+    groups.sort_by_key(|g| g.point);
+    groups
+}
+
 pub fn find_groups(board: &Board) -> Vec<Group> {
-    let mut legal_points = board
+    let legal_points = board
         .points
         .iter()
         .enumerate()
@@ -838,41 +1965,52 @@ pub fn find_groups(board: &Board) -> Vec<Group> {
         })
         .collect::<Vec<_>>();
 
-    let mut seen = HashSet::new();
+    // The number of the group that last reached each point, counting from 1.
+    // A stone is only ever reached by its own group, so a marked stone is
+    // already grouped, while liberties are counted once per group.
+    let mut reached = vec![0; board.points.len()];
     let mut stack = VecDeque::new();
     let mut groups = Vec::new();
 
-    while let Some(point) = legal_points.pop() {
-        let mut group = Group::default();
-        group.alive = true;
-        group.team = board.get_point(point);
+    for &point in legal_points.iter().rev() {
+        if reached[board.coord_to_idx(point)] != 0 {
+            continue;
+        }
+        let mut group = Group {
+            alive: true,
+            team: board.get_point(point),
+            ..Group::default()
+        };
         if group.team.is_empty() {
             unreachable!("scanned an empty point");
         }
+        let mark = groups.len() + 1;
 
-        seen.insert(point);
-
+        reached[board.coord_to_idx(point)] = mark;
         stack.push_back(point);
 
         while let Some(point) = stack.pop_front() {
             group.points.push(point);
             for point in board.surrounding_points(point) {
-                if !seen.insert(point) {
+                let idx = board.coord_to_idx(point);
+                if reached[idx] == mark {
                     continue;
                 }
 
                 match board.get_point(point) {
                     x if x == group.team => {
+                        reached[idx] = mark;
                         stack.push_back(point);
-                        legal_points.retain(|x| *x != point);
                     }
-                    Color(0) => group.liberties += 1,
+                    Color(0) => {
+                        reached[idx] = mark;
+                        group.liberties += 1;
+                    }
                     _ => {}
                 }
             }
         }
 
-        seen.clear();
         groups.push(group);
     }
 
@@ -1,77 +1,344 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 use super::Color;
 
-#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
+/// A board is copied for every history entry and scoring pass, so the parts
+/// that never change are shared between the copies instead.
+#[derive(Clone, PartialEq, Hash)]
 pub struct Board<T = Color> {
+    config: Arc<BoardConfig>,
+    pub points: Vec<T>,
+}
+
+/// Size and topology of a board, fixed once the board is made.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct BoardConfig {
     pub width: u32,
     pub height: u32,
     pub toroidal: bool,
-    pub points: Vec<T>,
 }
 
-pub type Point = (u32, u32);
+/// How a board is written out, the same as before the config was split off.
+#[derive(Serialize)]
+#[serde(rename = "Board")]
+struct BoardRef<'a, T> {
+    width: u32,
+    height: u32,
+    toroidal: bool,
+    points: &'a [T],
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Board")]
+struct BoardOwned<T> {
+    width: u32,
+    height: u32,
+    toroidal: bool,
+    points: Vec<T>,
+}
+
+impl<T: Serialize> Serialize for Board<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BoardRef {
+            width: self.width(),
+            height: self.height(),
+            toroidal: self.toroidal(),
+            points: &self.points,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Board<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let board = BoardOwned::deserialize(deserializer)?;
+        Ok(Board {
+            config: Arc::new(BoardConfig {
+                width: board.width,
+                height: board.height,
+                toroidal: board.toroidal,
+            }),
+            points: board.points,
+        })
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Board<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Board")
+            .field("width", &self.width())
+            .field("height", &self.height())
+            .field("toroidal", &self.toroidal())
+            .field("points", &self.points)
+            .finish()
+    }
+}
+
+/// A coordinate on the board. Points are ordered like board indices, row by row.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "(u32, u32)", into = "(u32, u32)")]
+pub struct Point {
+    pub x: u32,
+    pub y: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PointClass {
+    Corner,
+    Edge,
+    Interior,
+}
+
+/// Column labels of human readable coordinates, I is skipped to avoid confusion with J.
+const COLUMN_LETTERS: &str = "ABCDEFGHJKLMNOPQRSTUVWXYZ";
+
+/// Which corner row 1 of a human readable coordinate is at. Only affects
+/// `format_coord_from` and `parse_coord`, points are always indexed from the top.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoordOrigin {
+    #[default]
+    BottomLeft,
+    TopLeft,
+}
+
+impl Point {
+    pub const fn new(x: u32, y: u32) -> Point {
+        Point { x, y }
+    }
+}
+
+impl std::fmt::Debug for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("").field(&self.x).field(&self.y).finish()
+    }
+}
+
+impl Ord for Point {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.y, self.x).cmp(&(other.y, other.x))
+    }
+}
+
+impl PartialOrd for Point {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<(u32, u32)> for Point {
+    fn from((x, y): (u32, u32)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl From<Point> for (u32, u32) {
+    fn from(p: Point) -> Self {
+        (p.x, p.y)
+    }
+}
+
+impl<T> Board<T> {
+    pub fn width(&self) -> u32 {
+        self.config.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.config.height
+    }
+
+    pub fn toroidal(&self) -> bool {
+        self.config.toroidal
+    }
+
+    /// A board of the same size and topology holding `points`, sharing the config.
+    pub fn with_points<U>(&self, points: Vec<U>) -> Board<U> {
+        debug_assert_eq!(points.len(), self.points.len());
+        Board {
+            config: self.config.clone(),
+            points,
+        }
+    }
+
+    /// An empty board of the same size and topology, sharing the config.
+    pub fn empty_like<U: Copy + Default>(&self) -> Board<U> {
+        self.with_points(vec![U::default(); self.points.len()])
+    }
+}
 
 impl<T: Copy + Default> Board<T> {
     pub fn empty(width: u32, height: u32, toroidal: bool) -> Self {
         Board {
-            width,
-            height,
-            toroidal,
+            config: Arc::new(BoardConfig {
+                width,
+                height,
+                toroidal,
+            }),
             points: vec![T::default(); (width * height) as usize],
         }
     }
 
-    pub fn point_within(&self, (x, y): Point) -> bool {
-        (0..self.width).contains(&x) && (0..self.height).contains(&y)
+    /// The same stones on the same topology. Use this where positions are compared,
+    /// so anything else a board may carry later doesn't make equal positions differ.
+    pub fn same_position(&self, other: &Board<T>) -> bool
+    where
+        T: PartialEq,
+    {
+        self.config == other.config && self.points == other.points
+    }
+
+    pub fn point_within(&self, p: Point) -> bool {
+        (0..self.width()).contains(&p.x) && (0..self.height()).contains(&p.y)
+    }
+
+    /// Panics if the point is outside the board, regardless of topology.
+    /// Use `try_get_point` for coordinates that may be off the board.
+    pub fn get_point(&self, p: Point) -> T {
+        self.points[self.coord_to_idx(p)]
     }
 
-    pub fn get_point(&self, (x, y): Point) -> T {
-        self.points[(y * self.width + x) as usize]
+    /// Returns `None` for coordinates off the board. Toroidal boards wrap instead.
+    pub fn try_get_point(&self, x: i32, y: i32) -> Option<T> {
+        self.wrap_point(x, y).map(|p| self.get_point(p))
     }
 
-    pub fn point_mut(&mut self, (x, y): Point) -> &mut T {
-        &mut self.points[(y * self.width + x) as usize]
+    pub fn point_mut(&mut self, p: Point) -> &mut T {
+        let idx = self.coord_to_idx(p);
+        &mut self.points[idx]
     }
 
     pub fn idx_to_coord(&self, idx: usize) -> Option<Point> {
         if idx < self.points.len() {
-            Some((idx as u32 % self.width, idx as u32 / self.width))
+            Some(Point::new(
+                idx as u32 % self.width(),
+                idx as u32 / self.width(),
+            ))
         } else {
             None
         }
     }
 
+    pub fn coord_to_idx(&self, p: Point) -> usize {
+        (p.y * self.width() + p.x) as usize
+    }
+
+    /// Formats a point as a human readable coordinate, eg. "Q16" on a 19x19 board.
+    /// Columns skip the letter I and rows are counted from the bottom.
+    pub fn format_coord(&self, p: Point) -> String {
+        self.format_coord_from(p, CoordOrigin::BottomLeft)
+    }
+
+    /// Like `format_coord`, with rows counted from `origin`.
+    pub fn format_coord_from(&self, p: Point, origin: CoordOrigin) -> String {
+        let column = COLUMN_LETTERS.chars().nth(p.x as usize).unwrap_or('?');
+        let row = match origin {
+            CoordOrigin::BottomLeft => self.height() - p.y,
+            CoordOrigin::TopLeft => p.y + 1,
+        };
+        format!("{}{}", column, row)
+    }
+
+    /// Reads a coordinate written by `format_coord_from` with the same `origin`.
+    /// Letters can be either case. `None` if it's not on the board.
+    pub fn parse_coord(&self, text: &str, origin: CoordOrigin) -> Option<Point> {
+        let mut chars = text.trim().chars();
+        let column = chars.next()?.to_ascii_uppercase();
+        let x = COLUMN_LETTERS.find(column)? as u32;
+        let row = chars.as_str().parse::<u32>().ok()?;
+        if row == 0 || row > self.height() {
+            return None;
+        }
+        let y = match origin {
+            CoordOrigin::BottomLeft => self.height() - row,
+            CoordOrigin::TopLeft => row - 1,
+        };
+        Some(Point::new(x, y)).filter(|&p| self.point_within(p))
+    }
+
     pub fn wrap_point(&self, x: i32, y: i32) -> Option<Point> {
-        wrap_point(x, y, self.width as i32, self.height as i32, self.toroidal)
+        wrap_point(
+            x,
+            y,
+            self.width() as i32,
+            self.height() as i32,
+            self.toroidal(),
+        )
     }
 
+    /// Neighbors are wanted as points here, so this goes through `wrap_point`
+    /// rather than `try_get_point`, which only gives the color.
     pub fn surrounding_points(&self, p: Point) -> impl Iterator<Item = Point> {
-        let x = p.0 as i32;
-        let y = p.1 as i32;
-        let width = self.width as i32;
-        let height = self.height as i32;
-        let toroidal = self.toroidal;
+        let x = p.x as i32;
+        let y = p.y as i32;
+        let width = self.width() as i32;
+        let height = self.height() as i32;
+        let toroidal = self.toroidal();
         [(-1, 0), (1, 0), (0, -1), (0, 1)]
             .iter()
             .filter_map(move |&(dx, dy)| wrap_point(x + dx, y + dy, width, height, toroidal))
     }
 
+    /// Number of orthogonal neighbors of a point.
+    pub fn degree(&self, p: Point) -> usize {
+        self.surrounding_points(p).count()
+    }
+
+    /// Where the point is relative to the edges. Toroidal boards have no edges.
+    pub fn point_class(&self, p: Point) -> PointClass {
+        match self.degree(p) {
+            0..=2 => PointClass::Corner,
+            3 => PointClass::Edge,
+            _ => PointClass::Interior,
+        }
+    }
+
     pub fn surrounding_diagonal_points(&self, p: Point) -> impl Iterator<Item = Point> {
-        let x = p.0 as i32;
-        let y = p.1 as i32;
-        let width = self.width as i32;
-        let height = self.height as i32;
-        let toroidal = self.toroidal;
+        let x = p.x as i32;
+        let y = p.y as i32;
+        let width = self.width() as i32;
+        let height = self.height() as i32;
+        let toroidal = self.toroidal();
         [(-1, -1), (1, -1), (1, 1), (-1, 1)]
             .iter()
             .filter_map(move |&(dx, dy)| wrap_point(x + dx, y + dy, width, height, toroidal))
     }
 }
 
+impl Board<Color> {
+    /// Draws the board as text for logs and test failures, labeled like `format_coord`.
+    /// Black is `X`, white `O` and other colors their number.
+    pub fn to_ascii(&self) -> String {
+        let columns = (0..self.width())
+            .map(|x| {
+                let coord = self.format_coord(Point::new(x, 0));
+                coord.chars().next().unwrap_or('?').to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut out = format!("   {}\n", columns);
+        for y in 0..self.height() {
+            let row = (0..self.width())
+                .map(|x| match self.get_point(Point::new(x, y)).0 {
+                    0 => '.',
+                    1 => 'X',
+                    2 => 'O',
+                    c => char::from_digit(c as u32, 36).unwrap_or('?'),
+                })
+                .map(String::from)
+                .collect::<Vec<_>>()
+                .join(" ");
+            out += &format!("{:>2} {}\n", self.height() - y, row);
+        }
+        if self.toroidal() {
+            out += "   (edges wrap around)\n";
+        }
+        out
+    }
+}
+
 impl<T: Hash> Board<T> {
     pub fn hash(&self) -> u64 {
         let mut hasher = DefaultHasher::new();
@@ -82,24 +349,17 @@ impl<T: Hash> Board<T> {
 
 fn wrap_point(x: i32, y: i32, width: i32, height: i32, toroidal: bool) -> Option<Point> {
     if x >= 0 && x < width && y >= 0 && y < height {
-        Some((x as u32, y as u32))
+        Some(Point::new(x as u32, y as u32))
     } else if toroidal {
-        let x = if x < 0 {
-            x + width
-        } else if x >= width {
-            x - width
-        } else {
-            x
-        };
-        let y = if y < 0 {
-            y + height
-        } else if y >= height {
-            y - height
-        } else {
-            y
-        };
-        Some((x as u32, y as u32))
+        // Any distance off the board wraps, not just a single step.
+        Some(Point::new(
+            x.rem_euclid(width) as u32,
+            y.rem_euclid(height) as u32,
+        ))
     } else {
         None
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,195 @@
+use super::*;
+
+/// The same points under another size or topology.
+fn reshaped(board: &Board, width: u32, height: u32, toroidal: bool) -> Board {
+    Board {
+        config: Arc::new(BoardConfig {
+            width,
+            height,
+            toroidal,
+        }),
+        points: board.points.clone(),
+    }
+}
+
+#[test]
+fn point_ordering_follows_rows() {
+    assert_eq!(Point::new(3, 4), Point::from((3, 4)));
+    assert_ne!(Point::new(3, 4), Point::new(4, 3));
+
+    assert!(Point::new(5, 0) < Point::new(0, 1));
+    assert!(Point::new(0, 1) < Point::new(1, 1));
+
+    let mut points = vec![Point::new(1, 1), Point::new(2, 0), Point::new(0, 1)];
+    points.sort();
+    assert_eq!(
+        points,
+        vec![Point::new(2, 0), Point::new(0, 1), Point::new(1, 1)]
+    );
+}
+
+#[test]
+fn point_index_round_trip() {
+    let board = Board::<Color>::empty(7, 5, false);
+
+    for idx in 0..board.points.len() {
+        let point = board.idx_to_coord(idx).unwrap();
+        assert!(board.point_within(point));
+        assert_eq!(board.coord_to_idx(point), idx);
+    }
+    assert_eq!(board.idx_to_coord(35), None);
+
+    assert_eq!(board.idx_to_coord(8), Some(Point::new(1, 1)));
+    assert_eq!(<(u32, u32)>::from(Point::new(1, 1)), (1, 1));
+}
+
+#[test]
+fn clones_share_config_but_not_points() {
+    let board = Board::<Color>::empty(3, 2, true);
+    let mut other = board.clone();
+    assert!(Arc::ptr_eq(&board.config, &other.config));
+
+    *other.point_mut(Point::new(1, 1)) = Color(1);
+    assert_eq!(board.get_point(Point::new(1, 1)), Color::empty());
+
+    let labels = board.empty_like::<u32>();
+    assert!(Arc::ptr_eq(&board.config, &labels.config));
+    assert_eq!(labels.points.len(), 6);
+}
+
+#[test]
+fn same_position_compares_stones_and_topology() {
+    let mut board = Board::empty(3, 2, false);
+    *board.point_mut(Point::new(1, 0)) = Color(1);
+
+    let mut other = board.clone();
+    assert!(board.same_position(&other));
+    *other.point_mut(Point::new(2, 1)) = Color(2);
+    assert!(!board.same_position(&other));
+
+    let other = reshaped(&board, 3, 2, true);
+    assert!(!board.same_position(&other));
+
+    // The same points in a different shape.
+    let other = reshaped(&board, 2, 3, false);
+    assert!(!board.same_position(&other));
+}
+
+#[test]
+fn try_get_point_respects_topology() {
+    let mut board = Board::empty(3, 2, false);
+    *board.point_mut(Point::new(2, 1)) = Color(1);
+
+    assert_eq!(board.try_get_point(2, 1), Some(Color(1)));
+    assert_eq!(board.try_get_point(0, 0), Some(Color::empty()));
+    assert_eq!(board.try_get_point(-1, 1), None);
+    assert_eq!(board.try_get_point(3, 0), None);
+    assert_eq!(board.try_get_point(0, 2), None);
+
+    let board = reshaped(&board, 3, 2, true);
+    assert_eq!(board.try_get_point(-1, 1), Some(Color(1)));
+    assert_eq!(board.try_get_point(2, -1), Some(Color(1)));
+    assert_eq!(board.try_get_point(3, 0), Some(Color::empty()));
+
+    // More than a board width off still wraps.
+    assert_eq!(board.try_get_point(-10, 1), Some(Color(1)));
+    assert_eq!(board.try_get_point(8, -7), Some(Color(1)));
+    let board = reshaped(&board, 3, 2, false);
+    assert_eq!(board.try_get_point(-10, 1), None);
+}
+
+#[test]
+fn surrounding_points_are_orthogonal() {
+    let board = Board::<Color>::empty(5, 5, false);
+    let mut corner = board
+        .surrounding_points(Point::new(0, 0))
+        .collect::<Vec<_>>();
+    corner.sort();
+    assert_eq!(corner, vec![Point::new(1, 0), Point::new(0, 1)]);
+
+    let board = reshaped(&board, 5, 5, true);
+    let mut corner = board
+        .surrounding_points(Point::new(0, 0))
+        .collect::<Vec<_>>();
+    corner.sort();
+    assert_eq!(
+        corner,
+        vec![
+            Point::new(1, 0),
+            Point::new(4, 0),
+            Point::new(0, 1),
+            Point::new(0, 4)
+        ]
+    );
+}
+
+#[test]
+fn point_class_on_rectangular_board() {
+    let board = Board::<Color>::empty(5, 3, false);
+    let class = |x, y| board.point_class(Point::new(x, y));
+
+    assert_eq!(class(0, 0), PointClass::Corner);
+    assert_eq!(class(4, 2), PointClass::Corner);
+    assert_eq!(class(2, 0), PointClass::Edge);
+    assert_eq!(class(0, 1), PointClass::Edge);
+    assert_eq!(class(2, 1), PointClass::Interior);
+
+    assert_eq!(board.degree(Point::new(0, 0)), 2);
+    assert_eq!(board.degree(Point::new(4, 1)), 3);
+    assert_eq!(board.degree(Point::new(1, 1)), 4);
+}
+
+#[test]
+fn toroidal_board_is_all_interior() {
+    let board = Board::<Color>::empty(5, 3, true);
+    for idx in 0..board.points.len() {
+        let point = board.idx_to_coord(idx).unwrap();
+        assert_eq!(board.degree(point), 4);
+        assert_eq!(board.point_class(point), PointClass::Interior);
+    }
+}
+
+#[test]
+fn ascii_rendering() {
+    let mut board = Board::empty(3, 2, false);
+    *board.point_mut(Point::new(0, 0)) = Color(1);
+    *board.point_mut(Point::new(2, 1)) = Color(2);
+    assert_eq!(board.to_ascii(), "   A B C\n 2 X . .\n 1 . . O\n");
+
+    let mut board = Board::empty(10, 10, true);
+    *board.point_mut(Point::new(8, 0)) = Color(3);
+    *board.point_mut(Point::new(9, 9)) = Color(4);
+    let ascii = board.to_ascii();
+    let lines = ascii.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "   A B C D E F G H J K");
+    assert_eq!(lines[1], "10 . . . . . . . . 3 .");
+    assert_eq!(lines[10], " 1 . . . . . . . . . 4");
+    assert_eq!(lines[11], "   (edges wrap around)");
+}
+
+#[test]
+fn coord_origin_only_changes_labels() {
+    let board = Board::<Color>::empty(19, 19, false);
+    let point = Point::new(15, 3);
+    assert_eq!(board.format_coord(point), "Q16");
+    assert_eq!(board.format_coord_from(point, CoordOrigin::TopLeft), "Q4");
+
+    let board = Board::<Color>::empty(9, 7, false);
+    for origin in [CoordOrigin::BottomLeft, CoordOrigin::TopLeft] {
+        for idx in 0..board.points.len() {
+            let point = board.idx_to_coord(idx).unwrap();
+            let text = board.format_coord_from(point, origin);
+            assert_eq!(board.parse_coord(&text, origin), Some(point), "{}", text);
+            let lowercase = text.to_lowercase();
+            assert_eq!(board.parse_coord(&lowercase, origin), Some(point));
+        }
+    }
+    assert_eq!(
+        board.parse_coord("J1", CoordOrigin::TopLeft),
+        Some(Point::new(8, 0))
+    );
+    assert_eq!(board.parse_coord("I1", CoordOrigin::TopLeft), None);
+    assert_eq!(board.parse_coord("K1", CoordOrigin::TopLeft), None);
+    assert_eq!(board.parse_coord("A8", CoordOrigin::BottomLeft), None);
+    assert_eq!(board.parse_coord("A0", CoordOrigin::BottomLeft), None);
+}
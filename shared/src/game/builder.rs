@@ -0,0 +1,570 @@
+use super::clock::{self, GameClock};
+use super::{
+    Board, BoardHistory, Color, ColorHandicap, ColorScores, Game, GameModifier, GameState,
+    GameStateStack, GroupVec, HandicapPlacement, Point, Seat, SharedState, ToroidalGo,
+    VisibilityBoard,
+};
+use crate::states::play::traitor::TraitorState;
+use crate::states::PlayState;
+use serde::{Deserialize, Serialize};
+
+use rand::prelude::*;
+use rand_pcg::Lcg64Xsh32;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BuildError {
+    /// The seats don't fit the colors or rules, see `validate_seats`.
+    Seats(SeatError),
+    /// Too few or too many seats.
+    SeatCount,
+    /// Too few or too many colors.
+    ColorCount,
+    BoardSize,
+    /// The handicap can't be placed on this board or with these colors.
+    Handicap,
+    /// The first player's color has no seat.
+    FirstPlayer,
+    /// A setup stone is off the board or of a color outside the game.
+    Setup,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SeatError {
+    /// A seat refers to a color outside the game.
+    InvalidColor,
+    /// A color has no seat to play it.
+    ColorWithoutSeat,
+    /// Some colors have more seats than others.
+    UnevenTeams,
+    /// A player holds several seats in a variant where each plays one.
+    DoubleBooked,
+}
+
+impl From<SeatError> for BuildError {
+    fn from(error: SeatError) -> Self {
+        BuildError::Seats(error)
+    }
+}
+
+/// Checks that `seats` make sense for a game of `color_count` colors.
+/// Zen go rotates the colors of the seats, so they only need to be valid colors.
+pub fn validate_seats(
+    seats: &[Seat],
+    color_count: usize,
+    mods: &GameModifier,
+) -> Result<(), SeatError> {
+    if !seats
+        .iter()
+        .all(|s| !s.team.is_empty() && s.team.as_usize() <= color_count)
+    {
+        return Err(SeatError::InvalidColor);
+    }
+
+    if mods.zen_go.is_none() {
+        let counts = (1..=color_count as u8)
+            .map(|c| seats.iter().filter(|s| s.team == Color(c)).count())
+            .collect::<Vec<_>>();
+        if counts.contains(&0) {
+            return Err(SeatError::ColorWithoutSeat);
+        }
+        if counts.iter().any(|&c| c != counts[0]) {
+            return Err(SeatError::UnevenTeams);
+        }
+    }
+
+    if one_seat_per_player(mods) {
+        for (idx, seat) in seats.iter().enumerate() {
+            let held_before =
+                seat.player.is_some() && seats[..idx].iter().any(|s| s.player == seat.player);
+            if held_before {
+                return Err(SeatError::DoubleBooked);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Hidden and simultaneous moves are secret per player, so one player
+/// can't play for several seats.
+pub(super) fn one_seat_per_player(mods: &GameModifier) -> bool {
+    mods.hidden_move.is_some() || mods.simultaneous.is_some()
+}
+
+/// Validates and creates new games.
+#[derive(Debug, Clone)]
+pub struct GameBuilder {
+    seats: Vec<u8>,
+    komis: GroupVec<i32>,
+    size: (u8, u8),
+    toroidal: Option<bool>,
+    handicap: Option<u32>,
+    first_player: Option<Color>,
+    setup: Vec<(Point, Color)>,
+    mods: GameModifier,
+    seed: u64,
+    nigiri: Option<Nigiri>,
+}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        GameBuilder {
+            seats: vec![1, 2],
+            komis: GroupVec::from(&[0, 15][..]),
+            size: (19, 19),
+            toroidal: None,
+            handicap: None,
+            first_player: None,
+            setup: Vec::new(),
+            mods: GameModifier::default(),
+            seed: 0,
+            nigiri: None,
+        }
+    }
+}
+
+impl GameBuilder {
+    /// A standard two player game on 19x19 with 7.5 komi.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The color of each seat.
+    pub fn seats(mut self, seats: &[u8]) -> Self {
+        self.seats = seats.to_vec();
+        self
+    }
+
+    /// Point adjustment of each color in half points. This also decides the number of colors.
+    pub fn komi(mut self, komis: GroupVec<i32>) -> Self {
+        self.komis = komis;
+        self
+    }
+
+    pub fn board_size(mut self, width: u8, height: u8) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    pub fn toroidal(mut self, toroidal: bool) -> Self {
+        self.toroidal = Some(toroidal);
+        self
+    }
+
+    /// Number of fixed handicap stones given to black.
+    pub fn handicap(mut self, stones: u32) -> Self {
+        self.handicap = Some(stones);
+        self
+    }
+
+    /// Color that moves first, eg. white in go problems.
+    pub fn first_player(mut self, color: Color) -> Self {
+        self.first_player = Some(color);
+        self
+    }
+
+    /// Stones on the board before the first move, eg. a go problem. They are put down
+    /// as given, even groups without liberties, and don't count as moves.
+    pub fn setup(mut self, stones: Vec<(Point, Color)>) -> Self {
+        self.setup = stones;
+        self
+    }
+
+    pub fn rules(mut self, mods: GameModifier) -> Self {
+        self.mods = mods;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// A nigiri that was already held, eg. in a stored game. Without one the
+    /// nigiri is drawn from the seed.
+    pub fn nigiri_result(mut self, nigiri: Nigiri) -> Self {
+        self.nigiri = Some(nigiri);
+        self
+    }
+
+    /// The limits games were held to before the builder, checked for stored games too.
+    fn validate_limits(&self) -> Result<(), BuildError> {
+        if !self.seats.iter().all(|&t| t > 0 && t <= 4) {
+            return Err(BuildError::Seats(SeatError::InvalidColor));
+        }
+
+        // 7 = 3 colors, rengo
+        // 4 = 4 colors
+        if !(1..=7).contains(&self.seats.len()) {
+            return Err(BuildError::SeatCount);
+        }
+        if !(1..=4).contains(&self.komis.len()) {
+            return Err(BuildError::ColorCount);
+        }
+
+        // Don't allow huge boards
+        let (width, height) = self.size;
+        if width > 25 || height > 25 {
+            return Err(BuildError::BoardSize);
+        }
+
+        let setup_valid = self.setup.iter().all(|(point, color)| {
+            point.x < width as u32
+                && point.y < height as u32
+                && !color.is_empty()
+                && color.as_usize() <= self.komis.len()
+        });
+        if !setup_valid {
+            return Err(BuildError::Setup);
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), BuildError> {
+        self.validate_limits()?;
+        let color_count = self.komis.len();
+
+        let seats = self
+            .seats
+            .iter()
+            .map(|&t| Seat::new(Color(t)))
+            .collect::<Vec<_>>();
+        validate_seats(&seats, color_count, &self.mods)?;
+
+        let (width, height) = self.size;
+        if width == 0 || height == 0 {
+            return Err(BuildError::BoardSize);
+        }
+
+        if self.mods.handicap > 0 {
+            handicap_points(self.mods.handicap, width as u32, height as u32)
+                .ok_or(BuildError::Handicap)?;
+            if color_count != 2 {
+                return Err(BuildError::Handicap);
+            }
+        }
+
+        let handicaps = &self.mods.color_handicaps;
+        for (idx, handicap) in handicaps.iter().enumerate() {
+            let valid = handicap.stones > 0
+                && !handicap.color.is_empty()
+                && handicap.color.as_usize() <= color_count
+                && handicaps[..idx].iter().all(|h| h.color != handicap.color)
+                && self.mods.handicap == 0;
+            if !valid {
+                return Err(BuildError::Handicap);
+            }
+        }
+        color_handicap_points(handicaps, width as u32, height as u32)
+            .ok_or(BuildError::Handicap)?;
+
+        if (self.mods.nigiri || self.mods.komi_auction) && color_count != 2 {
+            return Err(BuildError::ColorCount);
+        }
+
+        if let Some(color) = self.mods.first_player {
+            if !self.seats.contains(&color.0) {
+                return Err(BuildError::FirstPlayer);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn build(self) -> Result<Game, BuildError> {
+        let builder = self.with_overrides();
+        builder.validate()?;
+        builder.create()
+    }
+
+    /// Builds a stored game with only the checks of `validate_limits`, so games
+    /// created before a rule was validated still load.
+    pub(super) fn build_stored(self) -> Result<Game, BuildError> {
+        let builder = self.with_overrides();
+        builder.validate_limits()?;
+        builder.create()
+    }
+
+    fn with_overrides(mut self) -> Self {
+        if let Some(toroidal) = self.toroidal {
+            self.mods.toroidal = if toroidal { Some(ToroidalGo {}) } else { None };
+        }
+        if let Some(handicap) = self.handicap {
+            self.mods.handicap = handicap;
+        }
+        if let Some(color) = self.first_player {
+            self.mods.first_player = Some(color);
+        }
+        self
+    }
+
+    fn create(self) -> Result<Game, BuildError> {
+        let GameBuilder {
+            seats,
+            komis,
+            size,
+            setup,
+            mods,
+            seed,
+            nigiri: held_nigiri,
+            ..
+        } = self;
+
+        let mut seats = seats;
+        let nigiri = if mods.nigiri {
+            let nigiri = held_nigiri.unwrap_or_else(|| nigiri(seed));
+            // The guesser holds black, so losing the guess swaps every seat's color.
+            if nigiri.guesser == Color(2) {
+                for team in &mut seats {
+                    *team = 3 - *team;
+                }
+            }
+            Some(nigiri)
+        } else {
+            None
+        };
+
+        let mut board = Board::empty(size.0 as _, size.1 as _, mods.toroidal.is_some());
+        let mut first_player = Color(1);
+        if mods.handicap > 0 {
+            for point in handicap_points(mods.handicap, board.width(), board.height())
+                .ok_or(BuildError::Handicap)?
+            {
+                *board.point_mut(point) = Color(1);
+            }
+            // White moves first after the handicap stones.
+            first_player = Color(2);
+        }
+        for (point, color) in
+            color_handicap_points(&mods.color_handicaps, board.width(), board.height())
+                .ok_or(BuildError::Handicap)?
+        {
+            *board.point_mut(point) = color;
+        }
+        for &(point, color) in &setup {
+            *board.point_mut(point) = color;
+        }
+        let first_seat = |color: Color| seats.iter().position(|&t| t == color.0);
+        let turn = mods
+            .first_player
+            .and_then(first_seat)
+            .or_else(|| color_handicap_turn(&mods.color_handicaps, &seats))
+            .or_else(|| first_seat(first_player))
+            .unwrap_or(0);
+
+        let mut points = komis.clone();
+        for handicap in &mods.color_handicaps {
+            *points.score_for_mut(handicap.color) -= handicap.stones as i32 * mods.point_unit();
+        }
+
+        let mut clock = mods
+            .clock
+            .as_ref()
+            .map(|r| GameClock::new(r.rule.clone(), seats.len()));
+
+        // TODO: PUZZLE use the original game creation time
+        if let Some(clock) = &mut clock {
+            use std::time;
+            clock.initialize_clocks(clock::Millisecond(
+                time::SystemTime::now()
+                    .duration_since(time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as i128,
+            ));
+        }
+
+        let board_visibility = if mods.phantom.is_some() {
+            Some(VisibilityBoard::empty(
+                size.0 as _,
+                size.1 as _,
+                mods.toroidal.is_some(),
+            ))
+        } else {
+            None
+        };
+
+        let traitor = mods.traitor.as_ref().map(|rule| {
+            let mut stone_count = (size.0 as usize * size.1 as usize / komis.len()) as u32;
+            if mods.pixel {
+                stone_count /= 4;
+            }
+
+            TraitorState::new(komis.len(), stone_count, seed, rule)
+        });
+
+        let shared = SharedState {
+            seats: seats.iter().map(|&t| Seat::new(Color(t))).collect(),
+            points: points.clone(),
+            turn,
+            pass_count: 0,
+            board: board.clone(),
+            board_visibility: board_visibility.clone(),
+            board_history: vec![BoardHistory {
+                hash: board.hash(),
+                board,
+                board_visibility,
+                state: GameState::Play(PlayState::starting(seats.len(), komis.len(), &mods)),
+                points,
+                turn,
+                traitor: traitor.clone(),
+            }],
+            komis,
+            mods,
+            clock,
+            traitor,
+            nigiri,
+            komi_auction: None,
+            event_sink: None,
+            board_publisher: Default::default(),
+        };
+
+        Ok(Game {
+            state: GameState::new_game(&shared),
+            state_stack: GameStateStack::default(),
+            shared,
+            actions: vec![],
+            seed,
+            setup,
+        })
+    }
+}
+
+/// The traditional way of picking colors: one player grabs a handful of stones
+/// and the other guesses whether there's an odd or even number of them.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Nigiri {
+    pub stones: u32,
+    pub guessed_odd: bool,
+    /// Black if the guess was right.
+    pub guesser: Color,
+}
+
+/// Nigiri with the game's seed, so replays pick the same colors.
+pub fn nigiri(seed: u64) -> Nigiri {
+    let mut rng = Lcg64Xsh32::seed_from_u64(seed);
+    let stones = rng.gen_range(1, 21);
+    let guessed_odd = rng.gen();
+    let guesser = if (stones % 2 == 1) == guessed_odd {
+        Color(1)
+    } else {
+        Color(2)
+    };
+    Nigiri {
+        stones,
+        guessed_odd,
+        guesser,
+    }
+}
+
+/// Fixed stones of every color in `handicaps`, sharing the star points in order.
+/// Returns `None` if they don't fit on the board.
+fn color_handicap_points(
+    handicaps: &[ColorHandicap],
+    width: u32,
+    height: u32,
+) -> Option<Vec<(Point, Color)>> {
+    let colors = handicaps
+        .iter()
+        .filter(|h| h.placement == HandicapPlacement::Fixed)
+        .flat_map(|h| std::iter::repeat_n(h.color, h.stones as usize))
+        .collect::<Vec<_>>();
+    if colors.is_empty() {
+        return Some(Vec::new());
+    }
+    // A single stone still goes on a corner star point.
+    let points = handicap_points(colors.len().max(2) as u32, width, height)?;
+    Some(points.into_iter().zip(colors).collect())
+}
+
+/// A color placing its handicap freely moves first. Otherwise the colors that
+/// got fixed stones have already moved and the turn starts after them.
+fn color_handicap_turn(handicaps: &[ColorHandicap], seats: &[u8]) -> Option<usize> {
+    if let Some(free) = handicaps
+        .iter()
+        .find(|h| h.placement == HandicapPlacement::Free)
+    {
+        return seats.iter().position(|&t| t == free.color.0);
+    }
+    let last = seats
+        .iter()
+        .rposition(|&t| handicaps.iter().any(|h| h.color.0 == t))?;
+    Some((last + 1) % seats.len())
+}
+
+/// Rating points that make one stone of difference on 19x19.
+const RATING_PER_STONE: f32 = 100.0;
+/// A stone is worth about 14 points, in half points.
+const STONE_VALUE: f32 = 28.0;
+/// 7.5 komi for even games, in half points.
+const EVEN_KOMI: i32 = 15;
+
+/// Suggests fixed handicap stones for black and komi for white in half points,
+/// for a game where white is rated `rating_diff` points above black. Only advisory.
+///
+/// 1. The difference in stones is `rating_diff / 100`, scaled by the board area
+///    relative to 19x19 since a stone matters more on a small board.
+/// 2. Black gets the whole stones if that's at least two and they fit the board,
+///    see `handicap_points`. Otherwise the stones are left for komi.
+/// 3. Komi starts from 7.5 in even games and 0.5 with handicap stones, and the
+///    rest of the difference is taken off at 14 points per stone.
+pub fn suggest_handicap_komi(rating_diff: i32, width: u32, height: u32) -> (u32, i32) {
+    let area = (width * height) as f32 / (19.0 * 19.0);
+    let difference = rating_diff.max(0) as f32 / RATING_PER_STONE * area;
+
+    let mut stones = (difference.floor() as u32).min(9);
+    while stones >= 2 && handicap_points(stones, width, height).is_none() {
+        stones -= 1;
+    }
+    if stones < 2 {
+        stones = 0;
+    }
+
+    let base = if stones > 0 { 1 } else { EVEN_KOMI };
+    let rest = difference - stones as f32;
+    (stones, base - (rest * STONE_VALUE).round() as i32)
+}
+
+/// Traditional handicap placement on the star points.
+/// Returns `None` if the board is too small or lacks the needed star points.
+pub fn handicap_points(stones: u32, width: u32, height: u32) -> Option<Vec<Point>> {
+    if !(2..=9).contains(&stones) || width < 7 || height < 7 {
+        return None;
+    }
+    // Side and center stones need a middle line.
+    if stones > 4 && (width.is_multiple_of(2) || height.is_multiple_of(2)) {
+        return None;
+    }
+
+    let edge = |size: u32| if size >= 13 { 3 } else { 2 };
+    let (left, top) = (edge(width), edge(height));
+    let (right, bottom) = (width - 1 - left, height - 1 - top);
+    let (middle_x, middle_y) = (width / 2, height / 2);
+
+    let mut points = vec![
+        Point::new(right, top),
+        Point::new(left, bottom),
+        Point::new(right, bottom),
+        Point::new(left, top),
+    ];
+
+    match stones {
+        2..=4 => points.truncate(stones as usize),
+        5 => points.push(Point::new(middle_x, middle_y)),
+        6 | 7 => {
+            points.push(Point::new(left, middle_y));
+            points.push(Point::new(right, middle_y));
+        }
+        _ => {
+            points.push(Point::new(left, middle_y));
+            points.push(Point::new(right, middle_y));
+            points.push(Point::new(middle_x, top));
+            points.push(Point::new(middle_x, bottom));
+        }
+    }
+    if stones == 7 || stones == 9 {
+        points.push(Point::new(middle_x, middle_y));
+    }
+
+    Some(points)
+}
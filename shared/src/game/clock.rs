@@ -101,6 +101,13 @@ impl GameClock {
         }
     }
 
+    /// Time left for the given clock as of the last time it was updated.
+    pub fn time_left(&self, clock_idx: usize) -> Millisecond {
+        match &self.clocks[clock_idx] {
+            PlayerClock::Plain { time_left, .. } => *time_left,
+        }
+    }
+
     /// Returns the time left for the given clock at current timestamp `time`.
     pub fn advance_clock(&mut self, clock_idx: usize, time: Millisecond) -> Millisecond {
         let clock = &mut self.clocks[clock_idx];
@@ -117,6 +124,18 @@ impl GameClock {
         }
     }
 
+    /// Like `advance_clock`, but only this clock is counted from `time` on.
+    /// Used when players act simultaneously instead of taking turns.
+    pub fn spend_time(&mut self, clock_idx: usize, time: Millisecond) -> Millisecond {
+        let time_left = self.advance_clock(clock_idx, time);
+        match &mut self.clocks[clock_idx] {
+            PlayerClock::Plain { last_time, .. } => {
+                *last_time = time;
+            }
+        }
+        time_left
+    }
+
     pub fn end_turn(&mut self, clock_idx: usize, time: Millisecond) {
         let clock = &mut self.clocks[clock_idx];
 
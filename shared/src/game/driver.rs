@@ -0,0 +1,52 @@
+use super::clock::Millisecond;
+use super::{ActionKind, Game, GameResult, GameState, MakeActionError, TakeSeatError};
+
+/// Plays a game without a server, for tests and tools.
+/// The clock only moves when told to, so timed games stay reproducible.
+pub struct GameDriver {
+    pub game: Game,
+    time: Millisecond,
+}
+
+impl GameDriver {
+    pub fn new(game: Game) -> Self {
+        GameDriver {
+            game,
+            time: Millisecond(0),
+        }
+    }
+
+    /// Seats player `n + 1` in seat `n`.
+    pub fn seated(mut game: Game) -> Result<Self, TakeSeatError> {
+        for idx in 0..game.shared.seats.len() {
+            game.take_seat(idx as u64 + 1, idx)?;
+        }
+        Ok(GameDriver::new(game))
+    }
+
+    pub fn play(&mut self, player_id: u64, action: ActionKind) -> Result<(), MakeActionError> {
+        self.game.make_action(player_id, action, self.time)
+    }
+
+    /// Plays `actions` in order, stopping at the first one that fails.
+    pub fn play_all(&mut self, actions: &[(u64, ActionKind)]) -> Result<(), MakeActionError> {
+        for (player_id, action) in actions {
+            self.play(*player_id, action.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Lets time pass before the next action.
+    pub fn wait(&mut self, time: Millisecond) {
+        self.time.0 += time.0;
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.game.state
+    }
+
+    /// The result once the game is over.
+    pub fn result(&self) -> Option<&GameResult> {
+        self.game.state.result()
+    }
+}
@@ -0,0 +1,20 @@
+use super::{ActionKind, Color, GamePhase};
+
+/// Receives engine events, eg. for a server to record metrics.
+/// Install one in `SharedState::event_sink`. Every method does nothing by default.
+pub trait EventSink: Send + Sync {
+    /// An action was accepted, after its state change has been applied.
+    fn action_made(&self, _player_id: u64, _action: &ActionKind) {}
+
+    /// Stones of `color` were removed from the board by a placement.
+    fn stones_captured(&self, _color: Color, _stones: u32) {}
+
+    fn phase_changed(&self, _from: GamePhase, _to: GamePhase) {}
+
+    /// The seat ran out of time, its action was replaced with a resignation
+    /// or, while scoring, an accept.
+    fn clock_ran_out(&self, _seat_idx: usize) {}
+
+    /// Scoring was finished with the current count, see `Game::scoring_timeout`.
+    fn scoring_timed_out(&self) {}
+}
@@ -1,15 +1,20 @@
 use super::Board;
 use super::Game;
+use super::Point;
 use std::fmt::Write;
 
 struct SGFWriter {
     buffer: String,
+    /// Points of the current node for AE, AB and AW. A property can only appear
+    /// once per node, so they are written together when the node ends.
+    setup: [Vec<(char, char)>; 3],
 }
 
 impl SGFWriter {
     fn new() -> SGFWriter {
         SGFWriter {
             buffer: "(;FF[4]GM[1]".to_string(),
+            setup: Default::default(),
         }
     }
 
@@ -21,37 +26,47 @@ impl SGFWriter {
         }
     }
 
-    fn set_point(&mut self, point: (u32, u32), color: u8) {
-        let name = match color {
-            0 => "AE",
-            1 => "AB",
-            2 => "AW",
-            _ => unreachable!(),
-        };
+    fn handicap(&mut self, stones: u32) {
+        let _ = write!(&mut self.buffer, "HA[{}]", stones);
+    }
 
-        let (x, y) = self.point(point);
+    fn set_point(&mut self, point: Point, color: u8) {
+        let point = self.point(point);
+        self.setup[color as usize].push(point);
+    }
 
-        let _ = write!(&mut self.buffer, "{}[{}{}]", name, x, y);
+    fn flush_setup(&mut self) {
+        for (name, points) in ["AE", "AB", "AW"].iter().zip(&mut self.setup) {
+            if points.is_empty() {
+                continue;
+            }
+            self.buffer.push_str(name);
+            for (x, y) in points.drain(..) {
+                let _ = write!(&mut self.buffer, "[{}{}]", x, y);
+            }
+        }
     }
 
-    fn point(&self, point: (u32, u32)) -> (char, char) {
+    fn point(&self, point: Point) -> (char, char) {
         let mut letters = 'a'..='z';
-        let x = letters.clone().nth(point.0 as usize).unwrap_or('a');
-        let y = letters.nth(point.1 as usize).unwrap_or('a');
+        let x = letters.clone().nth(point.x as usize).unwrap_or('a');
+        let y = letters.nth(point.y as usize).unwrap_or('a');
         (x, y)
     }
 
-    fn label(&mut self, point: (u32, u32), text: &str) {
+    fn label(&mut self, point: Point, text: &str) {
         let (x, y) = self.point(point);
 
         let _ = write!(&mut self.buffer, "LB[{}{}:{}]", x, y, text);
     }
 
     fn end_turn(&mut self) {
+        self.flush_setup();
         let _ = write!(&mut self.buffer, ";");
     }
 
     fn finish(mut self) -> String {
+        self.flush_setup();
         let _ = write!(&mut self.buffer, ")");
         self.buffer
     }
@@ -59,12 +74,17 @@ impl SGFWriter {
 
 /// Write a simple single-variation representation of the game.
 /// Limited to two colors so has to use markers for the other colors and hidden stones.
+/// The root node holds the starting position, like fixed handicap stones, and node N
+/// holds move N as counted by `Game::move_number`.
 pub fn sgf_export(game: &Game) -> String {
     let mut writer = SGFWriter::new();
-    let (width, height) = (game.shared.board.width, game.shared.board.height);
+    let (width, height) = (game.shared.board.width(), game.shared.board.height());
     writer.size((width, height));
+    if game.shared.mods.handicap > 0 {
+        writer.handicap(game.shared.mods.handicap);
+    }
 
-    let mut last = Board::empty(width, height, game.shared.board.toroidal);
+    let mut last = Board::empty(width, height, game.shared.board.toroidal());
 
     for history in &game.shared.board_history {
         let board = &history.board;
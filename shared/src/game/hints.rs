@@ -0,0 +1,105 @@
+use super::{Color, Point, SharedState};
+
+/// Suggests moves for teaching, eg. joseki in the corners.
+/// The engine never asks for hints, servers call a provider when a player wants one.
+pub trait HintProvider: Send + Sync {
+    /// Suggested points for `color` to play, with a label for each.
+    fn hints(&self, shared: &SharedState, color: Color) -> Vec<(Point, String)>;
+}
+
+/// A known corner position and its usual continuations.
+/// Points are counted from the corner, so `(3, 3)` is the star point.
+struct BookEntry {
+    own: &'static [(u32, u32)],
+    other: &'static [(u32, u32)],
+    moves: &'static [(u32, u32, &'static str)],
+}
+
+const BOOK: &[BookEntry] = &[
+    BookEntry {
+        own: &[],
+        other: &[],
+        moves: &[(3, 3, "Star point"), (2, 3, "Komoku")],
+    },
+    BookEntry {
+        own: &[],
+        other: &[(3, 3)],
+        moves: &[(2, 5, "Low approach"), (2, 2, "3-3 invasion")],
+    },
+    BookEntry {
+        own: &[(3, 3)],
+        other: &[(2, 5)],
+        moves: &[(5, 2, "Knight's move answer")],
+    },
+    BookEntry {
+        own: &[(3, 3)],
+        other: &[(2, 2)],
+        moves: &[(2, 3, "Block")],
+    },
+];
+
+/// Points from each corner that a position is read from.
+const CORNER_SIZE: u32 = 6;
+
+/// Looks up each corner in a small built-in book of openings. Corners are
+/// matched in all eight orientations, so one entry covers every mirror image.
+/// Gives nothing on boards too small for separate corners, on toroidal boards
+/// and while stones are hidden, as the hints would give them away.
+#[derive(Debug, Clone, Default)]
+pub struct CornerBook;
+
+impl HintProvider for CornerBook {
+    fn hints(&self, shared: &SharedState, color: Color) -> Vec<(Point, String)> {
+        let board = &shared.board;
+        if board.toroidal()
+            || shared.board_visibility.is_some()
+            || board.width() < 2 * CORNER_SIZE
+            || board.height() < 2 * CORNER_SIZE
+        {
+            return Vec::new();
+        }
+
+        let mut hints: Vec<(Point, String)> = Vec::new();
+        for &(flip_x, flip_y) in &[(false, false), (true, false), (false, true), (true, true)] {
+            for transpose in [false, true] {
+                let to_board = |(a, b): (u32, u32)| {
+                    let (u, v) = if transpose { (b, a) } else { (a, b) };
+                    let x = if flip_x { board.width() - 1 - u } else { u };
+                    let y = if flip_y { board.height() - 1 - v } else { v };
+                    Point::new(x, y)
+                };
+
+                let mut own = Vec::new();
+                let mut other = Vec::new();
+                for a in 0..CORNER_SIZE {
+                    for b in 0..CORNER_SIZE {
+                        match board.get_point(to_board((a, b))) {
+                            Color(0) => {}
+                            c if c == color => own.push((a, b)),
+                            _ => other.push((a, b)),
+                        }
+                    }
+                }
+
+                let matches = |expected: &[(u32, u32)], found: &[(u32, u32)]| {
+                    expected.len() == found.len() && expected.iter().all(|p| found.contains(p))
+                };
+                let entry = BOOK
+                    .iter()
+                    .find(|e| matches(e.own, &own) && matches(e.other, &other));
+                let entry = match entry {
+                    Some(e) => e,
+                    None => continue,
+                };
+                for &(a, b, label) in entry.moves {
+                    let point = to_board((a, b));
+                    if board.get_point(point).is_empty() && !hints.iter().any(|h| h.0 == point) {
+                        hints.push((point, label.to_string()));
+                    }
+                }
+            }
+        }
+
+        hints
+    }
+}
@@ -0,0 +1,214 @@
+use super::{Color, GameBuilder, GroupVec, Point};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SgfError {
+    /// The text doesn't start with a game tree, `(;`.
+    NoGameTree,
+    /// A property value is missing its closing `]`.
+    Unterminated,
+    /// `SZ` isn't a size like `19` or `19:13`.
+    BadSize(String),
+    /// `KM` isn't a number of half points, like `6.5` or `7`.
+    BadKomi(String),
+    /// `HA` isn't a stone count.
+    BadHandicap(String),
+    /// A setup stone isn't a point on the board.
+    BadPoint(String),
+    /// `HA` disagrees with the number of black setup stones.
+    HandicapMismatch { handicap: u32, stones: usize },
+    /// `PL` isn't `B` or `W`.
+    BadPlayer(String),
+    /// A point has both a black and a white setup stone.
+    SetupOverlap(Point),
+}
+
+/// The starting position and rules from the root node of an SGF file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SgfSetup {
+    pub size: (u32, u32),
+    /// Komi for white in half points.
+    pub komi: i32,
+    pub handicap: u32,
+    pub black: Vec<Point>,
+    pub white: Vec<Point>,
+    /// The color to move from `PL`, eg. in go problems.
+    pub to_move: Option<Color>,
+}
+
+impl SgfSetup {
+    /// A two color game starting from this position. Setup stones are put on
+    /// the board as they are, so problems can have positions no game would reach.
+    /// Without `PL`, white moves first after handicap stones and black otherwise.
+    pub fn builder(&self) -> GameBuilder {
+        let stones = self
+            .black
+            .iter()
+            .map(|&p| (p, Color(1)))
+            .chain(self.white.iter().map(|&p| (p, Color(2))))
+            .collect();
+        let default_first = if self.handicap > 0 && !self.black.is_empty() {
+            Color(2)
+        } else {
+            Color(1)
+        };
+        GameBuilder::new()
+            .board_size(self.size.0 as u8, self.size.1 as u8)
+            .komi(GroupVec::from(&[0, self.komi][..]))
+            .setup(stones)
+            .first_player(self.to_move.unwrap_or(default_first))
+    }
+}
+
+/// Reads the root node of `sgf`, normalizing what other programs write.
+/// Komi can be an integer or a decimal of half points. A missing `HA` is
+/// taken from the black setup stones when there are no white ones, and a
+/// given `HA` has to match them unless the handicap is placed freely later.
+pub fn sgf_setup(sgf: &str) -> Result<SgfSetup, SgfError> {
+    let properties = root_properties(sgf)?;
+    let get = |name: &str| {
+        properties
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, values)| &values[..])
+            .unwrap_or(&[])
+    };
+
+    let size = match get("SZ").first() {
+        Some(value) => parse_size(value)?,
+        None => (19, 19),
+    };
+    let komi = match get("KM").first() {
+        Some(value) => parse_komi(value)?,
+        None => 0,
+    };
+    let black = get("AB")
+        .iter()
+        .map(|value| parse_point(value, size))
+        .collect::<Result<Vec<_>, _>>()?;
+    let white = get("AW")
+        .iter()
+        .map(|value| parse_point(value, size))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(&point) = black.iter().find(|p| white.contains(p)) {
+        return Err(SgfError::SetupOverlap(point));
+    }
+    let to_move = match get("PL").first().map(|v| v.trim()) {
+        Some("B") | Some("b") => Some(Color(1)),
+        Some("W") | Some("w") => Some(Color(2)),
+        Some(value) => return Err(SgfError::BadPlayer(value.to_string())),
+        None => None,
+    };
+
+    let handicap = match get("HA").first() {
+        Some(value) => {
+            let handicap = value
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| SgfError::BadHandicap(value.clone()))?;
+            if !black.is_empty() && handicap as usize != black.len() {
+                return Err(SgfError::HandicapMismatch {
+                    handicap,
+                    stones: black.len(),
+                });
+            }
+            handicap
+        }
+        None if white.is_empty() => black.len() as u32,
+        None => 0,
+    };
+
+    Ok(SgfSetup {
+        size,
+        komi,
+        handicap,
+        black,
+        white,
+        to_move,
+    })
+}
+
+/// Properties of the first node with their values, unescaped.
+fn root_properties(sgf: &str) -> Result<Vec<(String, Vec<String>)>, SgfError> {
+    let rest = sgf.trim_start();
+    let rest = rest.strip_prefix('(').ok_or(SgfError::NoGameTree)?;
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix(';').ok_or(SgfError::NoGameTree)?;
+
+    let mut properties: Vec<(String, Vec<String>)> = Vec::new();
+    let mut chars = rest.chars();
+    let mut name = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            ';' | '(' | ')' => break,
+            '[' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => value.extend(chars.next()),
+                        Some(']') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(SgfError::Unterminated),
+                    }
+                }
+                // Further values of a property follow without its name.
+                match properties.last_mut() {
+                    Some((last, values)) if name.is_empty() || *last == name => values.push(value),
+                    _ => properties.push((name.clone(), vec![value])),
+                }
+                name.clear();
+            }
+            c if c.is_ascii_uppercase() => name.push(c),
+            // Old files mix lowercase letters into the names, eg. `KoMi`.
+            _ => {}
+        }
+    }
+
+    Ok(properties)
+}
+
+fn parse_size(value: &str) -> Result<(u32, u32), SgfError> {
+    let bad = || SgfError::BadSize(value.to_string());
+    let parse = |v: &str| {
+        v.trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|&v| (1..=52).contains(&v))
+            .ok_or_else(bad)
+    };
+    match value.split_once(':') {
+        Some((width, height)) => Ok((parse(width)?, parse(height)?)),
+        None => {
+            let size = parse(value)?;
+            Ok((size, size))
+        }
+    }
+}
+
+fn parse_komi(value: &str) -> Result<i32, SgfError> {
+    let bad = || SgfError::BadKomi(value.to_string());
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(0);
+    }
+    let doubled = value.parse::<f64>().map_err(|_| bad())? * 2.0;
+    if !doubled.is_finite() || doubled.fract() != 0.0 || doubled.abs() > i32::MAX as f64 {
+        return Err(bad());
+    }
+    Ok(doubled as i32)
+}
+
+fn parse_point(value: &str, size: (u32, u32)) -> Result<Point, SgfError> {
+    let coord = |c: u8| match c {
+        b'a'..=b'z' => Some((c - b'a') as u32),
+        b'A'..=b'Z' => Some((c - b'A') as u32 + 26),
+        _ => None,
+    };
+    let point = match value.as_bytes() {
+        &[x, y] => coord(x).zip(coord(y)).map(|(x, y)| Point::new(x, y)),
+        _ => None,
+    };
+    point
+        .filter(|p| p.x < size.0 && p.y < size.1)
+        .ok_or_else(|| SgfError::BadPoint(value.to_string()))
+}
@@ -0,0 +1,148 @@
+use super::clock::Millisecond;
+use super::{ActionKind, Game, MakeActionError, ReplayActionKind};
+
+/// A position in a `ReviewSession`.
+pub struct ReviewNode {
+    pub game: Game,
+    /// Moves into the mainline this node branches off, for variations started there.
+    pub mainline_move: usize,
+    /// The action that led here from the parent, `None` for a mainline position.
+    pub action: Option<(u64, ActionKind)>,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+/// Explores variations of a game's history without touching the game.
+/// Positions on the mainline are rebuilt with `Game::replay_to` and variation
+/// moves are played on copies with the normal rules, so only legal moves end
+/// up in the tree.
+pub struct ReviewSession {
+    mainline: Game,
+    nodes: Vec<ReviewNode>,
+    current: usize,
+}
+
+impl ReviewSession {
+    /// Starts at the last position of `game`.
+    /// Returns `None` if the game can't replay its own history.
+    pub fn new(game: &Game) -> Option<Self> {
+        // Counted the way `replay_to` counts, seat changes aren't moves.
+        let moves = game
+            .actions
+            .iter()
+            .filter(|a| {
+                !matches!(
+                    a.action,
+                    ReplayActionKind::TakeSeat(_) | ReplayActionKind::LeaveSeat(_)
+                )
+            })
+            .count();
+        let mut session = ReviewSession {
+            mainline: game.clone(),
+            nodes: Vec::new(),
+            current: 0,
+        };
+        session.current = session.mainline_node(moves)?;
+        Some(session)
+    }
+
+    /// Goes to the mainline position after `moves` actions, not counting seat changes.
+    /// Returns `None` if the history can't be replayed that far.
+    pub fn goto(&mut self, moves: usize) -> Option<&Game> {
+        self.current = self.mainline_node(moves)?;
+        Some(self.game())
+    }
+
+    fn mainline_node(&mut self, moves: usize) -> Option<usize> {
+        let existing = self
+            .nodes
+            .iter()
+            .position(|n| n.parent.is_none() && n.mainline_move == moves);
+        if let Some(idx) = existing {
+            return Some(idx);
+        }
+        let game = self.mainline.replay_to(moves)?;
+        self.nodes.push(ReviewNode {
+            game,
+            mainline_move: moves,
+            action: None,
+            parent: None,
+            children: Vec::new(),
+        });
+        Some(self.nodes.len() - 1)
+    }
+
+    /// Plays `action` from the current position, moving into the variation.
+    /// Playing an action already explored from here reuses its node.
+    pub fn play(&mut self, player_id: u64, action: ActionKind) -> Result<&Game, MakeActionError> {
+        let parent = &self.nodes[self.current];
+        let existing = parent
+            .children
+            .iter()
+            .copied()
+            .find(|&idx| self.nodes[idx].action.as_ref() == Some(&(player_id, action.clone())));
+        if let Some(idx) = existing {
+            self.current = idx;
+            return Ok(self.game());
+        }
+
+        let mut game = parent.game.clone();
+        game.make_action(player_id, action.clone(), Millisecond(0))?;
+        let node = ReviewNode {
+            game,
+            mainline_move: parent.mainline_move,
+            action: Some((player_id, action)),
+            parent: Some(self.current),
+            children: Vec::new(),
+        };
+        self.nodes.push(node);
+        let idx = self.nodes.len() - 1;
+        self.nodes[self.current].children.push(idx);
+        self.current = idx;
+        Ok(self.game())
+    }
+
+    /// Steps back towards the mainline. Returns false at a mainline position.
+    pub fn back(&mut self) -> bool {
+        match self.nodes[self.current].parent {
+            Some(parent) => {
+                self.current = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.nodes[self.current].game
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Goes to a node returned by `current`.
+    pub fn select(&mut self, node: usize) -> Option<&Game> {
+        if node >= self.nodes.len() {
+            return None;
+        }
+        self.current = node;
+        Some(self.game())
+    }
+
+    pub fn nodes(&self) -> &[ReviewNode] {
+        &self.nodes
+    }
+
+    /// The actions played since leaving the mainline, oldest first.
+    pub fn variation(&self) -> Vec<(u64, ActionKind)> {
+        let mut actions = Vec::new();
+        let mut node = &self.nodes[self.current];
+        while let (Some(action), Some(parent)) = (&node.action, node.parent) {
+            actions.push(action.clone());
+            node = &self.nodes[parent];
+        }
+        actions.reverse();
+        actions
+    }
+}
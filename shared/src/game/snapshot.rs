@@ -0,0 +1,64 @@
+use super::{Board, SharedState};
+use std::sync::{Arc, Mutex};
+
+/// The board as of the last committed action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardSnapshot {
+    pub board: Board,
+    pub move_number: u32,
+    pub turn: usize,
+}
+
+impl BoardSnapshot {
+    pub(super) fn of(shared: &SharedState) -> Self {
+        BoardSnapshot {
+            board: shared.board.clone(),
+            move_number: shared.move_number(),
+            turn: shared.turn,
+        }
+    }
+}
+
+type Slot = Arc<Mutex<Arc<BoardSnapshot>>>;
+
+/// Lets other threads read the board without locking the game, see `SharedState::board_reader`.
+/// A new snapshot is swapped in whole after each action, so readers never see a
+/// move half applied. Cloning a game gives the clone a publisher of its own.
+#[derive(Default)]
+pub struct BoardPublisher {
+    slot: Option<Slot>,
+}
+
+impl Clone for BoardPublisher {
+    fn clone(&self) -> Self {
+        BoardPublisher::default()
+    }
+}
+
+impl BoardPublisher {
+    /// Stores the current board for readers. Skipped until someone asks for a reader.
+    pub(super) fn publish(&self, shared: &SharedState) {
+        if let Some(slot) = &self.slot {
+            let snapshot = Arc::new(BoardSnapshot::of(shared));
+            *slot.lock().expect("Board snapshot lock poisoned") = snapshot;
+        }
+    }
+
+    /// `current` is used if this is the first reader.
+    pub(super) fn reader(&mut self, current: BoardSnapshot) -> BoardReader {
+        let slot = self
+            .slot
+            .get_or_insert_with(|| Arc::new(Mutex::new(Arc::new(current))));
+        BoardReader(slot.clone())
+    }
+}
+
+/// A handle to the latest `BoardSnapshot` of a game, cheap to clone and send to other threads.
+#[derive(Clone)]
+pub struct BoardReader(Slot);
+
+impl BoardReader {
+    pub fn load(&self) -> Arc<BoardSnapshot> {
+        self.0.lock().expect("Board snapshot lock poisoned").clone()
+    }
+}
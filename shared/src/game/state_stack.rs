@@ -0,0 +1,57 @@
+use super::{ActionChange, GameState, MakeActionError};
+use serde::{Deserialize, Serialize};
+
+/// States put on hold under the current one.
+///
+/// Scoring is pushed over play, so `Cancel` or `ResumePlay` during scoring pops back
+/// to the play state that was left. Its pass markers are cleared when scoring
+/// starts, so play goes on as if nobody had passed. Finishing a game
+/// swaps the top state, so the suspended play state stays below `Done`.
+/// The bottom state can never be popped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GameStateStack(Vec<GameState>);
+
+impl GameStateStack {
+    /// Number of states on hold.
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn suspended(&self) -> &[GameState] {
+        &self.0
+    }
+
+    /// Suspends `current` and makes `state` the current one.
+    pub fn push(&mut self, current: &mut GameState, state: GameState) {
+        let old_state = std::mem::replace(current, state);
+        self.0.push(old_state);
+    }
+
+    /// Drops `current` and goes back to the state it was pushed over.
+    pub fn pop(&mut self, current: &mut GameState) -> Result<(), MakeActionError> {
+        *current = self.0.pop().ok_or(MakeActionError::NoPreviousState)?;
+        Ok(())
+    }
+
+    /// Applies the change an action asked for.
+    /// Returns whether the current state was popped, as clocks restart after that.
+    pub fn apply(
+        &mut self,
+        current: &mut GameState,
+        change: ActionChange,
+    ) -> Result<bool, MakeActionError> {
+        match change {
+            ActionChange::None => Ok(false),
+            ActionChange::SwapState(state) => {
+                *current = state;
+                Ok(false)
+            }
+            ActionChange::PushState(state) => {
+                self.push(current, state);
+                Ok(false)
+            }
+            ActionChange::PopState => self.pop(current).map(|_| true),
+        }
+    }
+}
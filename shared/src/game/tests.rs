@@ -1,12 +1,15 @@
 use super::*;
+use crate::test_support::game_with_position;
+use std::collections::HashSet;
 
 #[test]
 fn seats() {
     let mut game = Game::standard(
-        &vec![1, 2],
+        &[1, 2],
         GroupVec::from(&[0, 15][..]),
         (9, 9),
         GameModifier::default(),
+        0,
     )
     .unwrap();
 
@@ -54,6 +57,1577 @@ fn seats() {
     assert_eq!(game.leave_seat(300, 1), Err(TakeSeatError::NotOpen));
 }
 
+#[test]
+fn last_move_has_coordinate() {
+    let mut game = Game::standard(
+        &[1, 2],
+        GroupVec::from(&[0, 15][..]),
+        (19, 19),
+        GameModifier::default(),
+        0,
+    )
+    .unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+
+    game.make_action(100, ActionKind::Place(15, 3), Millisecond(0))
+        .unwrap();
+
+    let events = game.get_view(100).last_move;
+    assert_eq!(
+        events,
+        vec![MoveEvent {
+            point: Point::new(15, 3),
+            coord: Some("Q16".to_string()),
+        }]
+    );
+
+    let packed = serde_cbor::to_vec(&events).unwrap();
+    let unpacked: Vec<MoveEvent> = serde_cbor::from_slice(&packed).unwrap();
+    assert_eq!(unpacked, events);
+    assert!(packed.windows(3).any(|w| w == b"Q16"));
+}
+
+#[test]
+fn builder_creates_handicap_game() {
+    let game = GameBuilder::new()
+        .board_size(9, 9)
+        .toroidal(false)
+        .komi(GroupVec::from(&[0, 1][..]))
+        .handicap(3)
+        .build()
+        .unwrap();
+
+    assert_eq!(game.shared.mods.handicap, 3);
+    assert_eq!(game.shared.turn, 1);
+    assert_eq!(game.shared.board.get_point(Point::new(6, 2)), Color(1));
+    assert_eq!(game.shared.board.get_point(Point::new(2, 6)), Color(1));
+    assert_eq!(game.shared.board.get_point(Point::new(6, 6)), Color(1));
+    assert_eq!(game.shared.board.get_point(Point::new(2, 2)), Color(0));
+    assert_eq!(
+        game.shared.board_history[0].board, game.shared.board,
+        "handicap stones are part of the initial position"
+    );
+}
+
+#[test]
+fn handicap_stones_are_setup_not_moves() {
+    let mut game = GameBuilder::new().handicap(4).build().unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    assert_eq!(game.move_number(), 0);
+
+    game.make_action(200, ActionKind::Place(2, 2), Millisecond(0))
+        .unwrap();
+    assert_eq!(game.move_number(), 1);
+    assert_eq!(game.summary().move_number, 1);
+
+    // The root node has the handicap, every following node is a move.
+    let sgf = export::sgf_export(&game);
+    assert_eq!(sgf, "(;FF[4]GM[1]SZ[19]HA[4]AB[dd][pd][dp][pp];AW[cc];)");
+}
+
+#[test]
+fn sgf_komi_is_read_in_half_points() {
+    let komi = |sgf: &str| import::sgf_setup(sgf).map(|setup| setup.komi);
+    assert_eq!(komi("(;KM[6.5])"), Ok(13));
+    assert_eq!(komi("(;KM[7])"), Ok(14));
+    assert_eq!(komi("(;KM[-0.5])"), Ok(-1));
+    assert_eq!(komi("(;KoMi[ 0.50 ])"), Ok(1));
+    assert_eq!(komi("(;GM[1])"), Ok(0));
+    assert_eq!(
+        komi("(;KM[6.25])"),
+        Err(import::SgfError::BadKomi("6.25".to_string()))
+    );
+    assert_eq!(
+        komi("(;KM[six])"),
+        Err(import::SgfError::BadKomi("six".to_string()))
+    );
+}
+
+#[test]
+fn sgf_handicap_is_taken_from_setup_stones() {
+    let setup = import::sgf_setup("(;SZ[9]AB[cc][gg];B[ee])").unwrap();
+    assert_eq!(setup.size, (9, 9));
+    assert_eq!(setup.handicap, 2);
+    assert_eq!(setup.black, vec![Point::new(2, 2), Point::new(6, 6)]);
+
+    // Our own export reads back the same.
+    let game = GameBuilder::new().handicap(4).build().unwrap();
+    let setup = import::sgf_setup(&export::sgf_export(&game)).unwrap();
+    assert_eq!(setup.handicap, 4);
+    assert_eq!(setup.black.len(), 4);
+
+    // A position with stones of both colors isn't a handicap.
+    let setup = import::sgf_setup("(;AB[aa][bb]AW[cc])").unwrap();
+    assert_eq!(setup.handicap, 0);
+}
+
+#[test]
+fn sgf_problem_setup_builds_the_position() {
+    let sgf = "(;GM[1]FF[4]SZ[9]PL[W]C[White to kill]\
+               AB[ba][bb][cb][db][da]AW[ca][ea][eb][ec][dc][cc][bc][ac];W[aa])";
+    let setup = import::sgf_setup(sgf).unwrap();
+    assert_eq!(setup.handicap, 0);
+    assert_eq!(setup.to_move, Some(Color(2)));
+
+    let mut game = setup.builder().build().unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    let board = &game.shared.board;
+    assert_eq!(board.get_point(Point::new(1, 0)), Color(1));
+    assert_eq!(board.get_point(Point::new(3, 1)), Color(1));
+    // Surrounded with no liberties, but problems may set it up like this.
+    assert_eq!(board.get_point(Point::new(2, 0)), Color(2));
+    assert_eq!(board.get_point(Point::new(0, 2)), Color(2));
+    assert_eq!(board.get_point(Point::new(0, 0)), Color::empty());
+    assert_eq!(board.points.iter().filter(|c| !c.is_empty()).count(), 13);
+    assert_eq!(game.shared.board_history[0].board, game.shared.board);
+    assert_eq!(game.move_number(), 0);
+
+    assert_eq!(game.shared.get_active_seat().team, Color(2));
+    game.make_action(200, ActionKind::Place(0, 0), Millisecond(0))
+        .unwrap();
+
+    assert_eq!(
+        import::sgf_setup("(;AB[aa]AW[aa])"),
+        Err(import::SgfError::SetupOverlap(Point::new(0, 0)))
+    );
+}
+
+#[test]
+fn setup_stones_survive_dump_and_load() {
+    let setup = import::sgf_setup("(;SZ[9]PL[W]AB[aa][bb]AW[cc])").unwrap();
+    let mut game = setup.builder().build().unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    game.make_action(200, ActionKind::Place(4, 4), Millisecond(0))
+        .unwrap();
+
+    let loaded = Game::load(&game.dump()).unwrap();
+    assert_eq!(loaded.shared.board, game.shared.board);
+    assert_eq!(
+        loaded
+            .shared
+            .board
+            .points
+            .iter()
+            .filter(|c| !c.is_empty())
+            .count(),
+        4
+    );
+    assert_eq!(loaded.shared.turn, game.shared.turn);
+
+    // Replaying from the start begins with the setup stones.
+    let start = game.replay_to(0).unwrap();
+    assert_eq!(start.shared.board, game.shared.board_history[0].board);
+}
+
+#[test]
+fn sgf_contradictory_handicap_is_rejected() {
+    assert_eq!(
+        import::sgf_setup("(;HA[4]AB[dd][pd][dp][pp][jj])"),
+        Err(import::SgfError::HandicapMismatch {
+            handicap: 4,
+            stones: 5
+        })
+    );
+    // Free placement puts the stones down later.
+    assert_eq!(import::sgf_setup("(;HA[4])").unwrap().handicap, 4);
+    assert_eq!(
+        import::sgf_setup("(;SZ[9]AB[tt])"),
+        Err(import::SgfError::BadPoint("tt".to_string()))
+    );
+}
+
+#[test]
+fn suggested_handicap_follows_rating() {
+    // Even games keep the usual komi, whoever is stronger.
+    assert_eq!(suggest_handicap_komi(0, 19, 19), (0, 15));
+    assert_eq!(suggest_handicap_komi(-300, 19, 19), (0, 15));
+
+    // Five stones of difference, placed as five stones with half a point of komi.
+    assert_eq!(suggest_handicap_komi(500, 19, 19), (5, 1));
+    // Half a stone left over comes off the komi.
+    assert_eq!(suggest_handicap_komi(450, 19, 19), (4, -13));
+    // A single stone is given as komi instead.
+    assert_eq!(suggest_handicap_komi(150, 19, 19), (0, -27));
+
+    // The same difference is worth fewer stones on a small board.
+    let (stones, komi) = suggest_handicap_komi(500, 9, 9);
+    assert_eq!(stones, 0);
+    assert!(komi < 15);
+    // Stones that don't fit the board aren't suggested.
+    let (stones, _) = suggest_handicap_komi(2000, 10, 10);
+    assert_eq!(stones, 4);
+}
+
+#[test]
+fn builder_rejects_invalid_games() {
+    let build = |builder: GameBuilder| builder.build().err();
+
+    assert_eq!(
+        build(GameBuilder::new().seats(&[1, 3])),
+        Some(BuildError::Seats(SeatError::InvalidColor))
+    );
+    assert_eq!(
+        build(GameBuilder::new().seats(&[1, 1])),
+        Some(BuildError::Seats(SeatError::ColorWithoutSeat))
+    );
+    assert_eq!(
+        build(GameBuilder::new().seats(&[])),
+        Some(BuildError::SeatCount)
+    );
+    assert_eq!(
+        build(GameBuilder::new().komi(GroupVec::new())),
+        Some(BuildError::ColorCount)
+    );
+    assert_eq!(
+        build(GameBuilder::new().board_size(26, 19)),
+        Some(BuildError::BoardSize)
+    );
+    assert_eq!(
+        build(GameBuilder::new().board_size(6, 6).handicap(2)),
+        Some(BuildError::Handicap)
+    );
+    assert_eq!(
+        build(GameBuilder::new().board_size(10, 10).handicap(5)),
+        Some(BuildError::Handicap)
+    );
+    assert_eq!(
+        build(
+            GameBuilder::new()
+                .seats(&[1, 2, 3])
+                .komi(GroupVec::from(&[0, 0, 0][..]))
+                .handicap(2)
+        ),
+        Some(BuildError::Handicap)
+    );
+    let twice = ColorHandicap {
+        color: Color(1),
+        stones: 2,
+        placement: HandicapPlacement::Free,
+    };
+    assert_eq!(
+        build(GameBuilder::new().rules(GameModifier {
+            color_handicaps: vec![twice.clone(), twice],
+            ..GameModifier::default()
+        })),
+        Some(BuildError::Handicap)
+    );
+    assert_eq!(
+        build(GameBuilder::new().setup(vec![(Point::new(19, 0), Color(1))])),
+        Some(BuildError::Setup)
+    );
+    assert_eq!(
+        build(GameBuilder::new().setup(vec![(Point::new(0, 0), Color(3))])),
+        Some(BuildError::Setup)
+    );
+}
+
+#[test]
+fn eliminated_color_loses_its_turns() {
+    let three_player_game = |on_elimination| {
+        let mut game = GameBuilder::new()
+            .board_size(5, 5)
+            .seats(&[1, 2, 3])
+            .komi(GroupVec::from(&[0, 0, 0][..]))
+            .rules(GameModifier {
+                on_elimination,
+                ..GameModifier::default()
+            })
+            .build()
+            .unwrap();
+        for idx in 0..3 {
+            game.take_seat(idx as u64 + 1, idx).unwrap();
+        }
+        // White's only stone sits in the corner next to black.
+        *game.shared.board.point_mut(Point::new(0, 0)) = Color(2);
+        *game.shared.board.point_mut(Point::new(1, 0)) = Color(1);
+        let history = &mut game.shared.board_history[0];
+        history.board = game.shared.board.clone();
+        history.hash = history.board.hash();
+        game
+    };
+    let place = |game: &mut Game, player, x, y| {
+        game.make_action(player, ActionKind::Place(x, y), Millisecond(0))
+    };
+
+    let mut game = three_player_game(Some(EliminationPolicy::Remove));
+    place(&mut game, 1, 0, 1).unwrap();
+    assert!(game.shared.seats[1].resigned);
+    place(&mut game, 3, 4, 4).unwrap();
+    assert_eq!(place(&mut game, 2, 2, 2), Err(MakeActionError::NotTurn));
+    place(&mut game, 1, 2, 2).unwrap();
+    place(&mut game, 3, 4, 3).unwrap();
+    assert_eq!(game.shared.turn, 0);
+    assert!(!game.state.is_terminal());
+
+    // Without the rule white keeps playing without stones.
+    let mut game = three_player_game(None);
+    place(&mut game, 1, 0, 1).unwrap();
+    assert!(!game.shared.seats[1].resigned);
+    assert_eq!(game.shared.turn, 1);
+}
+
+#[test]
+fn resigned_color_is_removed_or_neutralized() {
+    let resign_white = |on_elimination| {
+        let mut game = GameBuilder::new()
+            .board_size(5, 5)
+            .seats(&[1, 2, 3])
+            .komi(GroupVec::from(&[0, 0, 0][..]))
+            .rules(GameModifier {
+                on_elimination,
+                ..GameModifier::default()
+            })
+            .build()
+            .unwrap();
+        for idx in 0..3 {
+            game.take_seat(idx as u64 + 1, idx).unwrap();
+        }
+        for &(player, x, y) in &[(1, 0, 0), (2, 2, 2), (3, 4, 4)] {
+            game.make_action(player, ActionKind::Place(x, y), Millisecond(0))
+                .unwrap();
+        }
+        game.make_action(1, ActionKind::Pass, Millisecond(0))
+            .unwrap();
+        game.make_action(2, ActionKind::Resign, Millisecond(0))
+            .unwrap();
+        game
+    };
+
+    let game = resign_white(Some(EliminationPolicy::Remove));
+    assert_eq!(
+        game.shared.board.get_point(Point::new(2, 2)),
+        Color::empty()
+    );
+    assert_eq!(game.shared.turn, 2);
+
+    // White's stone stays on the board but counts for nobody, so it doesn't
+    // give black and red any territory either.
+    let mut game = resign_white(Some(EliminationPolicy::Neutralize));
+    assert_eq!(game.shared.board.get_point(Point::new(2, 2)), Color(2));
+    game.make_action(3, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    game.make_action(1, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(&scoring.scores[..], &[2, 0, 2]);
+}
+
+#[test]
+fn rengo_seats_are_validated() {
+    let mut game = GameBuilder::new().seats(&[1, 2, 1, 2]).build().unwrap();
+    for idx in 0..4 {
+        game.take_seat(idx as u64 + 1, idx).unwrap();
+    }
+    assert_eq!(
+        validate_seats(&game.shared.seats, 2, &game.shared.mods),
+        Ok(())
+    );
+
+    assert_eq!(
+        GameBuilder::new().seats(&[1, 2, 1]).build().err(),
+        Some(BuildError::Seats(SeatError::UnevenTeams))
+    );
+    assert_eq!(
+        GameBuilder::new().seats(&[1, 2, 0, 2]).build().err(),
+        Some(BuildError::Seats(SeatError::InvalidColor))
+    );
+
+    // One player can't play both colors when moves are secret.
+    let mut hidden = GameBuilder::new()
+        .seats(&[1, 2, 1, 2])
+        .rules(GameModifier {
+            simultaneous: Some(SimultaneousGo::default()),
+            ..GameModifier::default()
+        })
+        .build()
+        .unwrap();
+    hidden.take_seat(1, 0).unwrap();
+    assert_eq!(hidden.take_seat(1, 1), Err(TakeSeatError::CanOnlyHoldOne));
+    hidden.shared.seats[1].player = Some(1);
+    assert_eq!(
+        validate_seats(&hidden.shared.seats, 2, &hidden.shared.mods),
+        Err(SeatError::DoubleBooked)
+    );
+}
+
+fn three_color_game(handicap: ColorHandicap) -> Game {
+    let mut game = GameBuilder::new()
+        .seats(&[1, 2, 3])
+        .komi(GroupVec::from(&[0, 0, 0][..]))
+        .board_size(9, 9)
+        .rules(GameModifier {
+            color_handicaps: vec![handicap],
+            ..GameModifier::default()
+        })
+        .build()
+        .unwrap();
+    for (idx, player) in [100, 200, 300].iter().enumerate() {
+        game.take_seat(*player, idx).unwrap();
+    }
+    game
+}
+
+#[test]
+fn fixed_color_handicap_moves_last() {
+    let game = three_color_game(ColorHandicap {
+        color: Color(2),
+        stones: 2,
+        placement: HandicapPlacement::Fixed,
+    });
+
+    let board = &game.shared.board;
+    assert_eq!(board.get_point(Point::new(6, 2)), Color(2));
+    assert_eq!(board.get_point(Point::new(2, 6)), Color(2));
+    assert_eq!(board.points.iter().filter(|c| !c.is_empty()).count(), 2);
+    assert_eq!(game.shared.board_history[0].board, *board);
+    // Black and white have had their turn, so the third color starts.
+    assert_eq!(game.shared.turn, 2);
+    assert_eq!(&game.shared.points[..], &[0, -2 * 2, 0]);
+}
+
+#[test]
+fn free_color_handicap_is_one_turn() {
+    let mut game = three_color_game(ColorHandicap {
+        color: Color(3),
+        stones: 2,
+        placement: HandicapPlacement::Free,
+    });
+    assert!(game.shared.board.points.iter().all(|c| c.is_empty()));
+    assert_eq!(game.shared.turn, 2);
+
+    game.make_action(300, ActionKind::Place(2, 2), Millisecond(0))
+        .unwrap();
+    assert_eq!(game.shared.turn, 2);
+    game.make_action(300, ActionKind::Place(6, 6), Millisecond(0))
+        .unwrap();
+    assert_eq!(game.shared.turn, 0);
+    assert_eq!(
+        game.make_action(300, ActionKind::Place(4, 4), Millisecond(0)),
+        Err(MakeActionError::NotTurn)
+    );
+    game.make_action(100, ActionKind::Place(4, 4), Millisecond(0))
+        .unwrap();
+    assert_eq!(game.shared.turn, 1);
+}
+
+#[test]
+fn summary_follows_game() {
+    let mut game = GameBuilder::new()
+        .board_size(9, 9)
+        .toroidal(true)
+        .build()
+        .unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+
+    for &(player, x, y) in &[(100, 2, 2), (200, 6, 6), (100, 2, 6)] {
+        game.make_action(player, ActionKind::Place(x, y), Millisecond(0))
+            .unwrap();
+    }
+
+    let summary = game.summary();
+    assert_eq!(summary.phase, GamePhase::Play);
+    assert_eq!(summary.move_number, 3);
+    assert_eq!(summary.size, (9, 9));
+    assert_eq!(summary.rules, "Toroidal go");
+    assert_eq!(summary.clock, None);
+    assert_eq!(summary.seats[0], (Some(100), 1, false));
+
+    game.make_action(200, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    game.make_action(100, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+
+    let summary = game.summary();
+    assert_eq!(summary.phase, GamePhase::Scoring);
+    assert_eq!(summary.move_number, 5);
+
+    let packed = serde_cbor::to_vec(&summary).unwrap();
+    assert_eq!(
+        serde_cbor::from_slice::<GameSummary>(&packed).unwrap(),
+        summary
+    );
+}
+
+#[test]
+fn self_capture_resolves_captures_first() {
+    let self_capture_game = |rows: &[&str]| {
+        let mut game = game_with_position(rows, false);
+        game.shared.mods.self_capture = true;
+        game
+    };
+
+    // Without capturing, black at the corner would have no liberties.
+    let mut game = self_capture_game(&[
+        ".ox..", //
+        "ox...", //
+        "o....", //
+        ".....", //
+        ".....", //
+    ]);
+    game.make_action(100, ActionKind::Place(0, 0), Millisecond(0))
+        .unwrap();
+    assert_eq!(game.shared.board.get_point(Point::new(0, 0)), Color(1));
+    assert_eq!(
+        game.shared.board.get_point(Point::new(1, 0)),
+        Color::empty()
+    );
+    assert_eq!(game.shared.board.get_point(Point::new(0, 1)), Color(2));
+
+    // Filling the last liberty of an existing group kills it.
+    let mut game = self_capture_game(&[
+        ".xo..", //
+        "xo...", //
+        "o....", //
+        ".....", //
+        ".....", //
+    ]);
+    game.make_action(100, ActionKind::Place(0, 0), Millisecond(0))
+        .unwrap();
+    for point in &[(0, 0), (1, 0), (0, 1)] {
+        let point = Point::new(point.0, point.1);
+        assert_eq!(game.shared.board.get_point(point), Color::empty());
+    }
+
+    // A lone stone is still suicide.
+    let mut game = self_capture_game(&[
+        ".o...", //
+        "o....", //
+        ".....", //
+        ".....", //
+        ".....", //
+    ]);
+    assert_eq!(
+        game.make_action(100, ActionKind::Place(0, 0), Millisecond(0)),
+        Err(MakeActionError::Suicide)
+    );
+}
+
+#[test]
+fn self_capture_gives_no_capture_points() {
+    let mut game = game_with_position(
+        &[
+            ".xo..", //
+            "xo...", //
+            "o....", //
+            ".....", //
+            "..xox", //
+        ],
+        false,
+    );
+    game.shared.mods.self_capture = true;
+    game.shared.mods.captures_give_points = Some(CapturesGivePoints {});
+    let unit = game.shared.mods.point_unit();
+    let before = game.shared.points.clone();
+
+    // Black loses three stones to its own move.
+    game.make_action(100, ActionKind::Place(0, 0), Millisecond(0))
+        .unwrap();
+    assert_eq!(game.shared.points, before);
+
+    // Capturing white still counts.
+    game.make_action(200, ActionKind::Place(0, 0), Millisecond(0))
+        .unwrap();
+    game.make_action(100, ActionKind::Place(3, 3), Millisecond(0))
+        .unwrap();
+    assert_eq!(game.shared.points[0], before[0] + unit);
+}
+
+#[test]
+fn must_defend_atari_rejects_tenuki() {
+    let atari_game = |rows: &[&str]| {
+        let mut game = game_with_position(rows, false);
+        game.shared.mods.must_defend_atari = true;
+        game
+    };
+    let rows = [
+        "xo...", //
+        ".....", //
+        ".....", //
+        ".....", //
+        ".....", //
+    ];
+
+    let mut game = atari_game(&rows);
+    assert_eq!(
+        game.make_action(100, ActionKind::Place(4, 4), Millisecond(0)),
+        Err(MakeActionError::MustDefend)
+    );
+    assert_eq!(game.shared.board, game.shared.board_history[0].board);
+    game.make_action(100, ActionKind::Place(0, 1), Millisecond(0))
+        .unwrap();
+
+    // Capturing the attacker is a defense too.
+    let mut game = atari_game(&[
+        "xo...", //
+        ".x...", //
+        ".....", //
+        ".....", //
+        ".....", //
+    ]);
+    game.make_action(100, ActionKind::Place(2, 0), Millisecond(0))
+        .unwrap();
+    assert_eq!(
+        game.shared.board.get_point(Point::new(1, 0)),
+        Color::empty()
+    );
+
+    // Capturing somewhere else leaves the group in atari.
+    let mut game = atari_game(&[
+        "xo...", //
+        ".....", //
+        ".....", //
+        "...x.", //
+        "..xo.", //
+    ]);
+    assert_eq!(
+        game.make_action(100, ActionKind::Place(4, 4), Millisecond(0)),
+        Err(MakeActionError::MustDefend)
+    );
+
+    // Passing is always allowed.
+    let mut game = atari_game(&rows);
+    game.make_action(100, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+}
+
+/// Whether the black group formed by playing at `point` has any liberty,
+/// judged only by the board's own neighbor topology.
+fn has_liberty_after(game: &Game, point: Point) -> bool {
+    let mut board = game.shared.board.clone();
+    *board.point_mut(point) = Color(1);
+    find_groups(&board)
+        .iter()
+        .filter(|g| g.points.contains(&point))
+        .flat_map(|g| g.points.iter())
+        .any(|&p| {
+            board
+                .surrounding_points(p)
+                .any(|n| board.get_point(n).is_empty())
+        })
+}
+
+#[test]
+fn filling_own_last_liberty_follows_topology() {
+    // Black plays at the corner, joining two stones that only have that liberty left.
+    let position = &[
+        ".xo..", //
+        "xo...", //
+        "o....", //
+        ".....", //
+        ".....", //
+    ];
+    let point = Point::new(0, 0);
+
+    let mut flat = game_with_position(position, false);
+    assert!(!has_liberty_after(&flat, point));
+    assert_eq!(
+        flat.make_action(100, ActionKind::Place(0, 0), Millisecond(0)),
+        Err(MakeActionError::Suicide)
+    );
+    assert_eq!(flat.shared.board, flat.shared.board_history[0].board);
+
+    // On a torus the stones escape across the edge.
+    let mut torus = game_with_position(position, true);
+    assert!(has_liberty_after(&torus, point));
+    assert_eq!(
+        torus.make_action(100, ActionKind::Place(0, 0), Millisecond(0)),
+        Ok(())
+    );
+    assert_eq!(torus.shared.board.get_point(point), Color(1));
+
+    // ..unless the wrapped neighbors are filled too.
+    let mut closed = game_with_position(
+        &[
+            ".xo.o", //
+            "xo..o", //
+            "o....", //
+            ".....", //
+            "oo...", //
+        ],
+        true,
+    );
+    assert!(!has_liberty_after(&closed, point));
+    assert_eq!(
+        closed.make_action(100, ActionKind::Place(0, 0), Millisecond(0)),
+        Err(MakeActionError::Suicide)
+    );
+}
+
+#[test]
+fn capture_across_wrap_is_not_suicide() {
+    // The black stone at the right edge has no liberties of its own, but it takes
+    // the white corner stone's last liberty across the edge, so it captures instead.
+    let mut torus = game_with_position(
+        &[
+            "ox.o.", //
+            "x...o", //
+            ".....", //
+            ".....", //
+            "x...o", //
+        ],
+        true,
+    );
+    let point = Point::new(4, 0);
+    assert!(!has_liberty_after(&torus, point));
+    assert_eq!(
+        torus.make_action(100, ActionKind::Place(4, 0), Millisecond(0)),
+        Ok(())
+    );
+    assert_eq!(torus.shared.board.get_point(point), Color(1));
+    assert_eq!(
+        torus.shared.board.get_point(Point::new(0, 0)),
+        Color::empty()
+    );
+}
+
+#[test]
+fn one_stone_captures_two_groups() {
+    // Black at the center takes the last liberty of both white stones at once,
+    // and has no liberties of its own until they are removed.
+    let mut game = game_with_position(
+        &[
+            ".xxx.", //
+            "xo.ox", //
+            ".xxx.", //
+        ],
+        false,
+    );
+    game.shared.mods.captures_give_points = Some(CapturesGivePoints {});
+
+    assert_eq!(
+        game.make_action(100, ActionKind::Place(2, 1), Millisecond(0)),
+        Ok(())
+    );
+    let board = &game.shared.board;
+    assert_eq!(board.get_point(Point::new(1, 1)), Color::empty());
+    assert_eq!(board.get_point(Point::new(3, 1)), Color::empty());
+    let black = board.points.iter().filter(|&&c| c == Color(1)).count();
+    assert_eq!(black, 9);
+
+    assert_eq!(game.state.assume::<PlayState>().capture_count, 2);
+    let unit = game.shared.mods.point_unit();
+    assert_eq!(game.shared.points.score_for(Color(1)), 2 * unit);
+}
+
+#[test]
+fn captures_are_tracked_per_color() {
+    let mut game = GameBuilder::new()
+        .seats(&[1, 2, 3])
+        .komi(GroupVec::from(&[0, 0, 0][..]))
+        .board_size(5, 5)
+        .build()
+        .unwrap();
+    for idx in 0..3 {
+        game.take_seat(idx as u64 + 1, idx).unwrap();
+    }
+
+    // Black, white, blue
+    let moves = [
+        [(1, 0), (0, 0), (4, 0)],
+        [(0, 1), (2, 2), (2, 3)],
+        [(3, 0), (1, 2), (3, 3)],
+        [(4, 1), (1, 3), (3, 4)],
+    ];
+    for turn in &moves {
+        for (idx, &(x, y)) in turn.iter().enumerate() {
+            game.make_action(idx as u64 + 1, ActionKind::Place(x, y), Millisecond(0))
+                .unwrap();
+        }
+    }
+
+    let captures = &game.state.assume::<PlayState>().captures;
+    assert_eq!(captures.get(Color(1), Color(2)), 1);
+    assert_eq!(captures.get(Color(1), Color(3)), 1);
+    assert_eq!(captures.total_by(Color(1)), 2);
+    assert_eq!(captures.total_by(Color(2)), 0);
+    assert_eq!(captures.get(Color(3), Color(1)), 0);
+}
+
+#[test]
+fn trading_kos_offers_to_end() {
+    // Black can take the upper ko and white the lower one. Trading them
+    // changes nothing about the stone counts or the territory.
+    let mut game = game_with_position(
+        &[
+            ".xo.", //
+            "xo.o", //
+            ".xo.", //
+            "....", //
+            ".ox.", //
+            "ox.x", //
+            ".ox.", //
+        ],
+        false,
+    );
+    game.shared.mods.no_progress = Some(NoProgress { moves: 2 });
+
+    game.make_action(100, ActionKind::Place(2, 1), Millisecond(0))
+        .unwrap();
+    assert!(!game.state.assume::<PlayState>().no_progress);
+    game.make_action(200, ActionKind::Place(2, 5), Millisecond(0))
+        .unwrap();
+    assert!(game.state.assume::<PlayState>().no_progress);
+
+    // It's only an offer, the game goes on until both pass.
+    game.make_action(100, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    assert!(matches!(game.state, GameState::Play(_)));
+    game.make_action(200, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    assert!(matches!(game.state, GameState::Scoring(_)));
+}
+
+#[test]
+fn mismatched_expectation_is_rejected() {
+    // Black at (1, 0) captures the white corner stone.
+    let mut game = game_with_position(
+        &[
+            "o....", //
+            "x....", //
+            ".....", //
+            ".....", //
+            ".....", //
+        ],
+        false,
+    );
+    let wrong = MoveExpectation {
+        captures: Some(2),
+        hash: None,
+        confirm_self_atari: false,
+    };
+    assert_eq!(
+        game.make_action_expecting(100, ActionKind::Place(1, 0), Millisecond(0), Some(&wrong)),
+        Err(MakeActionError::UnexpectedResult)
+    );
+    assert_eq!(game.shared.board, game.shared.board_history[0].board);
+    assert_eq!(game.shared.turn, 0);
+
+    let mut expected_board = game.shared.board.clone();
+    *expected_board.point_mut(Point::new(1, 0)) = Color(1);
+    *expected_board.point_mut(Point::new(0, 0)) = Color::empty();
+    let right = MoveExpectation {
+        captures: Some(1),
+        hash: Some(expected_board.hash()),
+        confirm_self_atari: false,
+    };
+    assert_eq!(
+        game.make_action_expecting(100, ActionKind::Place(1, 0), Millisecond(0), Some(&right)),
+        Ok(())
+    );
+    assert_eq!(game.shared.board, expected_board);
+}
+
+#[test]
+fn last_point_on_2x2_board_captures() {
+    let mut game = GameBuilder::new()
+        .board_size(2, 2)
+        .komi(GroupVec::from(&[0, 0][..]))
+        .build()
+        .unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    for &(player, x, y) in &[(100, 0, 0), (200, 1, 0), (100, 0, 1)] {
+        game.make_action(player, ActionKind::Place(x, y), Millisecond(0))
+            .unwrap();
+    }
+    // Both colors are down to the last point, white takes it and captures black.
+    game.make_action(200, ActionKind::Place(1, 1), Millisecond(0))
+        .unwrap();
+    assert_eq!(
+        game.shared.board.get_point(Point::new(0, 0)),
+        Color::empty()
+    );
+    assert_eq!(
+        game.shared.board.get_point(Point::new(0, 1)),
+        Color::empty()
+    );
+
+    game.make_action(100, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    game.make_action(200, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(&scoring.scores[..], &[0, 8]);
+}
+
+#[test]
+fn last_point_on_3x3_board_captures() {
+    let rows = [
+        ".xx", //
+        "oxx", //
+        "oox", //
+    ];
+
+    let mut game = game_with_position(&rows, false);
+    game.shared.komis = GroupVec::from(&[0, 0][..]);
+    game.shared.points = game.shared.komis.clone();
+    game.make_action(100, ActionKind::Place(0, 0), Millisecond(0))
+        .unwrap();
+    assert_eq!(
+        game.shared
+            .board
+            .points
+            .iter()
+            .filter(|c| c.is_empty())
+            .count(),
+        3
+    );
+    game.make_action(200, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    game.make_action(100, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(&scoring.scores[..], &[18, 0]);
+
+    // The same point captures black when white gets to it.
+    let mut game = game_with_position(&rows, false);
+    game.make_action(100, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    game.make_action(200, ActionKind::Place(0, 0), Millisecond(0))
+        .unwrap();
+    assert_eq!(
+        game.shared
+            .board
+            .points
+            .iter()
+            .filter(|c| c.is_empty())
+            .count(),
+        5
+    );
+}
+
+#[test]
+fn max_moves_ends_play() {
+    let capped_game = |max_moves_void| {
+        let mut game = GameBuilder::new()
+            .board_size(5, 5)
+            .rules(GameModifier {
+                max_moves: Some(4),
+                max_moves_void,
+                ..GameModifier::default()
+            })
+            .build()
+            .unwrap();
+        game.take_seat(100, 0).unwrap();
+        game.take_seat(200, 1).unwrap();
+        for (idx, &(x, y)) in [(0, 0), (1, 1), (2, 2), (3, 3)].iter().enumerate() {
+            let player = [100, 200][idx % 2];
+            assert!(matches!(game.state, GameState::Play(_)));
+            game.make_action(player, ActionKind::Place(x, y), Millisecond(0))
+                .unwrap();
+        }
+        game
+    };
+
+    let mut game = capped_game(false);
+    assert!(matches!(game.state, GameState::Scoring(_)));
+    assert_eq!(game.move_number(), 4);
+
+    // Scoring can't be left for a play phase that allows no moves.
+    for action in [ActionKind::Cancel, ActionKind::ResumePlay] {
+        assert_eq!(
+            game.make_action(100, action, Millisecond(0)),
+            Err(MakeActionError::WrongPhase)
+        );
+    }
+    assert!(matches!(game.state, GameState::Scoring(_)));
+    assert_eq!(game.move_number(), 4);
+
+    let game = capped_game(true);
+    let result = game.state.result().unwrap();
+    assert_eq!(result.kind, ResultKind::Void);
+    assert_eq!(result.winner, None);
+}
+
+#[test]
+fn undo_restores_captured_stones() {
+    let mut game = game_with_position(
+        &[
+            "xooo.", //
+            ".xxx.", //
+            ".....", //
+            ".....", //
+            ".....", //
+        ],
+        false,
+    );
+    let before = game.shared.board.clone();
+    game.make_action(100, ActionKind::Place(4, 0), Millisecond(0))
+        .unwrap();
+    let captured = [Point::new(1, 0), Point::new(2, 0), Point::new(3, 0)];
+    let info = game.shared.move_info(1).unwrap();
+    assert_eq!(info.placed, vec![Point::new(4, 0)]);
+    assert_eq!(info.captured, captured);
+
+    game.make_action(200, ActionKind::Cancel, Millisecond(0))
+        .unwrap();
+    assert_eq!(game.shared.board, before);
+    for point in captured {
+        assert_eq!(game.shared.board.get_point(point), Color(2));
+    }
+    assert_eq!(
+        game.shared.board.get_point(Point::new(4, 0)),
+        Color::empty()
+    );
+    assert_eq!(game.shared.turn, 0);
+    assert_eq!(game.move_number(), 0);
+    let state = game.state.assume::<PlayState>();
+    assert_eq!(state.capture_count, 0);
+}
+
+#[test]
+fn self_atari_check_skips_captures_and_hidden_stones() {
+    let unconfirmed = MoveExpectation::default();
+
+    // Taking a ko leaves the capturing stone a single liberty.
+    let mut game = game_with_position(
+        &[
+            ".xo..", //
+            "xo.o.", //
+            ".xo..", //
+        ],
+        false,
+    );
+    game.shared.mods.confirm_self_atari = true;
+    assert_eq!(
+        game.make_action_expecting(
+            100,
+            ActionKind::Place(2, 1),
+            Millisecond(0),
+            Some(&unconfirmed)
+        ),
+        Ok(())
+    );
+
+    // In phantom go the answer would tell black where the white stones are.
+    let mut game = GameBuilder::new()
+        .board_size(5, 5)
+        .rules(GameModifier {
+            phantom: Some(PhantomGo {}),
+            confirm_self_atari: true,
+            ..GameModifier::default()
+        })
+        .build()
+        .unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    game.shared.board = {
+        let mut board = Board::empty(5, 5, false);
+        for point in [Point::new(2, 0), Point::new(1, 1)] {
+            *board.point_mut(point) = Color(2);
+        }
+        board
+    };
+    assert_eq!(
+        game.make_action_expecting(
+            100,
+            ActionKind::Place(1, 0),
+            Millisecond(0),
+            Some(&unconfirmed)
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn self_atari_needs_confirmation() {
+    // Black at (1, 0) would be left with a single liberty.
+    let rows = [
+        "..o..", //
+        ".o...", //
+        ".....", //
+        ".....", //
+        ".....", //
+    ];
+    let unconfirmed = MoveExpectation::default();
+    let confirmed = MoveExpectation::confirmed();
+    let confirming_game = || {
+        let mut game = game_with_position(&rows, false);
+        game.shared.mods.confirm_self_atari = true;
+        game
+    };
+
+    // Without the rule the move is played as usual.
+    let mut game = game_with_position(&rows, false);
+    game.make_action(100, ActionKind::Place(1, 0), Millisecond(0))
+        .unwrap();
+
+    let mut game = confirming_game();
+    assert_eq!(
+        game.make_action(100, ActionKind::Place(1, 0), Millisecond(0)),
+        Err(MakeActionError::SelfAtariNeedsConfirm)
+    );
+    assert_eq!(
+        game.make_action_expecting(
+            100,
+            ActionKind::Place(1, 0),
+            Millisecond(0),
+            Some(&unconfirmed)
+        ),
+        Err(MakeActionError::SelfAtariNeedsConfirm)
+    );
+    assert_eq!(game.shared.board, game.shared.board_history[0].board);
+    assert_eq!(game.shared.turn, 0);
+
+    assert_eq!(
+        game.make_action_expecting(
+            100,
+            ActionKind::Place(1, 0),
+            Millisecond(0),
+            Some(&confirmed)
+        ),
+        Ok(())
+    );
+    assert_eq!(game.shared.board.get_point(Point::new(1, 0)), Color(1));
+
+    // Replaying the recorded move doesn't ask again.
+    let mut replayed = confirming_game();
+    for action in &game.actions {
+        if let ReplayActionKind::Play(_) = action.action {
+            replayed.replay_action(action.clone()).unwrap();
+        }
+    }
+    assert_eq!(replayed.shared.board, game.shared.board);
+
+    // The flag doesn't matter for an ordinary move.
+    for expected in [&unconfirmed, &confirmed] {
+        let mut game = confirming_game();
+        assert_eq!(
+            game.make_action_expecting(
+                100,
+                ActionKind::Place(3, 3),
+                Millisecond(0),
+                Some(expected)
+            ),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn move_log_follows_a_ladder() {
+    let mut game = game_with_position(
+        &[
+            ".....", //
+            ".....", //
+            ".....", //
+            "...ox", //
+            "..xx.", //
+        ],
+        false,
+    );
+    let moves = [
+        (3, 2),
+        (2, 3),
+        (1, 3),
+        (2, 2),
+        (2, 1),
+        (1, 2),
+        (0, 2),
+        (1, 1),
+        (0, 1),
+        (1, 0),
+        (0, 0),
+        (2, 0),
+        (3, 0),
+    ];
+    for (idx, &(x, y)) in moves.iter().enumerate() {
+        let player = if idx % 2 == 0 { 100 } else { 200 };
+        game.make_action(player, ActionKind::Place(x, y), Millisecond(0))
+            .unwrap();
+    }
+
+    let log = game.move_log();
+    assert_eq!(log.len(), moves.len());
+    for (info, &(x, y)) in log.iter().zip(&moves) {
+        assert_eq!(info.placed, vec![Point::new(x, y)]);
+    }
+    let (last, running) = log.split_last().unwrap();
+    assert!(running.iter().all(|info| info.captured.is_empty()));
+
+    // The last move captures every white stone of the ladder.
+    let mut captured = last.captured.clone();
+    captured.sort_by_key(|p| (p.y, p.x));
+    let mut expected = [(3, 3), (2, 3), (2, 2), (1, 2), (1, 1), (1, 0), (2, 0)]
+        .iter()
+        .map(|&(x, y)| Point::new(x, y))
+        .collect::<Vec<_>>();
+    expected.sort_by_key(|p| (p.y, p.x));
+    assert_eq!(captured, expected);
+    assert_eq!(game.shared.move_info(moves.len() as u32 + 1), None);
+}
+
+#[test]
+fn replay_to_restores_earlier_move() {
+    // Black captures the white stone at (1, 0) with its fourth move, starting a ko.
+    let mut game = GameBuilder::new().board_size(5, 5).build().unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    let moves = [
+        (100, 0, 0),
+        (200, 1, 0),
+        (100, 1, 1),
+        (200, 2, 1),
+        (100, 4, 4),
+        (200, 3, 0),
+        (100, 2, 0),
+    ];
+    for &(player, x, y) in &moves {
+        game.make_action(player, ActionKind::Place(x, y), Millisecond(0))
+            .unwrap();
+    }
+    assert_eq!(
+        game.shared.board.get_point(Point::new(1, 0)),
+        Color::empty()
+    );
+
+    let before = game.replay_to(6).unwrap();
+    assert_eq!(before.shared.board.get_point(Point::new(1, 0)), Color(2));
+    assert_eq!(
+        before.shared.board.get_point(Point::new(2, 0)),
+        Color::empty()
+    );
+    assert_eq!(before.shared.turn, 0);
+    assert!(matches!(before.state, GameState::Play(_)));
+
+    // The ko is restored too, white can't retake immediately.
+    let mut after = game.replay_to(7).unwrap();
+    assert_eq!(after.shared.board, game.shared.board);
+    assert_eq!(
+        after.make_action(200, ActionKind::Place(1, 0), Millisecond(0)),
+        Err(MakeActionError::Ko)
+    );
+}
+
+#[test]
+fn group_id_survives_recompute() {
+    let mut game = game_with_position(
+        &[
+            ".xx..", //
+            ".x...", //
+            "...oo", //
+            ".....", //
+            "x....", //
+        ],
+        false,
+    );
+    let find = |board: &Board, point: Point| {
+        find_groups(board)
+            .into_iter()
+            .find(|g| g.points.contains(&point))
+            .unwrap()
+    };
+    let black = find(&game.shared.board, Point::new(1, 1));
+    let white = find(&game.shared.board, Point::new(4, 2));
+    assert_eq!(black.id(), GroupId(Point::new(1, 0)));
+    assert_eq!(white.id(), GroupId(Point::new(3, 2)));
+
+    // Unrelated stones don't change the ids, even though group order may change.
+    game.make_action(100, ActionKind::Place(3, 4), Millisecond(0))
+        .unwrap();
+    let board = &game.shared.board;
+    assert_eq!(find(board, Point::new(2, 0)).id(), black.id());
+    assert_eq!(find(board, Point::new(3, 2)).id(), white.id());
+    assert_ne!(find(board, Point::new(0, 4)).id(), black.id());
+}
+
+#[test]
+fn engine_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<Game>();
+    assert_send_sync::<SharedState>();
+    assert_send_sync::<GameState>();
+    assert_send_sync::<crate::states::ScoringState>();
+    assert_send_sync::<Board>();
+    assert_send_sync::<GameView>();
+    assert_send_sync::<GameSummary>();
+    assert_send_sync::<BoardReader>();
+}
+
+#[test]
+fn board_readers_never_see_half_a_move() {
+    use rand::prelude::*;
+    use rand_pcg::Lcg64Xsh32;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let mut game = GameBuilder::new().board_size(9, 9).build().unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    let reader = game.shared.board_reader();
+    let done = Arc::new(AtomicBool::new(false));
+
+    let readers = (0..4)
+        .map(|_| {
+            let reader = reader.clone();
+            let done = done.clone();
+            std::thread::spawn(move || {
+                let mut seen = Vec::new();
+                while !done.load(Ordering::Relaxed) {
+                    let snapshot = reader.load();
+                    if seen.last() != Some(&snapshot) {
+                        seen.push(snapshot);
+                    }
+                }
+                seen
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // Random moves on a small board capture plenty.
+    let mut rng = Lcg64Xsh32::seed_from_u64(5);
+    for _ in 0..2000 {
+        let player = [100, 200][game.shared.turn];
+        let (x, y) = (rng.gen_range(0, 9), rng.gen_range(0, 9));
+        let _ = game.make_action(player, ActionKind::Place(x, y), Millisecond(0));
+    }
+    done.store(true, Ordering::Relaxed);
+    let captured = game.move_log().iter().any(|info| !info.captured.is_empty());
+    assert!(game.move_number() > 100 && captured);
+
+    assert_eq!(reader.load().board, game.shared.board);
+    for handle in readers {
+        for snapshot in handle.join().unwrap() {
+            let history = &game.shared.board_history[snapshot.move_number as usize];
+            assert_eq!(snapshot.board, history.board);
+            assert_eq!(snapshot.turn, history.turn);
+        }
+    }
+}
+
+#[test]
+fn white_can_move_first() {
+    let mut game = GameBuilder::new()
+        .board_size(9, 9)
+        .first_player(Color(2))
+        .build()
+        .unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+
+    assert_eq!(
+        game.make_action(100, ActionKind::Place(2, 2), Millisecond(0)),
+        Err(MakeActionError::NotTurn)
+    );
+    game.make_action(200, ActionKind::Place(2, 2), Millisecond(0))
+        .unwrap();
+    game.make_action(100, ActionKind::Place(6, 6), Millisecond(0))
+        .unwrap();
+
+    // Replays keep the turn order.
+    let game = Game::load(&game.dump()).unwrap();
+    assert_eq!(game.shared.board.get_point(Point::new(2, 2)), Color(2));
+
+    assert_eq!(
+        GameBuilder::new().first_player(Color(3)).build().err(),
+        Some(BuildError::FirstPlayer)
+    );
+}
+
+#[test]
+fn verify_replay_reports_divergence() {
+    let mut game = GameBuilder::new().board_size(5, 5).build().unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    let moves = [(100, 0, 0), (200, 1, 0), (100, 4, 4), (200, 0, 1)];
+    for &(player, x, y) in &moves {
+        game.make_action(player, ActionKind::Place(x, y), Millisecond(0))
+            .unwrap();
+    }
+    let boards = (0..=moves.len())
+        .map(|n| (n as u32, game.shared.board_history[n].board.clone()))
+        .collect::<Vec<_>>();
+
+    // The history includes taking the seats.
+    let fresh = || GameBuilder::new().board_size(5, 5).build().unwrap();
+
+    assert_eq!(fresh().verify_replay(&game.actions, &boards), Ok(()));
+
+    // The source thinks the black corner stone survived the capture on move 4.
+    let mut diverged = boards.clone();
+    *diverged[4].1.point_mut(Point::new(0, 0)) = Color(1);
+    let mismatch = fresh().verify_replay(&game.actions, &diverged).unwrap_err();
+    assert_eq!(mismatch, ReplayMismatch::Board(4));
+    assert_eq!(mismatch.move_number(), 4);
+
+    // A move onto an occupied point can't be replayed at all.
+    let mut actions = game.actions.clone();
+    actions.push(GameAction::play(100, ActionKind::Place(4, 4)));
+    assert_eq!(
+        fresh().verify_replay(&actions, &boards),
+        Err(ReplayMismatch::Illegal(5))
+    );
+}
+
+#[test]
+fn strict_replay_rejects_occupied_points() {
+    let mut actions = vec![
+        GameAction::new(100, ReplayActionKind::TakeSeat(0)),
+        GameAction::new(200, ReplayActionKind::TakeSeat(1)),
+    ];
+    // Rows of stones far enough apart that nothing gets captured.
+    for i in 0..39 {
+        let player = if i % 2 == 0 { 100 } else { 200 };
+        let place = ActionKind::Place(i % 19, 3 * (i / 19));
+        actions.push(GameAction::play(player, place));
+    }
+
+    let fresh = || GameBuilder::new().board_size(19, 19).build().unwrap();
+    assert_eq!(fresh().replay_strict(&actions), Ok(()));
+
+    // The source captured the first black stone and white played there again.
+    actions.push(GameAction::play(200, ActionKind::Place(0, 0)));
+    let mut game = fresh();
+    assert_eq!(
+        game.replay_strict(&actions),
+        Err(ReplayError {
+            move_index: 40,
+            reason: MakeActionError::PointOccupied,
+        })
+    );
+    // Nothing got overwritten.
+    assert_eq!(game.shared.board.get_point(Point::new(0, 0)), Color(1));
+    assert_eq!(game.shared.board_history.len(), 40);
+}
+
+fn ready_check_game(empty_seats_block: bool) -> Game {
+    let mods = GameModifier {
+        ready_check: Some(ReadyCheck { empty_seats_block }),
+        ..GameModifier::default()
+    };
+    let mut game = GameBuilder::new()
+        .seats(&[1, 2, 1, 2])
+        .board_size(9, 9)
+        .rules(mods)
+        .build()
+        .unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    game.take_seat(300, 2).unwrap();
+    game
+}
+
+#[test]
+fn game_starts_when_every_seated_player_is_ready() {
+    let mut game = ready_check_game(false);
+    assert!(matches!(game.state, GameState::Waiting(_)));
+    assert_eq!(
+        game.make_action(100, ActionKind::Place(2, 2), Millisecond(0)),
+        Err(MakeActionError::Illegal)
+    );
+
+    game.make_action(100, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    game.make_action(200, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    // Changing your mind or leaving the seat resets readiness.
+    game.make_action(200, ActionKind::Cancel, Millisecond(0))
+        .unwrap();
+    game.make_action(300, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    game.leave_seat(300, 2).unwrap();
+    game.take_seat(300, 2).unwrap();
+    assert!(matches!(game.state, GameState::Waiting(_)));
+    assert_eq!(game.summary().phase, GamePhase::Waiting);
+
+    game.make_action(200, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    assert!(matches!(game.state, GameState::Waiting(_)));
+    // The empty fourth seat doesn't block.
+    game.make_action(300, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    assert!(matches!(game.state, GameState::Play(_)));
+
+    let replayed = Game::load(&game.dump()).unwrap();
+    assert!(matches!(replayed.state, GameState::Play(_)));
+}
+
+#[test]
+fn empty_seats_can_block_the_start() {
+    let mut game = ready_check_game(true);
+    for &player in &[100, 200, 300] {
+        game.make_action(player, ActionKind::Pass, Millisecond(0))
+            .unwrap();
+    }
+    assert!(matches!(game.state, GameState::Waiting(_)));
+
+    game.take_seat(400, 3).unwrap();
+    game.make_action(400, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    assert!(matches!(game.state, GameState::Play(_)));
+}
+
+#[test]
+fn full_serialization_resumes_scoring() {
+    let mods = GameModifier {
+        clock: Some(Clock {
+            rule: clock::ClockRule::Fischer(clock::FischerClock {
+                main_time: Millisecond(60_000),
+                increment: Millisecond(5_000),
+            }),
+        }),
+        ..GameModifier::default()
+    };
+    let mut game = GameBuilder::new()
+        .board_size(9, 9)
+        .rules(mods)
+        .build()
+        .unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+
+    for &(player, x, y, time) in &[(100, 2, 2, 1_000), (200, 6, 6, 4_000), (100, 2, 6, 9_000)] {
+        game.make_action(player, ActionKind::Place(x, y), Millisecond(time))
+            .unwrap();
+    }
+    game.make_action(200, ActionKind::Pass, Millisecond(12_000))
+        .unwrap();
+    game.make_action(100, ActionKind::Pass, Millisecond(13_000))
+        .unwrap();
+    game.make_action(100, ActionKind::AcceptScore, Millisecond(14_000))
+        .unwrap();
+
+    let data = game.serialize_full();
+    let mut restored = Game::deserialize_full(&data).unwrap();
+    assert_eq!(restored.serialize_full(), data);
+    assert_eq!(restored.shared.clock, game.shared.clock);
+    assert_eq!(restored.shared.board, game.shared.board);
+    assert_eq!(restored.actions, game.actions);
+
+    let scoring = restored.state.assume::<ScoringState>();
+    assert_eq!(scoring.players_accepted, vec![true, false]);
+    assert_eq!(
+        &scoring.scores[..],
+        &game.state.assume::<ScoringState>().scores[..]
+    );
+
+    restored
+        .make_action(200, ActionKind::AcceptScore, Millisecond(15_000))
+        .unwrap();
+    assert!(matches!(restored.state, GameState::Done(_)));
+}
+
+#[test]
+fn score_for_skips_empty_color() {
+    let mut scores = GroupVec::from(&[5, 15][..]);
+    assert_eq!(scores.score_for(Color(1)), 5);
+    assert_eq!(scores.score_for(Color(2)), 15);
+    *scores.score_for_mut(Color(1)) += 2;
+    assert_eq!(&scores[..], &[7, 15]);
+}
+
+#[test]
+#[should_panic(expected = "The empty color has no score")]
+fn score_for_rejects_empty_color() {
+    let scores = GroupVec::from(&[5, 15][..]);
+    scores.score_for(Color::empty());
+}
+
 use insta::{assert_debug_snapshot, glob};
 use std::fs;
 
@@ -66,3 +1640,940 @@ fn replay_snapshots() {
         assert_debug_snapshot!(view);
     });
 }
+
+/// Dumps a replay the way `Game::dump` does, for games the builder wouldn't create.
+fn dump_replay(replay: &GameReplay) -> Vec<u8> {
+    let mut vec = Vec::new();
+    replay
+        .serialize(&mut serde_cbor::Serializer::new(&mut vec).packed_format())
+        .unwrap();
+    vec
+}
+
+#[test]
+fn stored_games_load_without_new_checks() {
+    // Stored before every color needed a seat.
+    let replay = GameReplay {
+        actions: vec![GameAction::new(1, ReplayActionKind::TakeSeat(0))],
+        mods: GameModifier::default(),
+        komis: GroupVec::from(&[0, 15][..]),
+        seats: GroupVec::from(&[1, 1][..]),
+        size: (9, 9),
+        seed: 0,
+        setup: Vec::new(),
+        nigiri: None,
+    };
+    assert_eq!(
+        GameBuilder::new().seats(&[1, 1]).build().err(),
+        Some(BuildError::Seats(SeatError::ColorWithoutSeat))
+    );
+    let game = Game::load(&dump_replay(&replay)).unwrap();
+    assert_eq!(game.shared.seats[0].player, Some(1));
+}
+
+#[test]
+fn stored_uneven_teams_still_load() {
+    let replay = GameReplay {
+        actions: vec![
+            GameAction::new(1, ReplayActionKind::TakeSeat(0)),
+            GameAction::new(2, ReplayActionKind::TakeSeat(1)),
+            GameAction::new(3, ReplayActionKind::TakeSeat(2)),
+        ],
+        mods: GameModifier::default(),
+        komis: GroupVec::from(&[0, 15][..]),
+        seats: GroupVec::from(&[1, 2, 2][..]),
+        size: (9, 9),
+        seed: 0,
+        setup: Vec::new(),
+        nigiri: None,
+    };
+    assert_eq!(
+        GameBuilder::new().seats(&[1, 2, 2]).build().err(),
+        Some(BuildError::Seats(SeatError::UnevenTeams))
+    );
+    let game = Game::load(&dump_replay(&replay)).unwrap();
+    assert_eq!(game.shared.seats[2].player, Some(3));
+}
+
+#[test]
+fn driver_plays_a_full_game() {
+    let game = GameBuilder::new()
+        .board_size(5, 5)
+        .komi(GroupVec::from(&[0, 1][..]))
+        .build()
+        .unwrap();
+    let mut driver = GameDriver::seated(game).unwrap();
+
+    // Black walls off two columns, white three.
+    for y in 0..5 {
+        driver.play(1, ActionKind::Place(1, y)).unwrap();
+        driver.play(2, ActionKind::Place(2, y)).unwrap();
+    }
+    assert_eq!(
+        driver.play(1, ActionKind::Place(1, 0)),
+        Err(MakeActionError::PointOccupied)
+    );
+    driver
+        .play_all(&[(1, ActionKind::Pass), (2, ActionKind::Pass)])
+        .unwrap();
+    assert!(matches!(driver.state(), GameState::Scoring(_)));
+    assert_eq!(driver.result(), None);
+
+    // Changing our minds goes back to play and counts again.
+    driver.play(1, ActionKind::Cancel).unwrap();
+    assert!(matches!(driver.state(), GameState::Play(_)));
+    driver
+        .play_all(&[
+            (1, ActionKind::Pass),
+            (2, ActionKind::Pass),
+            (1, ActionKind::AcceptScore),
+            (2, ActionKind::AcceptScore),
+        ])
+        .unwrap();
+
+    let result = driver.result().unwrap();
+    assert_eq!(result.kind, ResultKind::Counted);
+    assert_eq!(result.winner, Some(Color(2)));
+    assert_eq!(result.margin, 11);
+    assert_eq!(
+        driver.play(1, ActionKind::Pass),
+        Err(MakeActionError::GameDone)
+    );
+}
+
+#[test]
+fn scoring_is_pushed_over_play() {
+    let game = GameBuilder::new().board_size(5, 5).build().unwrap();
+    let mut driver = GameDriver::seated(game).unwrap();
+    driver.play(1, ActionKind::Place(2, 2)).unwrap();
+    driver.play(2, ActionKind::Pass).unwrap();
+    assert_eq!(driver.game.state_stack.depth(), 0);
+
+    driver.play(1, ActionKind::Pass).unwrap();
+    assert!(matches!(driver.state(), GameState::Scoring(_)));
+    assert_eq!(driver.game.state_stack.depth(), 1);
+    assert!(matches!(
+        driver.game.state_stack.suspended(),
+        [GameState::Play(_)]
+    ));
+
+    // Back to the same play state, with the passes cleared.
+    driver.play(2, ActionKind::Cancel).unwrap();
+    assert_eq!(driver.game.state_stack.depth(), 0);
+    let state = driver.state().assume::<PlayState>();
+    assert_eq!(state.players_passed, vec![false, false]);
+    assert_eq!(
+        driver.game.shared.board.get_point(Point::new(2, 2)),
+        Color(1)
+    );
+}
+
+#[test]
+fn base_state_cannot_be_popped() {
+    let mut stack = GameStateStack::default();
+    let mut state = GameState::Play(PlayState::new(2));
+    assert_eq!(
+        stack.apply(&mut state, ActionChange::PopState),
+        Err(MakeActionError::NoPreviousState)
+    );
+    assert!(matches!(state, GameState::Play(_)));
+
+    let scoring = GameState::Done(ScoringState::new(
+        &Board::empty(3, 3, false),
+        &[],
+        &[0, 0],
+        &GameModifier::default(),
+        0,
+    ));
+    stack
+        .apply(&mut state, ActionChange::PushState(scoring))
+        .unwrap();
+    assert_eq!(stack.apply(&mut state, ActionChange::PopState), Ok(true));
+    assert!(matches!(state, GameState::Play(_)));
+    assert_eq!(stack.depth(), 0);
+}
+
+#[test]
+fn groups_are_split_by_color() {
+    let rows = ["x...x", ".....", "..o..", "....."];
+    let game = game_with_position(&rows, false);
+    let by_color = find_groups_by_color(&game.shared.board);
+    assert_eq!(by_color.len(), 2);
+    assert_eq!(by_color[0].len(), 2);
+    assert_eq!(by_color[1].len(), 1);
+    assert_eq!(by_color[1][0].points[..], [Point::new(2, 2)]);
+
+    // The black stones touch across the wrap.
+    let game = game_with_position(&rows, true);
+    let by_color = find_groups_by_color(&game.shared.board);
+    assert_eq!(by_color[0].len(), 1);
+    assert_eq!(by_color[0][0].points.len(), 2);
+
+    let scoring = ScoringState::new(
+        &game.shared.board,
+        &game.shared.seats,
+        &game.shared.points,
+        &game.shared.mods,
+        0,
+    );
+    assert_eq!(scoring.groups_of(Color(1)).count(), 1);
+    assert_eq!(scoring.groups_of(Color(2)).count(), 1);
+}
+
+fn atari_game(pass_loses: bool) -> GameDriver {
+    let game = GameBuilder::new()
+        .board_size(5, 5)
+        .rules(GameModifier {
+            atari_go: Some(AtariGo {
+                pass_loses,
+                ..AtariGo::default()
+            }),
+            ..GameModifier::default()
+        })
+        .build()
+        .unwrap();
+    GameDriver::seated(game).unwrap()
+}
+
+#[test]
+fn first_capture_wins_atari_go() {
+    let mut driver = atari_game(false);
+    driver
+        .play_all(&[
+            (1, ActionKind::Place(1, 0)),
+            (2, ActionKind::Place(0, 0)),
+            (1, ActionKind::Place(0, 1)),
+        ])
+        .unwrap();
+    let result = driver.result().unwrap();
+    assert_eq!(result.kind, ResultKind::FirstCapture);
+    assert_eq!(result.winner, Some(Color(1)));
+}
+
+#[test]
+fn passing_loses_atari_go() {
+    let mut driver = atari_game(true);
+    driver.play(1, ActionKind::Place(2, 2)).unwrap();
+    driver.play(2, ActionKind::Pass).unwrap();
+    let result = driver.result().unwrap();
+    assert_eq!(result.kind, ResultKind::PassForfeit);
+    assert_eq!(result.winner, Some(Color(1)));
+
+    // Without the flag passing ends the game as usual.
+    let mut driver = atari_game(false);
+    driver
+        .play_all(&[(1, ActionKind::Pass), (2, ActionKind::Pass)])
+        .unwrap();
+    assert!(matches!(driver.state(), GameState::Scoring(_)));
+}
+
+#[test]
+fn passing_out_is_counted_or_agreed() {
+    let pass_out = |mods: GameModifier| {
+        let game = GameBuilder::new()
+            .board_size(5, 5)
+            .rules(mods)
+            .build()
+            .unwrap();
+        let mut driver = GameDriver::seated(game).unwrap();
+        driver
+            .play_all(&[
+                (1, ActionKind::Place(2, 2)),
+                (2, ActionKind::Pass),
+                (1, ActionKind::Pass),
+            ])
+            .unwrap();
+        driver
+    };
+
+    let mut driver = pass_out(GameModifier::default());
+    assert!(matches!(driver.state(), GameState::Scoring(_)));
+    driver
+        .play_all(&[(1, ActionKind::AcceptScore), (2, ActionKind::AcceptScore)])
+        .unwrap();
+    let result = driver.result().unwrap();
+    assert_eq!(result.kind, ResultKind::Counted);
+    assert_eq!(result.winner, Some(Color(1)));
+
+    let driver = pass_out(GameModifier {
+        atari_go: Some(AtariGo {
+            pass_ends: true,
+            ..AtariGo::default()
+        }),
+        ..GameModifier::default()
+    });
+    let result = driver.result().unwrap();
+    assert_eq!(result.kind, ResultKind::Agreement);
+    assert_eq!(result.winner, None);
+}
+
+#[test]
+fn nigiri_picks_colors_from_the_seed() {
+    assert_eq!(nigiri(7), nigiri(7));
+
+    let build = |seed| {
+        GameBuilder::new()
+            .rules(GameModifier {
+                nigiri: true,
+                ..GameModifier::default()
+            })
+            .seed(seed)
+            .build()
+            .unwrap()
+    };
+    let mut swapped = false;
+    let mut kept = false;
+    for seed in 0..20 {
+        let game = build(seed);
+        let teams = game.shared.seats.iter().map(|s| s.team).collect::<Vec<_>>();
+        assert_eq!(
+            teams,
+            build(seed)
+                .shared
+                .seats
+                .iter()
+                .map(|s| s.team)
+                .collect::<Vec<_>>()
+        );
+        assert_ne!(teams[0], teams[1]);
+
+        let nigiri = game.shared.nigiri.unwrap();
+        assert_eq!(nigiri, super::nigiri(seed));
+        assert_eq!(teams[0], nigiri.guesser);
+        let loaded = Game::load(&game.dump()).unwrap();
+        assert_eq!(loaded.shared.seats, game.shared.seats);
+        swapped |= teams[0] == Color(2);
+        kept |= teams[0] == Color(1);
+        // Black still moves first.
+        assert_eq!(game.shared.get_active_seat().team, Color(1));
+    }
+    assert!(swapped && kept);
+
+    // The result is stored with the game, a dump doesn't depend on drawing it again.
+    let mut game = build(0);
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    let black = game.shared.get_active_seat().player.unwrap();
+    game.make_action(black, ActionKind::Place(3, 3), Millisecond(0))
+        .unwrap();
+    let held = game.shared.nigiri.unwrap();
+    let other_seed = (1..)
+        .find(|&seed| nigiri(seed).guesser != held.guesser)
+        .unwrap();
+    let mut replay = game.replay();
+    replay.seed = other_seed;
+    let loaded = Game::from_replay(replay).unwrap();
+    assert_eq!(loaded.shared.nigiri, Some(held));
+    assert_eq!(loaded.shared.seats, game.shared.seats);
+    assert_eq!(loaded.shared.board, game.shared.board);
+    assert_eq!(game.replay_to(0).unwrap().shared.nigiri, Some(held));
+
+    let loaded = Game::load(&game.dump()).unwrap();
+    assert_eq!(loaded.shared.nigiri, Some(held));
+    assert_eq!(loaded.actions, game.actions);
+
+    assert_eq!(
+        GameBuilder::new()
+            .seats(&[1, 2, 3])
+            .komi(GroupVec::from(&[0, 0, 0][..]))
+            .rules(GameModifier {
+                nigiri: true,
+                ..GameModifier::default()
+            })
+            .build()
+            .err(),
+        Some(BuildError::ColorCount)
+    );
+}
+
+fn komi_auction_game() -> Game {
+    let mut game = GameBuilder::new()
+        .board_size(9, 9)
+        .rules(GameModifier {
+            komi_auction: true,
+            ..GameModifier::default()
+        })
+        .build()
+        .unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    assert!(matches!(game.state, GameState::KomiAuction(_)));
+    game
+}
+
+#[test]
+fn komi_auction_chooser_takes_white() {
+    let mut game = komi_auction_game();
+    // Only black proposes and white has to wait for a proposal.
+    assert_eq!(
+        game.make_action(200, ActionKind::ProposeKomi(9), Millisecond(0)),
+        Err(MakeActionError::NotTurn)
+    );
+    assert_eq!(
+        game.make_action(200, ActionKind::ChooseColor(Color(2)), Millisecond(0)),
+        Err(MakeActionError::Illegal)
+    );
+    assert_eq!(
+        game.make_action(100, ActionKind::Place(4, 4), Millisecond(0)),
+        Err(MakeActionError::Illegal)
+    );
+
+    // 4.5
+    game.make_action(100, ActionKind::ProposeKomi(9), Millisecond(0))
+        .unwrap();
+    assert_eq!(
+        game.make_action(100, ActionKind::ChooseColor(Color(2)), Millisecond(0)),
+        Err(MakeActionError::NotTurn)
+    );
+    game.make_action(200, ActionKind::ChooseColor(Color(2)), Millisecond(0))
+        .unwrap();
+
+    assert!(matches!(game.state, GameState::Play(_)));
+    assert_eq!(&game.shared.komis[..], &[0, 9]);
+    assert_eq!(&game.shared.points[..], &[0, 9]);
+    assert_eq!(game.shared.seats[0].team, Color(1));
+    assert_eq!(game.shared.seats[1].team, Color(2));
+    assert_eq!(
+        game.shared.komi_auction,
+        Some(KomiAuctionResult {
+            komi: 9,
+            swapped: false
+        })
+    );
+    game.make_action(100, ActionKind::Place(4, 4), Millisecond(0))
+        .unwrap();
+}
+
+#[test]
+fn komi_auction_chooser_takes_black() {
+    let mut game = komi_auction_game();
+    game.make_action(100, ActionKind::ProposeKomi(21), Millisecond(0))
+        .unwrap();
+    game.make_action(200, ActionKind::ChooseColor(Color(1)), Millisecond(0))
+        .unwrap();
+
+    assert_eq!(&game.shared.points[..], &[0, 21]);
+    assert_eq!(game.shared.seats[0].team, Color(2));
+    assert_eq!(game.shared.seats[1].team, Color(1));
+    // The chooser took black, so they move first.
+    assert_eq!(
+        game.make_action(100, ActionKind::Place(4, 4), Millisecond(0)),
+        Err(MakeActionError::NotTurn)
+    );
+    game.make_action(200, ActionKind::Place(4, 4), Millisecond(0))
+        .unwrap();
+
+    let loaded = Game::load(&game.dump()).unwrap();
+    assert_eq!(loaded.shared.seats, game.shared.seats);
+    assert_eq!(loaded.shared.komis, game.shared.komis);
+    assert_eq!(loaded.shared.board, game.shared.board);
+}
+
+#[test]
+fn find_groups_partitions_the_stones() {
+    use crate::states::scoring::RegionCache;
+    use rand::prelude::*;
+    use rand_pcg::Lcg64Xsh32;
+
+    let mut rng = Lcg64Xsh32::seed_from_u64(3);
+    for _ in 0..200 {
+        let (width, height) = (rng.gen_range(1, 20), rng.gen_range(1, 20));
+        let mut board = Board::empty(width, height, rng.gen());
+        // Mostly sparse boards, some nearly full.
+        let density = rng.gen_range(0.0, 1.0);
+        for point in board.points.iter_mut() {
+            if rng.gen_bool(density) {
+                *point = Color(rng.gen_range(1, 4));
+            }
+        }
+        let groups = find_groups(&board);
+
+        // Every stone is in exactly one group of its color.
+        assert_eq!(RegionCache::new(&board, &groups).stones(), board);
+        let stones = board.points.iter().filter(|c| !c.is_empty()).count();
+        assert_eq!(groups.iter().map(|g| g.points.len()).sum::<usize>(), stones);
+
+        let mut group_of = Board::<usize>::empty(width, height, board.toroidal());
+        for (idx, group) in groups.iter().enumerate() {
+            for &point in &group.points {
+                *group_of.point_mut(point) = idx;
+            }
+        }
+        for (idx, group) in groups.iter().enumerate() {
+            let mut liberties = HashSet::new();
+            for &point in &group.points {
+                for p in board.surrounding_points(point) {
+                    match board.get_point(p) {
+                        Color(0) => {
+                            liberties.insert(p);
+                        }
+                        // Touching stones of the same color are in the same group.
+                        c if c == group.team => assert_eq!(group_of.get_point(p), idx),
+                        _ => {}
+                    }
+                }
+            }
+            assert_eq!(group.liberties as usize, liberties.len());
+        }
+    }
+}
+
+#[test]
+fn only_done_is_terminal() {
+    let board = Board::empty(3, 3, false);
+    let seats = [Seat::new(Color(1)), Seat::new(Color(2))];
+    let scoring = ScoringState::new(&board, &seats, &[0, 0], &GameModifier::default(), 0);
+    let states = [
+        GameState::free_placement(2, 2, board.clone(), false),
+        GameState::play(2),
+        GameState::Scoring(scoring.clone()),
+        GameState::Waiting(WaitingState::new(2)),
+        GameState::Simultaneous(crate::states::SimultaneousPlayState::new(2)),
+        GameState::KomiAuction(KomiAuctionState::default()),
+    ];
+    for state in &states {
+        assert!(!state.is_terminal());
+        assert_eq!(state.result(), None);
+    }
+
+    let done = scoring.finish_with_winner(ResultKind::FirstCapture, Color(2));
+    assert!(done.is_terminal());
+    assert_eq!(
+        done.result(),
+        Some(&GameResult {
+            kind: ResultKind::FirstCapture,
+            winner: Some(Color(2)),
+            margin: 0,
+            standings: vec![Color(2)],
+        })
+    );
+
+    // A finished game without a recorded result is still over.
+    let done = GameState::Done(scoring);
+    assert!(done.is_terminal());
+    assert_eq!(done.result(), None);
+}
+
+#[derive(Default)]
+struct RecordingSink(std::sync::Mutex<Vec<String>>);
+
+impl EventSink for RecordingSink {
+    fn action_made(&self, player_id: u64, action: &ActionKind) {
+        let event = format!("{} {:?}", player_id, action);
+        self.0.lock().unwrap().push(event);
+    }
+
+    fn stones_captured(&self, color: Color, stones: u32) {
+        let event = format!("captured {} of {:?}", stones, color);
+        self.0.lock().unwrap().push(event);
+    }
+
+    fn phase_changed(&self, from: GamePhase, to: GamePhase) {
+        let event = format!("{:?} -> {:?}", from, to);
+        self.0.lock().unwrap().push(event);
+    }
+}
+
+#[test]
+fn event_sink_sees_a_short_game() {
+    let game = GameBuilder::new().board_size(5, 5).build().unwrap();
+    let mut driver = GameDriver::seated(game).unwrap();
+    let sink = std::sync::Arc::new(RecordingSink::default());
+    driver.game.shared.event_sink = Some(sink.clone());
+
+    driver
+        .play_all(&[
+            (1, ActionKind::Place(0, 1)),
+            (2, ActionKind::Place(0, 0)),
+            (1, ActionKind::Place(1, 0)),
+            (2, ActionKind::Pass),
+            (1, ActionKind::Pass),
+            (1, ActionKind::AcceptScore),
+            (2, ActionKind::AcceptScore),
+        ])
+        .unwrap();
+    // Rejected actions aren't events.
+    assert!(driver.play(1, ActionKind::Pass).is_err());
+
+    assert_eq!(
+        *sink.0.lock().unwrap(),
+        vec![
+            "1 Place(0, 1)",
+            "2 Place(0, 0)",
+            "1 Place(1, 0)",
+            "captured 1 of 2",
+            "2 Pass",
+            "1 Pass",
+            "Play -> Scoring",
+            "1 AcceptScore",
+            "2 AcceptScore",
+            "Scoring -> Done",
+        ]
+    );
+}
+
+#[test]
+fn review_variation_leaves_the_game_alone() {
+    let mut game = GameBuilder::new().board_size(9, 9).build().unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+    for idx in 0..12 {
+        let player = if idx % 2 == 0 { 100 } else { 200 };
+        game.make_action(player, ActionKind::Place(idx % 9, idx / 9), Millisecond(0))
+            .unwrap();
+    }
+    let board = game.shared.board.clone();
+
+    let mut review = ReviewSession::new(&game).unwrap();
+    assert_eq!(review.game().shared.board, board);
+
+    let at_ten = review.goto(10).unwrap();
+    assert_eq!(at_ten.move_number(), 10);
+    let mainline_ten = review.current();
+    review.play(100, ActionKind::Place(8, 8)).unwrap();
+    review.play(200, ActionKind::Place(7, 8)).unwrap();
+    assert_eq!(
+        review.play(100, ActionKind::Place(7, 8)).err(),
+        Some(MakeActionError::PointOccupied)
+    );
+    assert_eq!(
+        review.variation(),
+        vec![
+            (100, ActionKind::Place(8, 8)),
+            (200, ActionKind::Place(7, 8))
+        ]
+    );
+    assert_eq!(
+        review.game().shared.board.get_point(Point::new(8, 8)),
+        Color(1)
+    );
+
+    // Replaying a move follows the existing branch.
+    assert!(review.back());
+    assert!(review.back());
+    assert!(!review.back());
+    assert_eq!(review.current(), mainline_ten);
+    review.play(100, ActionKind::Place(8, 8)).unwrap();
+    assert_eq!(review.nodes()[mainline_ten].children.len(), 1);
+
+    assert_eq!(game.shared.board, board);
+    assert_eq!(game.move_number(), 12);
+    assert_eq!(review.goto(12).unwrap().shared.board, board);
+}
+
+#[test]
+fn corner_book_suggests_star_points() {
+    let mut game = GameBuilder::new().board_size(19, 19).build().unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+
+    let hints = CornerBook.hints(&game.shared, Color(1));
+    for &(x, y) in &[(3, 3), (15, 3), (3, 15), (15, 15)] {
+        let hint = hints.iter().find(|h| h.0 == Point::new(x, y)).unwrap();
+        assert_eq!(hint.1, "Star point");
+    }
+    // Komoku in both orientations of every corner.
+    assert_eq!(hints.len(), 4 * 3);
+
+    // The approach mirrors the star point it approaches.
+    game.make_action(100, ActionKind::Place(15, 3), Millisecond(0))
+        .unwrap();
+    let hints = CornerBook.hints(&game.shared, Color(2));
+    assert!(hints.contains(&(Point::new(16, 5), "Low approach".to_string())));
+    assert!(hints.contains(&(Point::new(13, 2), "Low approach".to_string())));
+    assert!(hints.contains(&(Point::new(16, 2), "3-3 invasion".to_string())));
+
+    let small = GameBuilder::new().board_size(9, 9).build().unwrap();
+    assert!(CornerBook.hints(&small.shared, Color(1)).is_empty());
+}
+
+#[test]
+fn standard_scheme_gives_four_colors_distinct_markers() {
+    let scheme = ColorScheme::standard(4);
+    let markers = scheme.colors.iter().map(|s| s.marker).collect::<Vec<_>>();
+    for (idx, marker) in markers.iter().enumerate() {
+        assert!(!markers[idx + 1..].contains(marker));
+    }
+    assert_eq!(scheme.style(Color(3)).unwrap().rgb, [0x50, 0x74, 0xbc]);
+    assert!(scheme.style(Color(5)).is_none());
+
+    let packed = serde_cbor::to_vec(&scheme).unwrap();
+    let unpacked: ColorScheme = serde_cbor::from_slice(&packed).unwrap();
+    assert_eq!(unpacked, scheme);
+}
+
+#[test]
+fn stuck_player_passes_automatically() {
+    let mut game = game_with_position(
+        &[
+            "x.x.", //
+            "xxxx", //
+            "x.x.", //
+        ],
+        false,
+    );
+    game.shared.mods.auto_pass_when_stuck = true;
+
+    let white_moves = |game: &Game| match &game.state {
+        GameState::Play(state) => {
+            let mut shared = game.shared.clone();
+            shared.turn = 1;
+            state.legal_moves(&shared)
+        }
+        _ => panic!("Not playing"),
+    };
+    assert!(white_moves(&game).is_empty());
+
+    // Every placement of white would be suicide, so the turn comes straight back.
+    game.make_action(100, ActionKind::Place(3, 0), Millisecond(0))
+        .unwrap();
+    assert_eq!(game.shared.turn, 0);
+    assert_eq!(game.move_number(), 2);
+    assert_eq!(
+        game.shared.move_info(2).map(|info| info.placed),
+        Some(Vec::new())
+    );
+
+    // White's automatic pass counts towards ending the game.
+    game.make_action(100, ActionKind::Pass, Millisecond(0))
+        .unwrap();
+    assert!(matches!(game.state, GameState::Scoring(_)));
+}
+
+#[test]
+fn double_capture_doesnt_depend_on_scan_order() {
+    // Black at (2, 1) captures both white stones. The rotated board has the same
+    // move with the groups found in the opposite order.
+    let rows = [
+        ".x.x.", //
+        "xo.ox", //
+        ".x.x.", //
+        "..x..", //
+    ];
+    let rotated = rows
+        .iter()
+        .rev()
+        .map(|row| row.chars().rev().collect::<String>())
+        .collect::<Vec<_>>();
+    let rotated = rotated.iter().map(|r| r.as_str()).collect::<Vec<_>>();
+
+    let mut game = game_with_position(&rows, false);
+    game.make_action(100, ActionKind::Place(2, 1), Millisecond(0))
+        .unwrap();
+    let mut other = game_with_position(&rotated, false);
+    other
+        .make_action(100, ActionKind::Place(2, 2), Millisecond(0))
+        .unwrap();
+
+    let (width, height) = (game.shared.board.width(), game.shared.board.height());
+    for y in 0..height {
+        for x in 0..width {
+            assert_eq!(
+                game.shared.board.get_point(Point::new(x, y)),
+                other
+                    .shared
+                    .board
+                    .get_point(Point::new(width - 1 - x, height - 1 - y))
+            );
+        }
+    }
+    let info = game.shared.move_info(1).unwrap();
+    assert_eq!(info.captured, vec![Point::new(1, 1), Point::new(3, 1)]);
+}
+
+#[test]
+fn view_lists_group_liberties() {
+    let game = game_with_position(
+        &[
+            ".xo..", //
+            "xoo..", //
+            ".x...", //
+            ".....", //
+        ],
+        false,
+    );
+    let groups = game.get_view(100).groups;
+    assert_eq!(groups.len(), 4);
+    let in_atari = groups.iter().filter(|g| g.in_atari()).collect::<Vec<_>>();
+    assert_eq!(
+        in_atari,
+        vec![&GroupLiberties {
+            point: Point::new(1, 0),
+            color: Color(1),
+            liberties: 1,
+        }]
+    );
+    let white = groups.iter().find(|g| g.color == Color(2)).unwrap();
+    assert_eq!((white.point, white.liberties), (Point::new(2, 0), 3));
+}
+
+#[test]
+fn three_colors_end_after_a_full_round_of_passes() {
+    let three_colors = |pass_majority_ends| {
+        let game = GameBuilder::new()
+            .seats(&[1, 2, 3])
+            .komi(GroupVec::from(&[0, 0, 0][..]))
+            .board_size(7, 7)
+            .rules(GameModifier {
+                pass_majority_ends,
+                ..GameModifier::default()
+            })
+            .build()
+            .unwrap();
+        GameDriver::seated(game).unwrap()
+    };
+
+    let mut driver = three_colors(false);
+    driver
+        .play_all(&[
+            (1, ActionKind::Pass),
+            (2, ActionKind::Pass),
+            (3, ActionKind::Place(3, 3)),
+            (1, ActionKind::Pass),
+            (2, ActionKind::Pass),
+        ])
+        .unwrap();
+    assert!(matches!(driver.state(), GameState::Play(_)));
+    driver.play(3, ActionKind::Pass).unwrap();
+    assert!(matches!(driver.state(), GameState::Scoring(_)));
+
+    // Black's pass stands through white's move, so red makes a majority.
+    let mut driver = three_colors(true);
+    driver
+        .play_all(&[(1, ActionKind::Pass), (2, ActionKind::Place(3, 3))])
+        .unwrap();
+    assert!(matches!(driver.state(), GameState::Play(_)));
+    driver.play(3, ActionKind::Pass).unwrap();
+    assert!(matches!(driver.state(), GameState::Scoring(_)));
+}
+
+#[test]
+fn teaching_undo_is_logged() {
+    let game = |teaching_mode| {
+        let game = GameBuilder::new()
+            .board_size(5, 5)
+            .rules(GameModifier {
+                teaching_mode,
+                ..GameModifier::default()
+            })
+            .build()
+            .unwrap();
+        let mut driver = GameDriver::seated(game).unwrap();
+        driver
+            .play_all(&[(1, ActionKind::Place(1, 1)), (2, ActionKind::Place(3, 3))])
+            .unwrap();
+        driver
+    };
+
+    // Normally only the seat to move can undo.
+    let mut driver = game(false);
+    assert_eq!(
+        driver.play(2, ActionKind::Cancel),
+        Err(MakeActionError::NotTurn)
+    );
+
+    let mut driver = game(true);
+    driver.play(2, ActionKind::Cancel).unwrap();
+    assert_eq!(driver.game.move_number(), 1);
+    assert_eq!(driver.game.shared.turn, 1);
+    let last = driver.game.actions.last().unwrap();
+    assert_eq!(last.user_id, 2);
+    assert_eq!(
+        last.action,
+        ReplayActionKind::Undo(MoveInfo {
+            placed: vec![Point::new(3, 3)],
+            captured: Vec::new(),
+        })
+    );
+
+    let loaded = Game::load(&driver.game.dump()).unwrap();
+    assert_eq!(loaded.shared.board, driver.game.shared.board);
+    assert_eq!(loaded.actions, driver.game.actions);
+}
+
+#[test]
+fn ko_point_needs_a_true_ko_shape() {
+    let mut game = game_with_position(
+        &[
+            ".xo..", //
+            "xo.o.", //
+            ".xo..", //
+            ".....", //
+        ],
+        false,
+    );
+    game.make_action(100, ActionKind::Place(2, 1), Millisecond(0))
+        .unwrap();
+    assert_eq!(game.shared.ko_point(), Some(Point::new(1, 1)));
+    assert_eq!(
+        game.make_action(200, ActionKind::Place(1, 1), Millisecond(0)),
+        Err(MakeActionError::Ko)
+    );
+    game.make_action(200, ActionKind::Place(4, 3), Millisecond(0))
+        .unwrap();
+    assert_eq!(game.shared.ko_point(), None);
+
+    // Capturing one stone with a stone that keeps other liberties isn't a ko.
+    let mut game = game_with_position(
+        &[
+            "xox..", //
+            ".....", //
+            ".....", //
+        ],
+        false,
+    );
+    game.make_action(100, ActionKind::Place(1, 1), Millisecond(0))
+        .unwrap();
+    assert_eq!(game.shared.move_info(1).unwrap().captured.len(), 1);
+    assert_eq!(game.shared.ko_point(), None);
+}
+
+#[test]
+fn capture_count_atari_go_ends_at_the_move_cap() {
+    let counting_game = |max_moves| {
+        let game = GameBuilder::new()
+            .board_size(5, 5)
+            .rules(GameModifier {
+                atari_go: Some(AtariGo {
+                    capture_count: true,
+                    ..AtariGo::default()
+                }),
+                max_moves: Some(max_moves),
+                ..GameModifier::default()
+            })
+            .build()
+            .unwrap();
+        GameDriver::seated(game).unwrap()
+    };
+
+    let mut driver = counting_game(4);
+    driver
+        .play_all(&[
+            (1, ActionKind::Place(1, 0)),
+            (2, ActionKind::Place(0, 0)),
+            (1, ActionKind::Place(0, 1)),
+        ])
+        .unwrap();
+    // The capture doesn't end the game.
+    assert!(matches!(driver.state(), GameState::Play(_)));
+    driver.play(2, ActionKind::Place(4, 4)).unwrap();
+    let result = driver.result().unwrap();
+    assert_eq!(result.kind, ResultKind::MostCaptures);
+    assert_eq!(result.winner, Some(Color(1)));
+    assert_eq!(result.margin, driver.game.shared.mods.point_unit());
+
+    let mut driver = counting_game(2);
+    driver
+        .play_all(&[(1, ActionKind::Place(2, 2)), (2, ActionKind::Place(3, 3))])
+        .unwrap();
+    let result = driver.result().unwrap();
+    assert_eq!(result.kind, ResultKind::MostCaptures);
+    assert_eq!(result.winner, None);
+
+    // Passing out counts the captures too.
+    let mut driver = counting_game(10);
+    driver
+        .play_all(&[(1, ActionKind::Pass), (2, ActionKind::Pass)])
+        .unwrap();
+    assert_eq!(driver.result().unwrap().kind, ResultKind::MostCaptures);
+}
@@ -1,9 +1,13 @@
 #[macro_use]
 mod assume;
+pub mod bot;
 pub mod game;
 pub mod message;
 pub mod states;
 
+#[cfg(test)]
+mod test_support;
+
 #[cfg(test)]
 mod tests {
     #[test]
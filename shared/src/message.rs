@@ -10,7 +10,9 @@ use crate::game;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum GameAction {
-    Place(u32, u32),
+    /// The third field confirms a self-atari, see `GameModifier::confirm_self_atari`.
+    /// It's left out by older clients.
+    Place(u32, u32, #[serde(default)] bool),
     Pass,
     Cancel,
     Resign,
@@ -19,6 +21,11 @@ pub enum GameAction {
     LeaveSeat(u32),
     KickPlayer(u64),
     RequestSGF,
+    AcceptScore,
+    RejectScore(Option<String>),
+    ResumePlay,
+    ProposeKomi(i32),
+    ChooseColor(u8),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -27,7 +34,7 @@ pub struct StartGame {
     pub seats: Vec<u8>,
     pub komis: Vec<i32>,
     pub size: (u8, u8),
-    pub mods: game::GameModifier,
+    pub mods: Box<game::GameModifier>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -139,6 +146,10 @@ pub enum ServerMessage {
         points: Vec<i32>,
         move_number: u32,
         clock: Option<game::clock::GameClock>,
+        #[serde(default)]
+        last_move: Vec<game::MoveEvent>,
+        #[serde(default)]
+        groups: Vec<game::GroupLiberties>,
     },
     BoardAt {
         room_id: u32,
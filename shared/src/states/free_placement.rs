@@ -1,6 +1,6 @@
 use crate::game::{
     ActionChange, ActionKind, Board, BoardHistory, Color, GameState, MakeActionError,
-    MakeActionResult, Seat, SharedState, VisibilityBoard,
+    MakeActionResult, Point, Seat, SharedState, VisibilityBoard,
 };
 use serde::{Deserialize, Serialize};
 
@@ -39,7 +39,7 @@ impl FreePlacement {
         &mut self,
         shared: &mut SharedState,
         player_id: u64,
-        (x, y): (u32, u32),
+        Point { x, y }: Point,
     ) -> MakeActionResult {
         let (seat_idx, active_seat) = get_seat(&shared.seats, player_id);
         let team = active_seat.team;
@@ -63,7 +63,7 @@ impl FreePlacement {
             // In pixel mode coordinate 0,0 is outside the board.
             // This is to adjust for it.
 
-            if x > board.width || y > board.height {
+            if x > board.width() || y > board.height() {
                 return Err(MakeActionError::OutOfBounds);
             }
             let x = x as i32 - 1;
@@ -74,7 +74,7 @@ impl FreePlacement {
                 if x < 0 || y < 0 {
                     continue;
                 }
-                let coord = (x as u32, y as u32);
+                let coord = Point::new(x as u32, y as u32);
                 if !board.point_within(coord) {
                     continue;
                 }
@@ -90,12 +90,12 @@ impl FreePlacement {
                 return Err(MakeActionError::PointOccupied);
             }
         } else {
-            if !board.point_within((x, y)) {
+            if !board.point_within(Point::new(x, y)) {
                 return Err(MakeActionError::OutOfBounds);
             }
 
             // TODO: don't repeat yourself
-            let point = board.point_mut((x, y));
+            let point = board.point_mut(Point::new(x, y));
             if !point.is_empty() {
                 return Err(MakeActionError::PointOccupied);
             }
@@ -141,7 +141,7 @@ impl FreePlacement {
     }
 
     fn build_board(&self, mut board: Board) -> (Board, VisibilityBoard) {
-        let mut visibility = VisibilityBoard::empty(board.width, board.height, board.toroidal);
+        let mut visibility: VisibilityBoard = board.empty_like();
 
         for view_board in &self.boards {
             for (a, b, v) in izip!(
@@ -200,13 +200,18 @@ impl FreePlacement {
         action: ActionKind,
     ) -> MakeActionResult {
         match action {
-            ActionKind::Place(x, y) => self.make_action_place(shared, player_id, (x, y)),
+            ActionKind::Place(x, y) => self.make_action_place(shared, player_id, Point::new(x, y)),
             ActionKind::Pass => self.make_action_pass(shared, player_id),
             ActionKind::Cancel => self.make_action_cancel(shared, player_id),
             ActionKind::Resign => {
                 // We don't allow resigning in free placement
                 Ok(ActionChange::None)
             }
+            ActionKind::AcceptScore
+            | ActionKind::RejectScore(_)
+            | ActionKind::ResumePlay
+            | ActionKind::ProposeKomi(_)
+            | ActionKind::ChooseColor(_) => Err(MakeActionError::Illegal),
         }
     }
 }
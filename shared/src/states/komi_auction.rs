@@ -0,0 +1,114 @@
+use crate::game::{
+    ActionChange, ActionKind, Color, GameState, MakeActionError, MakeActionResult, SharedState,
+};
+use serde::{Deserialize, Serialize};
+
+/// The agreed outcome of a komi auction.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KomiAuctionResult {
+    /// Komi for white in half points.
+    pub komi: i32,
+    /// The chooser took black, so every seat swapped colors.
+    pub swapped: bool,
+}
+
+/// Pie rule for komi. Black proposes a komi and white then picks which color to
+/// play with it, so the proposer has a reason to make the komi fair.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct KomiAuctionState {
+    /// Komi for white in half points, once black has proposed one.
+    pub proposal: Option<i32>,
+}
+
+impl KomiAuctionState {
+    fn holds_color(shared: &SharedState, player_id: u64, color: Color) -> bool {
+        shared
+            .seats
+            .iter()
+            .any(|s| s.player == Some(player_id) && s.team == color)
+    }
+
+    fn make_action_propose(
+        &mut self,
+        shared: &mut SharedState,
+        player_id: u64,
+        komi: i32,
+    ) -> MakeActionResult {
+        if !Self::holds_color(shared, player_id, Color(1)) {
+            return Err(MakeActionError::NotTurn);
+        }
+        self.proposal = Some(komi);
+        Ok(ActionChange::None)
+    }
+
+    fn make_action_choose(
+        &mut self,
+        shared: &mut SharedState,
+        player_id: u64,
+        color: Color,
+    ) -> MakeActionResult {
+        if !Self::holds_color(shared, player_id, Color(2)) {
+            return Err(MakeActionError::NotTurn);
+        }
+        let komi = self.proposal.ok_or(MakeActionError::Illegal)?;
+        if color != Color(1) && color != Color(2) {
+            return Err(MakeActionError::Illegal);
+        }
+
+        let komis = [0, komi];
+        let rebase = |points: &mut [i32], old: &[i32]| {
+            for ((points, old), new) in points.iter_mut().zip(old).zip(&komis) {
+                *points += new - old;
+            }
+        };
+        rebase(&mut shared.points, &shared.komis);
+        rebase(&mut shared.board_history[0].points, &shared.komis);
+        shared.komis = komis[..].into();
+
+        // Taking black hands white to the proposer.
+        let swapped = color == Color(1);
+        if swapped {
+            let to_move = shared.get_active_seat().team;
+            for seat in &mut shared.seats {
+                seat.team = Color(3 - seat.team.0);
+            }
+            shared.turn = shared
+                .seats
+                .iter()
+                .position(|s| s.team == to_move)
+                .expect("Both colors have seats");
+            shared.board_history[0].turn = shared.turn;
+        }
+        shared.komi_auction = Some(KomiAuctionResult { komi, swapped });
+
+        Ok(ActionChange::SwapState(GameState::before_play(shared)))
+    }
+
+    pub fn make_action(
+        &mut self,
+        shared: &mut SharedState,
+        player_id: u64,
+        action: ActionKind,
+    ) -> MakeActionResult {
+        match action {
+            ActionKind::ProposeKomi(komi) => self.make_action_propose(shared, player_id, komi),
+            ActionKind::ChooseColor(color) => self.make_action_choose(shared, player_id, color),
+            ActionKind::Cancel => {
+                if !Self::holds_color(shared, player_id, Color(1)) {
+                    return Err(MakeActionError::NotTurn);
+                }
+                self.proposal = None;
+                Ok(ActionChange::None)
+            }
+            ActionKind::Resign => {
+                // We don't allow resigning before the game starts
+                Ok(ActionChange::None)
+            }
+            ActionKind::Place(..)
+            | ActionKind::Pass
+            | ActionKind::AcceptScore
+            | ActionKind::RejectScore(_)
+            | ActionKind::ResumePlay => Err(MakeActionError::Illegal),
+        }
+    }
+}
@@ -1,13 +1,19 @@
 pub mod free_placement;
+pub mod komi_auction;
 pub mod play;
 pub mod scoring;
+pub mod simultaneous;
+pub mod waiting;
 
 pub use self::free_placement::FreePlacement;
-pub use self::play::PlayState;
-pub use self::scoring::ScoringState;
+pub use self::komi_auction::{KomiAuctionResult, KomiAuctionState};
+pub use self::play::{CaptureMatrix, PlayState};
+pub use self::scoring::{GameResult, ResultKind, ScoreRejection, ScoringEvent, ScoringState};
+pub use self::simultaneous::SimultaneousPlayState;
+pub use self::waiting::WaitingState;
 
 use crate::assume::AssumeFrom;
-use crate::game::{Board, Seat};
+use crate::game::{Board, GameModifier, Seat, SharedState};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -16,6 +22,9 @@ pub enum GameState {
     Play(PlayState),
     Scoring(ScoringState),
     Done(ScoringState),
+    Waiting(WaitingState),
+    Simultaneous(SimultaneousPlayState),
+    KomiAuction(KomiAuctionState),
 }
 
 impl GameState {
@@ -33,12 +42,69 @@ impl GameState {
         ))
     }
 
+    /// The state a new game is created in.
+    pub fn new_game(shared: &SharedState) -> Self {
+        if shared.mods.komi_auction {
+            GameState::KomiAuction(KomiAuctionState::default())
+        } else {
+            GameState::before_play(shared)
+        }
+    }
+
+    /// Waits for the players to be ready if the rules ask for it, otherwise starts the game.
+    pub fn before_play(shared: &SharedState) -> Self {
+        if shared.mods.ready_check.is_some() {
+            GameState::Waiting(WaitingState::new(shared.seats.len()))
+        } else {
+            GameState::initial(shared)
+        }
+    }
+
+    /// The state a game starts in once every player is ready.
+    pub fn initial(shared: &SharedState) -> Self {
+        if let Some(rules) = &shared.mods.hidden_move {
+            GameState::free_placement(
+                shared.seats.len(),
+                shared.komis.len(),
+                shared.board.clone(),
+                rules.teams_share_stones,
+            )
+        } else if shared.mods.simultaneous.is_some() {
+            GameState::Simultaneous(SimultaneousPlayState::new(shared.seats.len()))
+        } else {
+            GameState::Play(PlayState::starting(
+                shared.seats.len(),
+                shared.komis.len(),
+                &shared.mods,
+            ))
+        }
+    }
+
+    /// Whether the game is over. Nothing can be played after this.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, GameState::Done(_))
+    }
+
+    /// How the game ended, `None` while it's still going.
+    pub fn result(&self) -> Option<&GameResult> {
+        match self {
+            GameState::Done(scoring) => scoring.result.as_ref(),
+            _ => None,
+        }
+    }
+
     pub fn play(seat_count: usize) -> Self {
         GameState::Play(PlayState::new(seat_count))
     }
 
-    pub fn scoring(board: &Board, seats: &[Seat], scores: &[i32]) -> Self {
-        GameState::Scoring(ScoringState::new(board, seats, scores))
+    pub fn scoring(
+        board: &Board,
+        seats: &[Seat],
+        scores: &[i32],
+        mods: &GameModifier,
+        turn: usize,
+    ) -> Self {
+        GameState::Scoring(ScoringState::new(board, seats, scores, mods, turn))
     }
 }
 
@@ -46,3 +112,5 @@ assume!(GameState);
 assume!(GameState, Play(x) => x, PlayState);
 assume!(GameState, Scoring(x) => x, ScoringState);
 assume!(GameState, FreePlacement(x) => x, FreePlacement);
+assume!(GameState, Simultaneous(x) => x, SimultaneousPlayState);
+assume!(GameState, KomiAuction(x) => x, KomiAuctionState);
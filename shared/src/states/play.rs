@@ -3,25 +3,82 @@ mod tetris;
 pub(crate) mod traitor;
 
 use crate::game::{
-    find_groups, ActionChange, ActionKind, Board, BoardHistory, Color, GameState, Group, GroupVec,
-    MakeActionError, MakeActionResult, Point, SharedState, VisibilityBoard,
+    find_groups, ActionChange, ActionKind, AtariGo, Board, BoardHistory, Color, ColorScores,
+    EliminationPolicy, GameModifier, GameState, Group, GroupVec, HandicapPlacement,
+    MakeActionError, MakeActionResult, MoveExpectation, Point, PointClass, SharedState,
+    VisibilityBoard,
 };
 use serde::{Deserialize, Serialize};
 
 use bitmaps::Bitmap;
 use tinyvec::tiny_vec;
 
+use super::scoring::{score_board, GameResult, ResultKind};
 use super::ScoringState;
 
 type Revealed = bool;
 
+/// Stones each color has captured from each color, so prisoners can be told apart
+/// with more than two colors. Removed stones of the player's own color count as
+/// captured from themselves.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CaptureMatrix(Vec<GroupVec<u32>>);
+
+impl CaptureMatrix {
+    pub fn get(&self, by: Color, from: Color) -> u32 {
+        self.0
+            .get(by.as_usize().wrapping_sub(1))
+            .and_then(|row| row.get(from.as_usize().wrapping_sub(1)))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Every stone captured by `by`, whatever the color.
+    pub fn total_by(&self, by: Color) -> u32 {
+        self.0
+            .get(by.as_usize().wrapping_sub(1))
+            .map_or(0, |row| row.iter().sum())
+    }
+
+    /// Stones each of `color_count` colors captured from the others, indexed like points.
+    pub fn of_opponents(&self, color_count: usize) -> GroupVec<u32> {
+        (1..=color_count as u8)
+            .map(Color)
+            .map(|c| self.total_by(c) - self.get(c, c))
+            .collect()
+    }
+
+    /// `from` holds the stones captured of each color, indexed like points.
+    fn add(&mut self, by: Color, from: &[u32]) {
+        let by = by.as_usize() - 1;
+        if self.0.len() <= by {
+            self.0.resize(by + 1, GroupVec::new());
+        }
+        let row = &mut self.0[by];
+        if row.len() < from.len() {
+            row.resize(from.len(), 0);
+        }
+        for (total, count) in row.iter_mut().zip(from) {
+            *total += count;
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PlayState {
     // TODO: use smallvec?
     pub players_passed: Vec<bool>,
-    pub last_stone: Option<GroupVec<(u32, u32)>>,
+    pub last_stone: Option<GroupVec<Point>>,
     /// Optimization for superko
     pub capture_count: usize,
+    /// Free handicap stones each color still has to place on its turn.
+    #[serde(default)]
+    pub handicap_left: GroupVec<u32>,
+    /// Nothing has changed in a while, see `NoProgress`. Ending is still up to the players.
+    #[serde(default)]
+    pub no_progress: bool,
+    #[serde(default)]
+    pub captures: CaptureMatrix,
 }
 
 impl PlayState {
@@ -30,22 +87,38 @@ impl PlayState {
             players_passed: vec![false; seat_count],
             last_stone: None,
             capture_count: 0,
+            handicap_left: GroupVec::new(),
+            no_progress: false,
+            captures: CaptureMatrix::default(),
+        }
+    }
+
+    /// A new game, with the free handicap stones still to be placed.
+    pub fn starting(seat_count: usize, color_count: usize, mods: &GameModifier) -> Self {
+        let mut state = PlayState::new(seat_count);
+        for handicap in &mods.color_handicaps {
+            if handicap.placement == HandicapPlacement::Free {
+                state.handicap_left.resize(color_count, 0);
+                state.handicap_left[handicap.color.as_usize() - 1] = handicap.stones;
+            }
         }
+        state
     }
 
     fn place_stone(
         &mut self,
         shared: &mut SharedState,
-        (x, y): Point,
+        point: Point,
         color_placed: Color,
     ) -> MakeActionResult<GroupVec<Point>> {
+        let Point { x, y } = point;
         let mut points_played = GroupVec::new();
 
         if shared.mods.pixel {
             // In pixel mode coordinate 0,0 is outside the board.
             // This is to adjust for it.
 
-            if x > shared.board.width || y > shared.board.height {
+            if x > shared.board.width() || y > shared.board.height() {
                 return Err(MakeActionError::OutOfBounds);
             }
             let x = x as i32 - 1;
@@ -82,41 +155,53 @@ impl PlayState {
                 return Err(MakeActionError::PointOccupied);
             }
         } else {
-            if !shared.board.point_within((x, y)) {
+            if !shared.board.point_within(point) {
                 return Err(MakeActionError::OutOfBounds);
             }
 
             // TODO: don't repeat yourself
-            let point = shared.board.point_mut((x, y));
             let revealed = if let Some(visibility) = &mut shared.board_visibility {
-                let revealed = !visibility.get_point((x, y)).is_empty();
-                *visibility.point_mut((x, y)) = Bitmap::new();
+                let revealed = !visibility.get_point(point).is_empty();
+                *visibility.point_mut(point) = Bitmap::new();
                 revealed
             } else {
                 false
             };
-            if !point.is_empty() {
+            let stone = shared.board.point_mut(point);
+            if !stone.is_empty() {
                 if revealed {
-                    self.last_stone = Some(tiny_vec![[Point; 8] => (x, y)]);
+                    self.last_stone = Some(tiny_vec![[Point; 8] => point]);
                     return Ok(points_played);
                 }
                 return Err(MakeActionError::PointOccupied);
             }
 
-            *point = color_placed;
-            points_played.push((x, y));
+            *stone = color_placed;
+            points_played.push(point);
         }
 
         Ok(points_played)
     }
 
+    /// Resolves captures after `points_played` were put on the board. The order is
+    /// fixed, so every implementation ends with the same board, reveals and points:
+    /// 1. Every group of another color without liberties is removed, ordered by
+    ///    `Group::id`, so the group with the first stone in board order goes first.
+    /// 2. Groups of the color to move that are still without liberties are then
+    ///    suicide, or self-capture, in the same order.
+    ///
+    /// Removing a group only gives other groups liberties, so the order of the
+    /// removals doesn't change the final board.
     fn capture(
         &self,
         shared: &mut SharedState,
         points_played: &mut GroupVec<Point>,
-    ) -> (usize, Revealed) {
+    ) -> (usize, GroupVec<u32>, Vec<(Point, Color)>, Revealed) {
         let active_seat = shared.get_active_seat();
         let mut captures = 0;
+        let mut captured = Vec::new();
+        let mut captured_from =
+            std::iter::repeat_n(0, shared.komis.len()).collect::<GroupVec<u32>>();
         let mut revealed = false;
 
         if shared.mods.phantom.is_some() {
@@ -132,14 +217,17 @@ impl PlayState {
             let board = &mut shared.board;
             for point in &group.points {
                 *board.point_mut(*point) = Color::empty();
+                captured.push((*point, group.team));
                 captures += 1;
             }
+            if let Some(count) = captured_from.get_mut(group.team.as_usize() - 1) {
+                *count += group.points.len() as u32;
+            }
             let reveals = reveal_group(shared.board_visibility.as_mut(), group, board);
 
             if let Some(ponnuki) = shared.mods.ponnuki_is_points {
-                let surrounding_count = board.surrounding_points(group.points[0]).count();
                 if group.points.len() == 1
-                    && surrounding_count == 4
+                    && board.point_class(group.points[0]) == PointClass::Interior
                     && board
                         .surrounding_points(group.points[0])
                         .all(|p| board.get_point(p) == active_seat.team)
@@ -147,14 +235,15 @@ impl PlayState {
                         .surrounding_diagonal_points(group.points[0])
                         .all(|p| board.get_point(p) != active_seat.team)
                 {
-                    shared.points[active_seat.team.0 as usize - 1] += ponnuki;
+                    *shared.points.score_for_mut(active_seat.team) += ponnuki;
                 }
             }
 
             reveals
         };
 
-        let groups = find_groups(&shared.board);
+        let mut groups = find_groups(&shared.board);
+        groups.sort_by_key(Group::id);
         let dead_opponents = groups
             .iter()
             .filter(|g| g.liberties == 0 && g.team != active_seat.team);
@@ -164,15 +253,20 @@ impl PlayState {
         }
 
         // TODO: only re-scan own previously dead grouos
-        let groups = find_groups(&shared.board);
+        let mut groups = find_groups(&shared.board);
+        groups.sort_by_key(Group::id);
         let dead_own = groups
             .iter()
             .filter(|g| g.liberties == 0 && g.team == active_seat.team);
 
         for group in dead_own {
+            // Opponents were captured above, so a move that captures always keeps a liberty.
+            // Self-capture only takes groups that had stones before this move.
+            let self_capture =
+                shared.mods.self_capture && group.points.iter().any(|p| !points_played.contains(p));
             let mut removed_move = false;
             for point in &group.points {
-                if points_played.contains(point) {
+                if !self_capture && points_played.contains(point) {
                     points_played.retain(|x| x != point);
                     *shared.board.point_mut(*point) = Color::empty();
                     removed_move = true;
@@ -187,11 +281,17 @@ impl PlayState {
             }
         }
 
+        // Stones a color loses to its own move aren't points for it.
         if shared.mods.captures_give_points.is_some() {
-            shared.points[active_seat.team.0 as usize - 1] += captures as i32 * 2;
+            let own = captured_from
+                .get(active_seat.team.as_usize() - 1)
+                .copied()
+                .unwrap_or(0);
+            *shared.points.score_for_mut(active_seat.team) +=
+                (captures as u32 - own) as i32 * shared.mods.point_unit();
         }
 
-        (captures, revealed)
+        (captures, captured_from, captured, revealed)
     }
 
     /// Superko
@@ -213,18 +313,8 @@ impl PlayState {
             .rev()
             .take(self.capture_count + captures)
         {
-            if *old_hash == hash && old_board == &shared.board {
-                let BoardHistory {
-                    board: old_board,
-                    points: old_points,
-                    ..
-                } = shared
-                    .board_history
-                    .last()
-                    .expect("board_history.last() shouldn't be None")
-                    .clone();
-                shared.board = old_board;
-                shared.points = old_points;
+            if *old_hash == hash && old_board.same_position(&shared.board) {
+                restore_last_position(shared);
                 return Err(MakeActionError::Ko);
             }
         }
@@ -232,14 +322,109 @@ impl PlayState {
         Ok(())
     }
 
+    /// Rejects the move if the color to move had groups in atari and the move
+    /// neither gave one of them more liberties nor captured stones next to one,
+    /// which are what had it in atari.
+    fn check_defended(
+        &self,
+        shared: &mut SharedState,
+        captured: &[(Point, Color)],
+    ) -> MakeActionResult<()> {
+        if !shared.mods.must_defend_atari {
+            return Ok(());
+        }
+        let team = shared.get_active_seat().team;
+        let before = &shared
+            .board_history
+            .last()
+            .expect("board_history.last() shouldn't be None")
+            .board;
+        let in_atari = find_groups(before)
+            .into_iter()
+            .filter(|g| g.team == team && g.liberties == 1)
+            .collect::<Vec<_>>();
+        if in_atari.is_empty() {
+            return Ok(());
+        }
+
+        let captured_attacker = captured.iter().any(|&(point, color)| {
+            color != team
+                && before
+                    .surrounding_points(point)
+                    .any(|p| in_atari.iter().any(|g| g.points.contains(&p)))
+        });
+        let after = find_groups(&shared.board);
+        let defended = in_atari.iter().any(|group| {
+            after
+                .iter()
+                .any(|g| g.points.contains(&group.points[0]) && g.liberties > 1)
+        });
+        if captured_attacker || defended {
+            return Ok(());
+        }
+
+        restore_last_position(shared);
+        Err(MakeActionError::MustDefend)
+    }
+
+    /// With `GameModifier::confirm_self_atari`, rejects an unconfirmed move that
+    /// leaves its own group with a single liberty.
+    fn check_self_atari(
+        &self,
+        shared: &mut SharedState,
+        expected: Option<&MoveExpectation>,
+        point: Point,
+        captures: usize,
+    ) -> MakeActionResult<()> {
+        // Moves that capture are exempt, as retaking a ko always leaves one liberty.
+        // With hidden stones the check would give them away.
+        let hidden =
+            shared.mods.hidden_move.is_some() || shared.mods.phantom.is_some() || shared.mods.pixel;
+        let confirmed = expected.is_some_and(|e| e.confirm_self_atari);
+        if !shared.mods.confirm_self_atari || confirmed || captures > 0 || hidden {
+            return Ok(());
+        }
+        let self_atari = find_groups(&shared.board)
+            .iter()
+            .any(|g| g.liberties == 1 && g.points.contains(&point));
+        if self_atari {
+            restore_last_position(shared);
+            return Err(MakeActionError::SelfAtariNeedsConfirm);
+        }
+        Ok(())
+    }
+
+    /// Rejects the move if it didn't turn out like the client expected.
+    fn check_expected(
+        &self,
+        shared: &mut SharedState,
+        expected: Option<&MoveExpectation>,
+        captures: usize,
+        hash: u64,
+    ) -> MakeActionResult<()> {
+        let expected = match expected {
+            Some(e) => e,
+            None => return Ok(()),
+        };
+        let captures_match = expected.captures.is_none_or(|c| c == captures);
+        let hash_match = expected.hash.is_none_or(|h| h == hash);
+        if captures_match && hash_match {
+            return Ok(());
+        }
+
+        restore_last_position(shared);
+        Err(MakeActionError::UnexpectedResult)
+    }
+
     fn make_action_place(
         &mut self,
         shared: &mut SharedState,
-        (x, y): (u32, u32),
+        point: Point,
         color_placed: Color,
+        expected: Option<&MoveExpectation>,
     ) -> MakeActionResult {
         // TODO: should use some kind of set to make suicide prevention faster
-        let mut points_played = self.place_stone(shared, (x, y), color_placed)?;
+        let mut points_played = self.place_stone(shared, point, color_placed)?;
         if let Some(rule) = &shared.mods.tetris {
             // This is valid because points_played is empty if the move is illegal.
             use tetris::TetrisResult::*;
@@ -274,16 +459,11 @@ impl PlayState {
             }
         }
 
-        let (captures, revealed) = self.capture(shared, &mut points_played);
+        let (captures, captured_from, captured, revealed) =
+            self.capture(shared, &mut points_played);
 
         if points_played.is_empty() {
-            let BoardHistory { board, points, .. } = shared
-                .board_history
-                .last()
-                .expect("board_history.last() shouldn't be None")
-                .clone();
-            shared.board = board;
-            shared.points = points;
+            restore_last_position(shared);
 
             if revealed {
                 return Ok(ActionChange::None);
@@ -294,8 +474,11 @@ impl PlayState {
         let hash = shared.board.hash();
 
         self.superko(shared, captures, hash)?;
+        self.check_defended(shared, &captured)?;
+        self.check_self_atari(shared, expected, point, captures)?;
+        self.check_expected(shared, expected, captures, hash)?;
 
-        let new_turn = if let Some(rule) = &shared.mods.n_plus_one {
+        let mut new_turn = if let Some(rule) = &shared.mods.n_plus_one {
             use n_plus_one::NPlusOneResult::*;
             match n_plus_one::check(
                 &points_played,
@@ -310,24 +493,75 @@ impl PlayState {
             false
         };
 
+        // Free handicap stones are all placed on the same turn.
+        let team = shared.get_active_seat().team;
+        if let Some(left) = self.handicap_left.get_mut(team.as_usize() - 1) {
+            if *left > 0 {
+                *left -= 1;
+                new_turn = new_turn || *left > 0;
+            }
+        }
+
+        self.captures.add(team, &captured_from);
         self.last_stone = Some(points_played);
+        let eliminated = eliminate_captured(shared, &captured_from);
 
         // TODO: Handle this at the view layer instead to have the marker visible for your own stones.
         if shared.mods.phantom.is_some() {
             self.last_stone = None;
         }
 
-        for passed in &mut self.players_passed {
-            *passed = false;
+        for (seat, passed) in shared.seats.iter().zip(&mut self.players_passed) {
+            if !shared.mods.pass_majority_ends || seat.team == team {
+                *passed = false;
+            }
         }
 
+        self.no_progress = no_progress(shared);
         self.next_turn(shared, new_turn);
         self.capture_count += captures;
 
+        if eliminated && one_color_left(shared) {
+            let state = ScoringState::new(
+                &shared.board,
+                &shared.seats,
+                &shared.points,
+                &shared.mods,
+                shared.turn,
+            );
+            return Ok(ActionChange::PushState(
+                state.finish(shared, ResultKind::Resignation),
+            ));
+        }
+
+        let captured_opponent = captured_from
+            .iter()
+            .enumerate()
+            .any(|(idx, &count)| count > 0 && idx != team.as_usize() - 1);
+        if shared.mods.atari_go.is_some() && captured_opponent && !counts_captures(shared) {
+            let state = ScoringState::new(
+                &shared.board,
+                &shared.seats,
+                &shared.points,
+                &shared.mods,
+                shared.turn,
+            );
+            return Ok(ActionChange::PushState(
+                state.finish_with_winner(ResultKind::FirstCapture, team),
+            ));
+        }
+
         Ok(ActionChange::None)
     }
 
     fn make_action_pass(&mut self, shared: &mut SharedState) -> MakeActionResult {
+        if let Some(AtariGo {
+            pass_loses: true, ..
+        }) = shared.mods.atari_go
+        {
+            return self.make_action_forfeit(shared);
+        }
+
         let active_seat = shared.get_active_seat();
 
         for (seat, passed) in shared.seats.iter().zip(self.players_passed.iter_mut()) {
@@ -338,25 +572,79 @@ impl PlayState {
 
         self.next_turn(shared, false);
 
-        if shared
-            .seats
-            .iter()
-            .zip(&self.players_passed)
-            .all(|(s, &pass)| s.resigned || pass)
-        {
+        if self.passing_ends_play(shared) {
             for passed in &mut self.players_passed {
                 *passed = false;
             }
+            if counts_captures(shared) {
+                return Ok(self.end_by_captures(shared));
+            }
+            if let Some(AtariGo {
+                pass_ends: true, ..
+            }) = shared.mods.atari_go
+            {
+                let mut state = ScoringState::new(
+                    &shared.board,
+                    &shared.seats,
+                    &shared.points,
+                    &shared.mods,
+                    shared.turn,
+                );
+                state.result = Some(GameResult::agreed());
+                return Ok(ActionChange::PushState(GameState::Done(state)));
+            }
+            if shared.mods.tromp_taylor {
+                let mods = GameModifier {
+                    auto_dead_stones: false,
+                    ..shared.mods.clone()
+                };
+                let state = ScoringState::new(
+                    &shared.board,
+                    &shared.seats,
+                    &shared.points,
+                    &mods,
+                    shared.turn,
+                );
+                return Ok(ActionChange::PushState(
+                    state.finish(shared, ResultKind::Counted),
+                ));
+            }
             return Ok(ActionChange::PushState(GameState::scoring(
                 &shared.board,
                 &shared.seats,
                 &shared.points,
+                &shared.mods,
+                shared.turn,
             )));
         }
 
         Ok(ActionChange::None)
     }
 
+    /// Every playing seat has passed since the last placement, or with
+    /// `GameModifier::pass_majority_ends` more than half of the playing colors have
+    /// a pass standing.
+    fn passing_ends_play(&self, shared: &SharedState) -> bool {
+        let mut colors = GroupVec::<Color>::new();
+        for seat in shared.seats.iter().filter(|s| !s.resigned) {
+            if !colors.contains(&seat.team) {
+                colors.push(seat.team);
+            }
+        }
+        let passed = |color: &Color| {
+            shared
+                .seats
+                .iter()
+                .zip(&self.players_passed)
+                .all(|(s, &pass)| s.team != *color || s.resigned || pass)
+        };
+        if shared.mods.pass_majority_ends && colors.len() >= 3 {
+            colors.iter().filter(|c| passed(c)).count() * 2 > colors.len()
+        } else {
+            colors.iter().all(passed)
+        }
+    }
+
     fn make_action_cancel(&mut self, shared: &mut SharedState) -> MakeActionResult {
         // Undo a turn
         if shared.board_history.len() < 2 {
@@ -366,6 +654,8 @@ impl PlayState {
         self.rollback_turn(shared, true)
     }
 
+    /// Goes back to the previous entry of `board_history`, restoring its board with
+    /// the captured stones. Ko is read from the history, so it goes back too.
     fn rollback_turn(
         &mut self,
         shared: &mut SharedState,
@@ -400,13 +690,31 @@ impl PlayState {
             .expect("Game turn number invalid");
 
         active_seat.resigned = true;
+        let team = active_seat.team;
 
         if shared.seats.iter().filter(|s| !s.resigned).count() <= 1 {
-            return Ok(ActionChange::PushState(GameState::Done(ScoringState::new(
+            let state = ScoringState::new(
                 &shared.board,
                 &shared.seats,
                 &shared.points,
-            ))));
+                &shared.mods,
+                shared.turn,
+            );
+            return Ok(ActionChange::PushState(
+                state.finish(shared, ResultKind::Resignation),
+            ));
+        }
+
+        let eliminated = shared.seats.iter().all(|s| s.team != team || s.resigned);
+        if eliminated && shared.mods.on_elimination == Some(EliminationPolicy::Remove) {
+            for point in &mut shared.board.points {
+                if *point == team {
+                    *point = Color::empty();
+                }
+            }
+            // Record the cleared board like a move, so ko and history see it.
+            self.next_turn(shared, false);
+            return Ok(ActionChange::None);
         }
 
         loop {
@@ -422,22 +730,78 @@ impl PlayState {
         Ok(ActionChange::None)
     }
 
+    /// Passing in atari go with `pass_loses` resigns every seat of the color.
+    fn make_action_forfeit(&mut self, shared: &mut SharedState) -> MakeActionResult {
+        let team = shared.get_active_seat().team;
+        for seat in shared.seats.iter_mut().filter(|s| s.team == team) {
+            seat.resigned = true;
+        }
+
+        let mut teams_left = shared.seats.iter().filter(|s| !s.resigned).map(|s| s.team);
+        let first = teams_left.next();
+        if teams_left.all(|t| Some(t) == first) {
+            let state = ScoringState::new(
+                &shared.board,
+                &shared.seats,
+                &shared.points,
+                &shared.mods,
+                shared.turn,
+            );
+            return Ok(ActionChange::PushState(
+                state.finish(shared, ResultKind::PassForfeit),
+            ));
+        }
+
+        self.next_turn(shared, false);
+
+        Ok(ActionChange::None)
+    }
+
     pub fn make_action(
         &mut self,
         shared: &mut SharedState,
         player_id: u64,
         action: ActionKind,
+    ) -> MakeActionResult {
+        self.make_action_expecting(shared, player_id, action, None)
+    }
+
+    /// Like `make_action`, but a placement fails with `UnexpectedResult` if it
+    /// doesn't match `expected`, and `expected` can confirm a self-atari.
+    pub fn make_action_expecting(
+        &mut self,
+        shared: &mut SharedState,
+        player_id: u64,
+        action: ActionKind,
+        expected: Option<&MoveExpectation>,
     ) -> MakeActionResult {
         let active_seat = shared.get_active_seat();
-        if active_seat.player != Some(player_id) {
+        let teaching_undo = shared.mods.teaching_mode && action == ActionKind::Cancel;
+        if active_seat.player != Some(player_id) && !teaching_undo {
             return Err(MakeActionError::NotTurn);
         }
+        if shared.max_moves_reached() && matches!(action, ActionKind::Place(..) | ActionKind::Pass)
+        {
+            return Err(MakeActionError::WrongPhase);
+        }
 
         let res = match action {
             ActionKind::Place(x, y) => {
                 let depth = shared.board_history.len();
 
-                let res = self.make_action_place(shared, (x, y), active_seat.team);
+                // With traitor stones the move is replayed below, so only check the final result.
+                let confirmed = MoveExpectation::confirmed();
+                let first_expected = if shared.traitor.is_some() {
+                    Some(&confirmed)
+                } else {
+                    expected
+                };
+                let res = self.make_action_place(
+                    shared,
+                    Point::new(x, y),
+                    active_seat.team,
+                    first_expected,
+                );
 
                 if res.is_ok() && shared.board_history.len() > depth && shared.traitor.is_some() {
                     // Depth increased -> the move is legal.
@@ -452,7 +816,8 @@ impl PlayState {
                         unreachable!();
                     };
 
-                    let res = self.make_action_place(shared, (x, y), color_placed);
+                    let res =
+                        self.make_action_place(shared, Point::new(x, y), color_placed, expected);
 
                     if res.is_err() {
                         shared.traitor = traitor;
@@ -465,15 +830,91 @@ impl PlayState {
             ActionKind::Pass => self.make_action_pass(shared),
             ActionKind::Cancel => self.make_action_cancel(shared),
             ActionKind::Resign => self.make_action_resign(shared),
+            ActionKind::AcceptScore
+            | ActionKind::RejectScore(_)
+            | ActionKind::ResumePlay
+            | ActionKind::ProposeKomi(_)
+            | ActionKind::ChooseColor(_) => {
+                return Err(MakeActionError::Illegal);
+            }
         };
 
         let res = res?;
 
         self.set_zen_teams(shared);
 
+        if let ActionChange::None = res {
+            if let Some(change) = self.end_at_max_moves(shared) {
+                return Ok(change);
+            }
+            if let Some(change) = self.auto_pass(shared) {
+                return change;
+            }
+        }
+
         Ok(res)
     }
 
+    /// Points where the seat to move could place a stone right now, found by
+    /// trying each empty point on a copy of the game.
+    pub fn legal_moves(&self, shared: &SharedState) -> Vec<Point> {
+        self.legal_points(shared).collect()
+    }
+
+    /// Like `legal_moves`, but stops trying at the first legal point.
+    pub fn has_legal_move(&self, shared: &SharedState) -> bool {
+        self.legal_points(shared).next().is_some()
+    }
+
+    fn legal_points<'a>(&'a self, shared: &'a SharedState) -> impl Iterator<Item = Point> + 'a {
+        let team = shared.get_active_seat().team;
+        // Pixel coordinates are shifted by one, see `place_stone`.
+        let extra = shared.mods.pixel as u32;
+        let width = shared.board.width() + extra;
+        // One copy of the game is shared by every trial placement. Nobody should
+        // hear about moves that aren't played.
+        let mut probe = shared.clone();
+        probe.event_sink = None;
+        let depth = probe.board_history.len();
+        // A self-atari is legal, it only needs confirming.
+        let confirmed = MoveExpectation::confirmed();
+        (0..shared.board.height() + extra)
+            .flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+            .filter(move |&point| extra == 1 || shared.board.get_point(point).is_empty())
+            .filter(move |&point| {
+                let mut state = self.clone();
+                let placed = state.make_action_place(&mut probe, point, team, Some(&confirmed));
+                let legal = placed.is_ok() && probe.board_history.len() > depth;
+
+                // Undo everything a placement can change for the next trial.
+                probe.board_history.truncate(depth);
+                probe.board.clone_from(&shared.board);
+                probe.board_visibility.clone_from(&shared.board_visibility);
+                probe.points.clone_from(&shared.points);
+                probe.seats.clone_from(&shared.seats);
+                probe.turn = shared.turn;
+                legal
+            })
+    }
+
+    /// Passes for every seat in a row that has no legal moves, see
+    /// `GameModifier::auto_pass_when_stuck`. Stops once the passes end play.
+    fn auto_pass(&mut self, shared: &mut SharedState) -> Option<MakeActionResult> {
+        if !shared.mods.auto_pass_when_stuck {
+            return None;
+        }
+        for _ in 0..shared.seats.len() {
+            if self.has_legal_move(shared) {
+                return None;
+            }
+            match self.make_action_pass(shared) {
+                Ok(ActionChange::None) => self.set_zen_teams(shared),
+                change => return Some(change),
+            }
+        }
+        None
+    }
+
     fn next_turn(&mut self, shared: &mut SharedState, new_turn: bool) {
         if !new_turn {
             loop {
@@ -498,6 +939,53 @@ impl PlayState {
         });
     }
 
+    /// Ends play once `GameModifier::max_moves` moves have been made.
+    fn end_at_max_moves(&self, shared: &SharedState) -> Option<ActionChange> {
+        if !shared.max_moves_reached() {
+            return None;
+        }
+        if counts_captures(shared) {
+            return Some(self.end_by_captures(shared));
+        }
+        let mut state = ScoringState::new(
+            &shared.board,
+            &shared.seats,
+            &shared.points,
+            &shared.mods,
+            shared.turn,
+        );
+        if shared.mods.max_moves_void {
+            state.result = Some(GameResult::void());
+            return Some(ActionChange::PushState(GameState::Done(state)));
+        }
+        Some(ActionChange::PushState(GameState::Scoring(state)))
+    }
+
+    /// Ends the game for `AtariGo::capture_count`. Stones a color lost to its own
+    /// moves don't count for it, equal captures are a draw.
+    fn end_by_captures(&self, shared: &SharedState) -> ActionChange {
+        let unit = shared.mods.point_unit();
+        let captured = self.captures.of_opponents(shared.komis.len());
+        let captures = captured
+            .iter()
+            .map(|&c| c as i32 * unit)
+            .collect::<GroupVec<i32>>();
+        let mut state = ScoringState::new(
+            &shared.board,
+            &shared.seats,
+            &shared.points,
+            &shared.mods,
+            shared.turn,
+        );
+        state.result = Some(GameResult::new(
+            ResultKind::MostCaptures,
+            &captures,
+            &captured,
+            &shared.seats,
+        ));
+        ActionChange::PushState(GameState::Done(state))
+    }
+
     fn set_zen_teams(&mut self, shared: &mut SharedState) {
         let move_number = shared.board_history.len() - 1;
         if let Some(zen) = &shared.mods.zen_go {
@@ -508,7 +996,7 @@ impl PlayState {
     }
 }
 
-pub(self) fn reveal_group(
+fn reveal_group(
     visibility: Option<&mut VisibilityBoard>,
     group: &Group,
     board: &Board,
@@ -528,3 +1016,84 @@ pub(self) fn reveal_group(
 
     revealed
 }
+
+/// Undoes the changes of a rejected move to the board and points.
+fn restore_last_position(shared: &mut SharedState) {
+    let BoardHistory { board, points, .. } = shared
+        .board_history
+        .last()
+        .expect("board_history.last() shouldn't be None")
+        .clone();
+    shared.board = board;
+    shared.points = points;
+}
+
+/// With `GameModifier::on_elimination`, resigns every seat of a color that lost
+/// its last stones to this move. Returns whether any color was eliminated.
+fn eliminate_captured(shared: &mut SharedState, captured_from: &[u32]) -> bool {
+    if shared.mods.on_elimination.is_none() {
+        return false;
+    }
+    let mut eliminated = false;
+    for (idx, &count) in captured_from.iter().enumerate() {
+        let color = Color(idx as u8 + 1);
+        if count == 0 || shared.board.points.contains(&color) {
+            continue;
+        }
+        for seat in shared.seats.iter_mut().filter(|s| s.team == color) {
+            eliminated |= !seat.resigned;
+            seat.resigned = true;
+        }
+    }
+    eliminated
+}
+
+fn one_color_left(shared: &SharedState) -> bool {
+    let mut teams = shared.seats.iter().filter(|s| !s.resigned).map(|s| s.team);
+    let first = teams.next();
+    teams.all(|t| Some(t) == first)
+}
+
+/// Compares the board to the position `NoProgress::moves` moves ago.
+fn no_progress(shared: &SharedState) -> bool {
+    let rule = match &shared.mods.no_progress {
+        Some(rule) => rule,
+        None => return false,
+    };
+    // The last history entry is the position before this move.
+    let history = &shared.board_history;
+    let moves = rule.moves.max(1) as usize;
+    if history.len() < moves {
+        return false;
+    }
+    let before = &history[history.len() - moves].board;
+
+    progress_marker(before) == progress_marker(&shared.board)
+}
+
+/// Stones and estimated area of each color, counting every group as alive.
+fn progress_marker(board: &Board) -> (Vec<usize>, Vec<usize>) {
+    let count = |board: &Board| {
+        let mut counts = Vec::new();
+        for color in board.points.iter().filter(|c| !c.is_empty()) {
+            if counts.len() < color.as_usize() {
+                counts.resize(color.as_usize(), 0);
+            }
+            counts[color.as_usize() - 1] += 1;
+        }
+        counts
+    };
+    let area = score_board(board, &find_groups(board));
+    (count(board), count(&area))
+}
+
+/// See `AtariGo::capture_count`.
+fn counts_captures(shared: &SharedState) -> bool {
+    matches!(
+        shared.mods.atari_go,
+        Some(AtariGo {
+            capture_count: true,
+            ..
+        })
+    )
+}
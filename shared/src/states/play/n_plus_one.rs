@@ -35,16 +35,16 @@ pub fn check(
 
         // Vertical ///////////////////////////////////////////////////////////
 
-        let mut y = point_played.1 as i32 - 1;
-        while let Some(p) = board.wrap_point(point_played.0 as i32, y) {
+        let mut y = point_played.y as i32 - 1;
+        while let Some(p) = board.wrap_point(point_played.x as i32, y) {
             if add_point(&mut line_points, p) {
                 break;
             }
             y -= 1;
         }
 
-        let mut y = point_played.1 as i32;
-        while let Some(p) = board.wrap_point(point_played.0 as i32, y) {
+        let mut y = point_played.y as i32;
+        while let Some(p) = board.wrap_point(point_played.x as i32, y) {
             if add_point(&mut line_points, p) {
                 break;
             }
@@ -65,16 +65,16 @@ pub fn check(
 
         // Horizontal /////////////////////////////////////////////////////////
 
-        let mut x = point_played.0 as i32 - 1;
-        while let Some(p) = board.wrap_point(x, point_played.1 as i32) {
+        let mut x = point_played.x as i32 - 1;
+        while let Some(p) = board.wrap_point(x, point_played.y as i32) {
             if add_point(&mut line_points, p) {
                 break;
             }
             x -= 1;
         }
 
-        let mut x = point_played.0 as i32;
-        while let Some(p) = board.wrap_point(x, point_played.1 as i32) {
+        let mut x = point_played.x as i32;
+        while let Some(p) = board.wrap_point(x, point_played.y as i32) {
             if add_point(&mut line_points, p) {
                 break;
             }
@@ -95,7 +95,7 @@ pub fn check(
 
         // Diagonal top left - bottom right ///////////////////////////////////
 
-        let mut point = (point_played.0 as i32 - 1, point_played.1 as i32 - 1);
+        let mut point = (point_played.x as i32 - 1, point_played.y as i32 - 1);
         while let Some(p) = board.wrap_point(point.0, point.1) {
             if add_point(&mut line_points, p) {
                 break;
@@ -105,7 +105,7 @@ pub fn check(
             point.1 -= 1;
         }
 
-        let mut point = (point_played.0 as i32, point_played.1 as i32);
+        let mut point = (point_played.x as i32, point_played.y as i32);
         while let Some(p) = board.wrap_point(point.0, point.1) {
             if add_point(&mut line_points, p) {
                 break;
@@ -129,7 +129,7 @@ pub fn check(
 
         // Diagonal bottom left - top right ///////////////////////////////////
 
-        let mut point = (point_played.0 as i32 - 1, point_played.1 as i32 + 1);
+        let mut point = (point_played.x as i32 - 1, point_played.y as i32 + 1);
         while let Some(p) = board.wrap_point(point.0, point.1) {
             if add_point(&mut line_points, p) {
                 break;
@@ -139,7 +139,7 @@ pub fn check(
             point.1 += 1;
         }
 
-        let mut point = (point_played.0 as i32, point_played.1 as i32);
+        let mut point = (point_played.x as i32, point_played.y as i32);
         while let Some(p) = board.wrap_point(point.0, point.1) {
             if add_point(&mut line_points, p) {
                 break;
@@ -1,17 +1,18 @@
 use rand::prelude::*;
 use rand_pcg::Lcg64Xsh32;
+use serde::{Deserialize, Serialize};
 
 use crate::game::Color;
 use crate::game::GroupVec;
 use crate::game::TraitorGo;
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 struct TeamState {
     traitor_count: u32,
     stone_count: u32,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TraitorState {
     /// Remaining traitors for each team
     team_states: GroupVec<TeamState>,
@@ -47,7 +48,7 @@ impl TraitorState {
                 .choose(&mut self.rng_state)
                 .expect("Empty color choices in TraitorState::next_color");
 
-            Color(color as u8)
+            Color(color)
         } else {
             team_color
         }
@@ -1,43 +1,383 @@
 use crate::game::{
-    find_groups, ActionChange, ActionKind, Board, Color, GameState, Group, GroupVec,
-    MakeActionResult, Point, Seat, SharedState,
+    find_groups, ActionChange, ActionKind, Board, Color, ColorScores, DamePolicy,
+    EliminationPolicy, GameModifier, GameState, Group, GroupId, GroupVec, MakeActionError,
+    MakeActionResult, Point, Seat, SekiTerritory, SharedState, TeamAcceptPolicy,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{HashSet, VecDeque};
 
+/// How a finished game was decided.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ResultKind {
+    /// Every player accepted the count.
+    Counted,
+    /// The count was accepted automatically because scoring timed out.
+    ScoringTimeout,
+    /// Every other color resigned.
+    Resignation,
+    /// The winner captured first in atari go.
+    FirstCapture,
+    /// Every other color passed in atari go, where passing loses.
+    PassForfeit,
+    /// Play hit `GameModifier::max_moves` with `max_moves_void`, nobody wins.
+    Void,
+    /// Everyone passed and the rules end the game there without a count, see
+    /// `AtariGo::pass_ends`. Nobody wins.
+    Agreement,
+    /// Play was abandoned and the result is only an estimate, see `estimate_result`.
+    Provisional,
+    /// The color with the most captures won, see `AtariGo::capture_count`.
+    MostCaptures,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub kind: ResultKind,
+    /// `None` for a draw.
+    pub winner: Option<Color>,
+    /// Lead of the winner over the next best color in half points.
+    pub margin: i32,
+    /// Colors from best to worst, see `GameResult::new` for how ties are ranked.
+    /// Only the winner when the rules decided the game instead of the count.
+    #[serde(default)]
+    pub standings: Vec<Color>,
+}
+
+impl GameResult {
+    /// See `ResultKind::Void`.
+    pub fn void() -> Self {
+        GameResult {
+            kind: ResultKind::Void,
+            winner: None,
+            margin: 0,
+            standings: Vec::new(),
+        }
+    }
+
+    /// See `ResultKind::Agreement`.
+    pub fn agreed() -> Self {
+        GameResult {
+            kind: ResultKind::Agreement,
+            ..GameResult::void()
+        }
+    }
+
+    /// Decides the winner from final `scores`. Colors that have resigned every seat can't win.
+    ///
+    /// `captures` are the stones each color captured from the others, see
+    /// `captured_stones`. Standings are ranked by, in order:
+    /// 1. Colors that resigned every seat come last.
+    /// 2. Higher score first.
+    /// 3. More captures first.
+    /// 4. Seat order, the color with the lowest seat index goes first.
+    ///
+    /// Tied colors are still a draw for the winner, the order is only for display.
+    pub fn new(kind: ResultKind, scores: &[i32], captures: &[u32], seats: &[Seat]) -> Self {
+        let first_seat = |color: Color| seats.iter().position(|s| s.team == color);
+        let mut ranked = scores
+            .iter()
+            .enumerate()
+            .map(|(idx, &score)| (Color(idx as u8 + 1), score))
+            .map(|(color, score)| {
+                let playing = seats.iter().any(|s| s.team == color && !s.resigned);
+                (color, score, playing)
+            })
+            .collect::<GroupVec<_>>();
+        ranked.sort_by_key(|&(color, score, playing)| {
+            (
+                !playing,
+                std::cmp::Reverse(score),
+                std::cmp::Reverse(captures.get(color.as_usize() - 1).copied().unwrap_or(0)),
+                first_seat(color).unwrap_or(usize::MAX),
+            )
+        });
+        let standings = ranked.iter().map(|&(color, _, _)| color).collect();
+        let playing = ranked
+            .iter()
+            .filter(|&&(_, _, playing)| playing)
+            .copied()
+            .collect::<GroupVec<_>>();
+
+        let (winner, margin) = match &playing[..] {
+            [] => (None, 0),
+            [(color, _, _)] => (Some(*color), 0),
+            [(color, first, _), (_, second, _), ..] => (
+                if first > second { Some(*color) } else { None },
+                first - second,
+            ),
+        };
+        GameResult {
+            kind,
+            winner,
+            margin,
+            standings,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreRejection {
+    pub player_id: u64,
+    pub reason: Option<String>,
+}
+
+/// What the last scoring action did, for clients that describe the game in text or audio.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ScoringEvent {
+    GroupMarkedDead {
+        group: GroupId,
+        color: Color,
+        stones: usize,
+    },
+    GroupRevived {
+        group: GroupId,
+        color: Color,
+        stones: usize,
+    },
+    /// `leader` is `None` for a tie.
+    ScoreChanged {
+        leader: Option<Color>,
+        margin: i32,
+    },
+    PlayerAccepted {
+        player_id: u64,
+    },
+    /// The player's accept was held back, see `ConfirmDeadStones`. Accepting again goes through.
+    LargeSwingWarning {
+        player_id: u64,
+        dead_stones: usize,
+    },
+}
+
+/// Rejection reasons are shown to everyone, so keep them short.
+const MAX_REJECTION_REASON: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScoringState {
     pub groups: Vec<Group>,
-    /// Vector of the board, marking who owns a point
-    pub points: Board,
+    /// The living stones, dead ones are left out.
+    pub stones: Board,
+    /// Empty points owned by a single color. Stones are never marked here.
+    pub territory: Board,
+    /// Final score of each color in half points, including komi adjustments.
     pub scores: GroupVec<i32>,
     // TODO: use smallvec?
     pub players_accepted: Vec<bool>,
+    /// Neutral points left on the board, scored according to `DamePolicy`.
+    #[serde(default)]
+    pub dame: u32,
+    /// Territory in regions with a false eye. Players should be warned before accepting.
+    #[serde(default)]
+    pub disputed: Vec<Point>,
+    /// Colors that have stones on the board but every one of them is marked dead.
+    /// Usually a misclick, so the UI should ask before accepting.
+    #[serde(default)]
+    pub all_dead: Vec<Color>,
+    /// Set once the game is over.
+    #[serde(default)]
+    pub result: Option<GameResult>,
+    /// The last rejection of the score, cleared when someone accepts.
+    #[serde(default)]
+    pub rejection: Option<ScoreRejection>,
+    /// Events from the last action.
+    #[serde(default)]
+    pub events: Vec<ScoringEvent>,
+    /// Seats that want to resume play to settle the dead stones on the board.
+    #[serde(default)]
+    pub resume_requested: Vec<bool>,
+    /// Players warned about their dead stones since the groups last changed.
+    #[serde(default)]
+    pub warned: Vec<u64>,
+    /// Speeds up rescoring when toggling groups. Not sent to clients.
+    #[serde(skip)]
+    pub region_cache: Option<RegionCache>,
+}
+
+/// Compares everything but `region_cache`, which isn't serialized and can be rebuilt.
+impl PartialEq for ScoringState {
+    fn eq(&self, other: &Self) -> bool {
+        let ScoringState {
+            groups,
+            stones,
+            territory,
+            scores,
+            players_accepted,
+            dame,
+            disputed,
+            all_dead,
+            result,
+            rejection,
+            events,
+            resume_requested,
+            warned,
+            region_cache: _,
+        } = self;
+        *groups == other.groups
+            && *stones == other.stones
+            && *territory == other.territory
+            && *scores == other.scores
+            && *players_accepted == other.players_accepted
+            && *dame == other.dame
+            && *disputed == other.disputed
+            && *all_dead == other.all_dead
+            && *result == other.result
+            && *rejection == other.rejection
+            && *events == other.events
+            && *resume_requested == other.resume_requested
+            && *warned == other.warned
+    }
 }
 
 impl ScoringState {
-    pub fn new(board: &Board, seats: &[Seat], scores: &[i32]) -> Self {
-        let groups = find_groups(board);
-        let points = score_board(board, &groups);
-        let mut scores: GroupVec<i32> = scores.into();
-        for color in &points.points {
-            if !color.is_empty() {
-                scores[color.0 as usize - 1] += 2;
-            }
+    /// `scores` are the points each color has before counting the board,
+    /// eg. komi and captures.
+    /// `turn` is the seat to move, it decides who fills dame first.
+    pub fn new(
+        board: &Board,
+        seats: &[Seat],
+        scores: &[i32],
+        mods: &GameModifier,
+        turn: usize,
+    ) -> Self {
+        let mut groups = find_groups(board);
+        if mods.auto_dead_stones {
+            mark_obviously_dead(board, &mut groups);
         }
+        let region_cache = RegionCache::new(board, &groups);
+        let disputed = region_cache.disputed();
+        let scores = region_cache.count(
+            &scored_area(&region_cache, seats, mods),
+            scores,
+            mods,
+            &fill_order(seats, turn),
+        );
+        let dame = region_cache.dame();
         ScoringState {
             groups,
-            points,
+            stones: region_cache.stones(),
+            territory: region_cache.territory(),
             scores,
+            dame,
             players_accepted: seats.iter().map(|s| s.resigned).collect(),
+            disputed,
+            all_dead: Vec::new(),
+            result: None,
+            rejection: None,
+            events: Vec::new(),
+            resume_requested: seats.iter().map(|s| s.resigned).collect(),
+            warned: Vec::new(),
+            region_cache: Some(region_cache),
         }
     }
 
+    /// Outlines of the territories, see `RegionCache::outlines`. Clients don't get
+    /// the cache, so it's rebuilt from the living stones there.
+    pub fn outlines(&self) -> Vec<TerritoryOutline> {
+        match &self.region_cache {
+            Some(cache) => cache.outlines(),
+            None => RegionCache::new(&self.stones, &find_groups(&self.stones)).outlines(),
+        }
+    }
+
+    /// See `dame_parity`.
+    pub fn dame_parity(&self, shared: &SharedState) -> Option<Color> {
+        dame_parity(self.dame, &shared.seats, shared.turn)
+    }
+
+    pub fn groups_of(&self, color: Color) -> impl Iterator<Item = &Group> {
+        self.groups.iter().filter(move |g| g.team == color)
+    }
+
+    /// Ends the game with the current count.
+    pub fn finish(&self, shared: &SharedState, kind: ResultKind) -> GameState {
+        let kind = match kind {
+            // Resigning during scoring only wins outright if a single color is left.
+            ResultKind::Resignation => {
+                let mut teams = shared.seats.iter().filter(|s| !s.resigned).map(|s| s.team);
+                let first = teams.next();
+                if teams.all(|t| Some(t) == first) {
+                    ResultKind::Resignation
+                } else {
+                    ResultKind::Counted
+                }
+            }
+            kind => kind,
+        };
+        let mut state = self.clone();
+        state.result = Some(GameResult::new(
+            kind,
+            &self.scores,
+            &captured_stones(shared),
+            &shared.seats,
+        ));
+        GameState::Done(state)
+    }
+
+    /// Ends the game with a winner decided by the rules rather than the count.
+    pub fn finish_with_winner(&self, kind: ResultKind, winner: Color) -> GameState {
+        let mut state = self.clone();
+        state.result = Some(GameResult {
+            kind,
+            winner: Some(winner),
+            margin: 0,
+            standings: vec![winner],
+        });
+        GameState::Done(state)
+    }
+
+    /// Recounts the marked board as if the game had been played with `komis`,
+    /// eg. to fix a game that was set up wrong. The state is left untouched,
+    /// see `commit_komi`.
+    pub fn rescore_with_komi(&self, shared: &SharedState, komis: &[i32]) -> GroupVec<i32> {
+        let rebuilt;
+        let cache = match &self.region_cache {
+            Some(cache) => cache,
+            None => {
+                rebuilt = RegionCache::new(&shared.board, &self.groups);
+                &rebuilt
+            }
+        };
+        cache.count(
+            &scored_area(cache, &shared.seats, &shared.mods),
+            &rebase_komi(shared, komis),
+            &shared.mods,
+            &fill_order(&shared.seats, shared.turn),
+        )
+    }
+
+    /// Replaces the komi of the game and updates the scores and result to match.
+    pub fn commit_komi(&mut self, shared: &mut SharedState, komis: &[i32]) {
+        self.scores = self.rescore_with_komi(shared, komis);
+        shared.points = rebase_komi(shared, komis);
+        shared.komis = komis.into();
+        if let Some(result) = &self.result {
+            self.result = Some(GameResult::new(
+                result.kind,
+                &self.scores,
+                &captured_stones(shared),
+                &shared.seats,
+            ));
+        }
+    }
+
+    /// Scoring ran out of time: every player accepts the current count.
+    pub fn timeout(&mut self, shared: &mut SharedState) -> MakeActionResult {
+        for accepted in &mut self.players_accepted {
+            *accepted = true;
+        }
+        Ok(ActionChange::SwapState(
+            self.finish(shared, ResultKind::ScoringTimeout),
+        ))
+    }
+
     pub fn make_action_place(
         &mut self,
         shared: &mut SharedState,
         point: Point,
     ) -> MakeActionResult {
+        if !shared.board.point_within(point) {
+            return Err(MakeActionError::OutOfBounds);
+        }
         let group = self.groups.iter_mut().find(|g| g.points.contains(&point));
 
         let group = match group {
@@ -46,14 +386,53 @@ impl ScoringState {
         };
 
         group.alive = !group.alive;
+        self.warned.clear();
+        let (id, color, stones) = (group.id(), group.team, group.points.len());
+        self.events.push(if group.alive {
+            ScoringEvent::GroupRevived {
+                group: id,
+                color,
+                stones,
+            }
+        } else {
+            ScoringEvent::GroupMarkedDead {
+                group: id,
+                color,
+                stones,
+            }
+        });
 
-        self.points = score_board(&shared.board, &self.groups);
-        self.scores = shared.points.clone();
-        for color in &self.points.points {
-            if !color.is_empty() {
-                self.scores[color.0 as usize - 1] += 2;
+        let cache = match &mut self.region_cache {
+            Some(cache) => {
+                cache.toggle(group);
+                cache
             }
-        }
+            None => self
+                .region_cache
+                .insert(RegionCache::new(&shared.board, &self.groups)),
+        };
+        self.stones = cache.stones();
+        self.territory = cache.territory();
+        self.disputed = cache.disputed();
+        self.scores = cache.count(
+            &scored_area(cache, &shared.seats, &shared.mods),
+            &shared.points,
+            &shared.mods,
+            &fill_order(&shared.seats, shared.turn),
+        );
+        self.dame = cache.dame();
+        self.all_dead = all_dead_colors(&self.groups);
+
+        let standing = GameResult::new(
+            ResultKind::Counted,
+            &self.scores,
+            &captured_stones(shared),
+            &shared.seats,
+        );
+        self.events.push(ScoringEvent::ScoreChanged {
+            leader: standing.winner,
+            margin: standing.margin,
+        });
 
         for (idx, accept) in self.players_accepted.iter_mut().enumerate() {
             *accept = shared.seats[idx].resigned;
@@ -67,38 +446,118 @@ impl ScoringState {
         shared: &mut SharedState,
         player_id: u64,
     ) -> MakeActionResult {
+        self.rejection = None;
+
         // A single player can hold multiple seats so we have to mark every seat they hold
-        let seats = shared
+        let teams = shared
             .seats
             .iter()
-            .enumerate()
-            .filter(|x| x.1.player == Some(player_id));
+            .filter(|s| s.player == Some(player_id))
+            .map(|s| s.team)
+            .collect::<GroupVec<_>>();
 
-        for (seat_idx, _) in seats {
-            self.players_accepted[seat_idx] = true;
+        if let Some(confirm) = &shared.mods.confirm_dead_stones {
+            let dead_stones = self
+                .groups
+                .iter()
+                .filter(|g| !g.alive && teams.contains(&g.team))
+                .map(|g| g.points.len())
+                .sum::<usize>();
+            if dead_stones > confirm.stones as usize && !self.warned.contains(&player_id) {
+                self.warned.push(player_id);
+                self.events.push(ScoringEvent::LargeSwingWarning {
+                    player_id,
+                    dead_stones,
+                });
+                return Ok(ActionChange::None);
+            }
+        }
+
+        for (seat_idx, seat) in shared.seats.iter().enumerate() {
+            let accepts = match shared.mods.team_accept_policy {
+                TeamAcceptPolicy::AllMembers => seat.player == Some(player_id),
+                TeamAcceptPolicy::AnyMember => teams.contains(&seat.team),
+            };
+            if accepts {
+                self.players_accepted[seat_idx] = true;
+            }
         }
-        if self.players_accepted.iter().all(|x| *x) {
-            Ok(ActionChange::SwapState(GameState::Done(self.clone())))
+        self.events.push(ScoringEvent::PlayerAccepted { player_id });
+        if self.all_accepted(shared) {
+            Ok(ActionChange::SwapState(
+                self.finish(shared, ResultKind::Counted),
+            ))
+        } else {
+            Ok(ActionChange::None)
+        }
+    }
+
+    fn make_action_reject(
+        &mut self,
+        shared: &mut SharedState,
+        player_id: u64,
+        reason: Option<String>,
+    ) -> MakeActionResult {
+        for (idx, accept) in self.players_accepted.iter_mut().enumerate() {
+            *accept = shared.seats[idx].resigned;
+        }
+
+        let reason = reason.map(|r| r.chars().take(MAX_REJECTION_REASON).collect());
+        self.rejection = Some(ScoreRejection { player_id, reason });
+
+        Ok(ActionChange::None)
+    }
+
+    /// Every seat that hasn't resigned has to accept, including seats nobody holds,
+    /// so one player can't finalize the count alone after the others leave.
+    /// A game with an abandoned seat is finished with `Game::scoring_timeout`.
+    fn all_accepted(&self, shared: &SharedState) -> bool {
+        shared
+            .seats
+            .iter()
+            .zip(&self.players_accepted)
+            .all(|(seat, &accepted)| accepted || seat.resigned)
+    }
+
+    /// Unlike `Cancel` this needs every player to agree, after which play goes on with
+    /// the clocks running until everyone passes again.
+    fn make_action_resume(&mut self, shared: &mut SharedState, player_id: u64) -> MakeActionResult {
+        self.resume_requested.resize(shared.seats.len(), false);
+        for (seat, requested) in shared.seats.iter().zip(&mut self.resume_requested) {
+            if seat.player == Some(player_id) || seat.resigned {
+                *requested = true;
+            }
+        }
+
+        if self.resume_requested.iter().all(|x| *x) {
+            Ok(ActionChange::PopState)
         } else {
             Ok(ActionChange::None)
         }
     }
 
     fn make_action_resign(&mut self, shared: &mut SharedState, player_id: u64) -> MakeActionResult {
-        // A single player can hold multiple seats so we have to mark every seat they hold
+        // A single player can hold multiple seats, but if they are of different colors
+        // only one color should be resigned.
+        let team = match resigning_team(shared, player_id) {
+            Some(team) => team,
+            None => return Ok(ActionChange::None),
+        };
         let seats = shared
             .seats
             .iter_mut()
             .enumerate()
-            .filter(|x| x.1.player == Some(player_id));
+            .filter(|x| x.1.player == Some(player_id) && x.1.team == team);
 
         for (seat_idx, seat) in seats {
             seat.resigned = true;
             self.players_accepted[seat_idx] = true;
         }
 
-        if self.players_accepted.iter().all(|x| *x) {
-            Ok(ActionChange::SwapState(GameState::Done(self.clone())))
+        if self.all_accepted(shared) {
+            Ok(ActionChange::SwapState(
+                self.finish(shared, ResultKind::Resignation),
+            ))
         } else {
             Ok(ActionChange::None)
         }
@@ -110,100 +569,686 @@ impl ScoringState {
         player_id: u64,
         action: ActionKind,
     ) -> MakeActionResult {
+        self.events.clear();
         match action {
-            ActionKind::Place(x, y) => self.make_action_place(shared, (x, y)),
-            ActionKind::Pass => self.make_action_pass(shared, player_id),
+            ActionKind::Place(x, y) => self.make_action_place(shared, Point::new(x, y)),
+            ActionKind::Pass | ActionKind::AcceptScore => self.make_action_pass(shared, player_id),
+            // Play can't go on past the move limit, so there is nothing to go back to.
+            ActionKind::Cancel | ActionKind::ResumePlay if shared.max_moves_reached() => {
+                Err(MakeActionError::WrongPhase)
+            }
             ActionKind::Cancel => Ok(ActionChange::PopState),
             ActionKind::Resign => self.make_action_resign(shared, player_id),
+            ActionKind::RejectScore(reason) => self.make_action_reject(shared, player_id, reason),
+            ActionKind::ResumePlay => self.make_action_resume(shared, player_id),
+            ActionKind::ProposeKomi(_) | ActionKind::ChooseColor(_) => {
+                Err(MakeActionError::Illegal)
+            }
+        }
+    }
+}
+
+/// Picks the color a player resigns when they hold seats of several colors:
+/// the color to move if they hold it, otherwise their first color still in the game.
+fn resigning_team(shared: &SharedState, player_id: u64) -> Option<Color> {
+    let active_team = shared.get_active_seat().team;
+    let mut held = shared
+        .seats
+        .iter()
+        .filter(|s| s.player == Some(player_id) && !s.resigned);
+    held.clone()
+        .find(|s| s.team == active_team)
+        .or_else(|| held.next())
+        .map(|s| s.team)
+}
+
+/// The color that would fill the last dame if the rest of the game were spent
+/// filling `dame` neutral points in turn order, starting from seat `turn`.
+/// `None` if every color fills the same number of them.
+/// Only informational, the actual count follows `DamePolicy`.
+pub fn dame_parity(dame: u32, seats: &[Seat], turn: usize) -> Option<Color> {
+    let order = fill_order(seats, turn);
+    if order.is_empty() || (dame as usize).is_multiple_of(order.len()) {
+        return None;
+    }
+    Some(order[(dame as usize - 1) % order.len()])
+}
+
+/// The count and result if every player passed now, for players peeking during
+/// the game. Obviously dead groups are left out like with `auto_dead_stones`,
+/// the rest is counted as alive. Nothing is changed, the result is `Counted`.
+pub fn preview_score(shared: &SharedState) -> (GroupVec<i32>, GameResult) {
+    let mods = GameModifier {
+        auto_dead_stones: true,
+        ..shared.mods.clone()
+    };
+    let state = ScoringState::new(
+        &shared.board,
+        &shared.seats,
+        &shared.points,
+        &mods,
+        shared.turn,
+    );
+    let result = GameResult::new(
+        ResultKind::Counted,
+        &state.scores,
+        &captured_stones(shared),
+        &shared.seats,
+    );
+    (state.scores, result)
+}
+
+/// A result for a game abandoned during play, for rating it instead of voiding it.
+/// Counted like `preview_score`, but the kind is `Provisional` so it can't be
+/// mistaken for a count the players accepted.
+pub fn estimate_result(shared: &SharedState) -> GameResult {
+    let (scores, _) = preview_score(shared);
+    GameResult::new(
+        ResultKind::Provisional,
+        &scores,
+        &captured_stones(shared),
+        &shared.seats,
+    )
+}
+
+/// Stones each color captured from the others by the end of play, see
+/// `CaptureMatrix::of_opponents`. Zero for games without a play state.
+pub fn captured_stones(shared: &SharedState) -> GroupVec<u32> {
+    match shared.board_history.last().map(|h| &h.state) {
+        Some(GameState::Play(play)) => play.captures.of_opponents(shared.komis.len()),
+        _ => std::iter::repeat_n(0, shared.komis.len()).collect(),
+    }
+}
+
+/// The area each color scores. With `EliminationPolicy::Neutralize` the stones
+/// and territory of colors whose every seat resigned count for nobody.
+fn scored_area(cache: &RegionCache, seats: &[Seat], mods: &GameModifier) -> Board {
+    let mut painted = cache.paint();
+    if mods.on_elimination == Some(EliminationPolicy::Neutralize) {
+        for point in &mut painted.points {
+            let team = *point;
+            if !point.is_empty() && seats.iter().all(|s| s.team != team || s.resigned) {
+                *point = Color::empty();
+            }
         }
     }
+    painted
 }
 
-/// Scores a board by filling in fully surrounded empty spaces based on chinese rules
-fn score_board(board: &Board, groups: &[Group]) -> Board {
-    let &Board {
-        width,
-        height,
-        toroidal,
-        ..
-    } = board;
-    let mut board = Board::empty(width, height, toroidal);
+/// Colors of the seats still in the game in turn order, starting from `turn`.
+fn fill_order(seats: &[Seat], turn: usize) -> Vec<Color> {
+    (0..seats.len())
+        .map(|idx| &seats[(turn + idx) % seats.len()])
+        .filter(|s| !s.resigned)
+        .map(|s| s.team)
+        .collect()
+}
 
-    // Fill living groups to the board
-    for group in groups {
-        if !group.alive {
-            continue;
+/// Empty points a group can't make two eyes with.
+const MAX_DEAD_AREA: usize = 6;
+
+/// Marks groups dead when they sit in a small area bordered by a single other
+/// color, with fewer than two empty points out of that color's reach.
+/// Groups surrounding each other that way are a seki, eg. one that only closes
+/// across the edge of a toroidal board, and stay alive.
+/// Anything less clear is left for the players.
+fn mark_obviously_dead(board: &Board, groups: &mut [Group]) {
+    let mut candidates = Vec::new();
+    for (idx, group) in groups.iter().enumerate() {
+        let mut area = HashSet::new();
+        let mut enemies = Vec::new();
+        let mut borders = Vec::new();
+        let mut queue = group.points.iter().copied().collect::<VecDeque<_>>();
+        while let Some(point) = queue.pop_front() {
+            if !area.insert(point) {
+                continue;
+            }
+            for next in board.surrounding_points(point) {
+                let color = board.get_point(next);
+                if color.is_empty() || color == group.team {
+                    queue.push_back(next);
+                } else {
+                    borders.push(next);
+                    if !enemies.contains(&color) {
+                        enemies.push(color);
+                    }
+                }
+            }
         }
-        for point in &group.points {
-            *board.point_mut(*point) = group.team;
+
+        let empty = area
+            .iter()
+            .filter(|&&p| board.get_point(p).is_empty())
+            .collect::<Vec<_>>();
+        let eye_space = empty
+            .iter()
+            .filter(|&&&p| {
+                board
+                    .surrounding_points(p)
+                    .all(|n| board.get_point(n) == group.team || board.get_point(n).is_empty())
+            })
+            .count();
+        if enemies.len() == 1 && empty.len() <= MAX_DEAD_AREA && eye_space < 2 {
+            candidates.push((idx, borders));
         }
     }
 
-    // Find empty points
-    let mut legal_points = board
-        .points
+    let is_candidate = |point: &Point| {
+        candidates
+            .iter()
+            .any(|&(idx, _)| groups[idx].points.contains(point))
+    };
+    let dead = candidates
         .iter()
-        .enumerate()
-        .filter_map(|(idx, c)| {
-            if c.is_empty() {
-                board.idx_to_coord(idx)
-            } else {
-                None
-            }
-        })
+        .filter(|(_, borders)| !borders.iter().any(is_candidate))
+        .map(|&(idx, _)| idx)
         .collect::<Vec<_>>();
+    for idx in dead {
+        groups[idx].alive = false;
+    }
+}
+
+/// Colors whose every group is marked dead.
+fn all_dead_colors(groups: &[Group]) -> Vec<Color> {
+    let mut colors = Vec::new();
+    for group in groups {
+        if !colors.contains(&group.team) && groups.iter().all(|g| g.team != group.team || !g.alive)
+        {
+            colors.push(group.team);
+        }
+    }
+    colors.sort_by_key(|c| c.0);
+    colors
+}
+
+/// The points of each color before counting the board, with `komis` in place of the old komi.
+fn rebase_komi(shared: &SharedState, komis: &[i32]) -> GroupVec<i32> {
+    shared
+        .points
+        .iter()
+        .zip(&shared.komis)
+        .zip(komis)
+        .map(|((points, old), new)| points - old + new)
+        .collect()
+}
 
-    #[derive(Copy, Clone)]
-    enum SeenTeams {
-        Zero,
-        One(Color),
-        Many,
+/// Adds a point for every stone and territory point on a scored board to `base` scores.
+/// `unit` is the value of a point, see `GameModifier::point_unit`.
+pub fn apply_area_scores(points: &Board, base: &[i32], unit: i32) -> GroupVec<i32> {
+    let mut scores: GroupVec<i32> = base.into();
+    for color in &points.points {
+        if !color.is_empty() {
+            *scores.score_for_mut(*color) += unit;
+        }
     }
-    use SeenTeams::*;
+    scores
+}
 
-    let mut seen = HashSet::new();
-    let mut stack = VecDeque::new();
-    let mut marked = Vec::new();
+/// Scores a board by filling in fully surrounded empty spaces based on chinese rules.
+/// The flood labels each empty point as it's reached, so points are skipped by
+/// looking up their label instead of being removed from a list of empty points.
+pub fn score_board(board: &Board, groups: &[Group]) -> Board {
+    RegionCache::new(board, groups).paint()
+}
 
-    while let Some(point) = legal_points.pop() {
-        stack.push_back(point);
+/// The empty regions of `board` with every stone counted as alive, found by the
+/// same flood as `score_board`. For eye space and other analysis.
+pub fn find_empty_regions(board: &Board) -> Vec<EmptyRegion> {
+    RegionCache::new(board, &find_groups(board)).empty_regions()
+}
 
-        let mut collisions = SeenTeams::Zero;
+/// Area of each color in points for teaching displays, not the official count.
+/// Empty regions bordering several colors are shared evenly between them instead
+/// of being neutral, so the totals add up to every point next to some stone.
+pub fn estimate_score(board: &Board, groups: &[Group], color_count: usize) -> GroupVec<f32> {
+    RegionCache::new(board, groups).estimate(color_count)
+}
 
-        while let Some(point) = stack.pop_front() {
-            marked.push(point);
-            for point in board.surrounding_points(point) {
-                if !seen.insert(point) {
-                    continue;
+/// Connected empty points, with the colors of the stones around them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmptyRegion {
+    /// In the order the flood reached them.
+    pub points: Vec<Point>,
+    /// In color order. Empty only on a board without stones.
+    pub colors: GroupVec<Color>,
+}
+
+/// A side of a point, see `BoundaryEdge`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// A side of a territory point that faces a stone or the edge of the board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoundaryEdge {
+    pub point: Point,
+    pub side: Side,
+}
+
+/// The outline of one owned empty region, for drawing around territory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TerritoryOutline {
+    pub owner: Color,
+    pub edges: Vec<BoundaryEdge>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Region {
+    points: Vec<Point>,
+    /// Colors of the living stones next to the region, in color order.
+    colors: GroupVec<Color>,
+    /// The only color touching this region, if there is exactly one.
+    owner: Option<Color>,
+    /// The territory contains a false eye, so the surrounding stones may not be safe.
+    disputed: bool,
+    /// Sides of the region's points facing a stone or the edge of the board.
+    boundary: Vec<BoundaryEdge>,
+}
+
+/// Labeling of the empty regions of a board while scoring.
+/// Toggling a group only refloods the regions next to it instead of the whole board.
+/// Each region is flooded completely from whichever point reaches it first, so the
+/// scored board doesn't depend on the order points or groups are visited in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionCache {
+    /// The board with only the living groups on it
+    stones: Board,
+    /// Region index + 1 for each empty point, 0 for stones
+    labels: Board<u32>,
+    /// Invalidated regions are left empty until their label is reused
+    regions: Vec<Region>,
+    /// Labels of invalidated regions, reused before new ones are added
+    free: Vec<u32>,
+}
+
+impl RegionCache {
+    pub fn new(board: &Board, groups: &[Group]) -> Self {
+        let points = (0..board.points.len()).filter_map(|idx| board.idx_to_coord(idx));
+        Self::flooded_in_order(board, groups, points)
+    }
+
+    /// Floods the regions starting from `order`, which has to cover the whole board.
+    fn flooded_in_order(
+        board: &Board,
+        groups: &[Group],
+        order: impl IntoIterator<Item = Point>,
+    ) -> Self {
+        let mut stones = board.empty_like();
+
+        // Fill living groups to the board
+        for group in groups {
+            if !group.alive {
+                continue;
+            }
+            for point in &group.points {
+                *stones.point_mut(*point) = group.team;
+            }
+        }
+
+        let mut cache = RegionCache {
+            stones,
+            labels: board.empty_like(),
+            regions: Vec::new(),
+            free: Vec::new(),
+        };
+
+        for point in order {
+            cache.flood(point);
+        }
+
+        cache
+    }
+
+    /// Updates the cache after `group` has been marked dead or alive.
+    pub fn toggle(&mut self, group: &Group) {
+        let team = if group.alive {
+            group.team
+        } else {
+            Color::empty()
+        };
+
+        // Every region touching the group has to be recomputed.
+        // Diagonals are included as they decide false eyes.
+        let mut stale = Vec::new();
+        for &point in &group.points {
+            let neighbours = self
+                .stones
+                .surrounding_points(point)
+                .chain(self.stones.surrounding_diagonal_points(point));
+            for point in std::iter::once(point).chain(neighbours) {
+                let label = self.labels.get_point(point);
+                if label != 0 && !stale.contains(&label) {
+                    stale.push(label);
                 }
+            }
+        }
 
-                match board.get_point(point) {
-                    Color(0) => {
-                        stack.push_back(point);
-                        legal_points.retain(|x| *x != point);
+        let mut dirty = group.points.to_vec();
+        for label in stale {
+            let region = std::mem::take(&mut self.regions[label as usize - 1]);
+            for &point in &region.points {
+                *self.labels.point_mut(point) = 0;
+            }
+            dirty.extend(region.points);
+            self.free.push(label);
+        }
+
+        for &point in &group.points {
+            *self.stones.point_mut(point) = team;
+        }
+
+        for point in dirty {
+            self.flood(point);
+        }
+    }
+
+    /// Returns a board with living stones and the territory they own.
+    pub fn paint(&self) -> Board {
+        let mut board = self.stones.clone();
+        self.fill_territory(&mut board);
+        board
+    }
+
+    /// Returns a board with only the living stones.
+    pub fn stones(&self) -> Board {
+        self.stones.clone()
+    }
+
+    /// Returns a board with only the owned empty points.
+    pub fn territory(&self) -> Board {
+        let mut board = self.stones.empty_like();
+        self.fill_territory(&mut board);
+        board
+    }
+
+    fn fill_territory(&self, board: &mut Board) {
+        for region in &self.regions {
+            if let Some(owner) = region.owner {
+                for &point in &region.points {
+                    *board.point_mut(point) = owner;
+                }
+            }
+        }
+    }
+
+    /// Adds the area score of `painted` (from `paint`) to `base` scores.
+    /// `fill_order` lists the colors in the order they would fill dame, starting
+    /// from the one to move.
+    pub fn count(
+        &self,
+        painted: &Board,
+        base: &[i32],
+        mods: &GameModifier,
+        fill_order: &[Color],
+    ) -> GroupVec<i32> {
+        let unit = mods.point_unit();
+        let mut scores = apply_area_scores(painted, base, unit);
+
+        if let Some(dynamic) = &mods.dynamic_komi {
+            let zeros = std::iter::repeat_n(0, base.len()).collect::<GroupVec<i32>>();
+            let area = apply_area_scores(painted, &zeros, unit);
+            let lead = area.iter().copied().max().unwrap_or(0);
+            for (score, area) in scores.iter_mut().zip(&area) {
+                let komi = (lead - area) as i64 * dynamic.percent as i64 / 100;
+                *score += komi.min(dynamic.cap as i64) as i32;
+            }
+        }
+
+        // With integer scoring an odd half point is dropped.
+        if mods.seki_territory == SekiTerritory::Split {
+            for (region, colors, _) in self.neutral_regions().filter(|r| r.2) {
+                for color in colors {
+                    *scores.score_for_mut(color) += region.points.len() as i32 * unit / 2;
+                }
+            }
+        }
+
+        match mods.dame_policy {
+            DamePolicy::Ignore => {}
+            DamePolicy::Split => {
+                for (region, colors, _) in self.neutral_regions().filter(|r| !r.2) {
+                    for color in colors {
+                        *scores.score_for_mut(color) += region.points.len() as i32 * unit / 2;
                     }
-                    c => {
-                        collisions = match collisions {
-                            Zero => One(c),
-                            One(x) if x == c => One(x),
-                            One(_) => Many,
-                            Many => Many,
+                }
+            }
+            DamePolicy::Parity => {
+                if !fill_order.is_empty() {
+                    for idx in 0..self.dame() as usize {
+                        let color = fill_order[idx % fill_order.len()];
+                        *scores.score_for_mut(color) += unit;
+                    }
+                }
+            }
+            DamePolicy::Button => {
+                if let Some(color) = fill_order.first() {
+                    *scores.score_for_mut(*color) += unit / 2;
+                }
+            }
+        }
+
+        scores
+    }
+
+    /// Area of each color with the neutral regions split, see `estimate_score`.
+    pub fn estimate(&self, color_count: usize) -> GroupVec<f32> {
+        let mut scores = std::iter::repeat_n(0.0, color_count).collect::<GroupVec<f32>>();
+        let area = self.paint();
+        for color in area.points.iter().filter(|c| !c.is_empty()) {
+            scores[color.as_usize() - 1] += 1.0;
+        }
+        for (region, colors, _) in self.neutral_regions() {
+            let share = region.points.len() as f32 / colors.len() as f32;
+            for color in colors {
+                scores[color.as_usize() - 1] += share;
+            }
+        }
+        scores
+    }
+
+    /// Number of neutral points that aren't part of a seki.
+    pub fn dame(&self) -> u32 {
+        self.neutral_regions()
+            .filter(|r| !r.2)
+            .map(|r| r.0.points.len() as u32)
+            .sum()
+    }
+
+    /// Regions touching several colors, with the colors and whether the region is a seki.
+    /// A region is counted as seki if every group next to it has at most two liberties,
+    /// so neither side can fill it without putting itself in atari.
+    fn neutral_regions(&self) -> impl Iterator<Item = (&Region, GroupVec<Color>, bool)> + '_ {
+        self.regions
+            .iter()
+            .filter(|r| r.owner.is_none() && !r.points.is_empty())
+            .filter_map(move |region| {
+                let mut colors = GroupVec::new();
+                let mut seki = true;
+                for &point in &region.points {
+                    for p in self.stones.surrounding_points(point) {
+                        let color = self.stones.get_point(p);
+                        if color.is_empty() {
+                            continue;
+                        }
+                        if seki && self.liberties(p) > 2 {
+                            seki = false;
+                        }
+                        if !colors.contains(&color) {
+                            colors.push(color);
                         }
                     }
                 }
+                if colors.len() < 2 {
+                    return None;
+                }
+                Some((region, colors, seki))
+            })
+    }
+
+    /// Liberties of the living group at `point`.
+    fn liberties(&self, point: Point) -> usize {
+        let board = &self.stones;
+        let team = board.get_point(point);
+        let mut seen = HashSet::new();
+        let mut liberties = HashSet::new();
+        let mut stack = vec![point];
+        seen.insert(point);
+        while let Some(point) = stack.pop() {
+            for p in board.surrounding_points(point) {
+                let color = board.get_point(p);
+                if color.is_empty() {
+                    liberties.insert(p);
+                } else if color == team && seen.insert(p) {
+                    stack.push(p);
+                }
             }
         }
+        liberties.len()
+    }
 
-        // The floodfill touched only a single color -> this must be their territory
-        if let One(color) = collisions {
-            for point in marked.drain(..) {
-                *board.point_mut(point) = color;
+    /// Every empty region, treating dead stones as empty points.
+    pub fn empty_regions(&self) -> Vec<EmptyRegion> {
+        self.regions
+            .iter()
+            .filter(|r| !r.points.is_empty())
+            .map(|r| EmptyRegion {
+                points: r.points.clone(),
+                colors: r.colors.clone(),
+            })
+            .collect()
+    }
+
+    /// Outlines of the regions owned by a single color. Regions emptied by
+    /// `toggle` and not yet reused are skipped.
+    pub fn outlines(&self) -> Vec<TerritoryOutline> {
+        self.regions
+            .iter()
+            .filter(|region| !region.points.is_empty())
+            .filter_map(|region| {
+                Some(TerritoryOutline {
+                    owner: region.owner?,
+                    edges: region.boundary.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the territory points of regions that contain a false eye.
+    pub fn disputed(&self) -> Vec<Point> {
+        let mut points = self
+            .regions
+            .iter()
+            .filter(|r| r.owner.is_some() && r.disputed)
+            .flat_map(|r| r.points.iter().copied())
+            .collect::<Vec<_>>();
+        points.sort();
+        points
+    }
+
+    /// An eye is false if enough of its diagonals are held by another color.
+    /// Eyes on the edge of the board only need one.
+    fn is_false_eye(&self, point: Point, owner: Color) -> bool {
+        let board = &self.stones;
+        let is_eye = board
+            .surrounding_points(point)
+            .all(|p| board.get_point(p) == owner);
+        if !is_eye {
+            return false;
+        }
+
+        let mut diagonals = 0;
+        let mut enemies = 0;
+        for p in board.surrounding_diagonal_points(point) {
+            diagonals += 1;
+            let color = board.get_point(p);
+            if !color.is_empty() && color != owner {
+                enemies += 1;
             }
         }
 
-        seen.clear();
-        marked.clear();
+        if diagonals < 4 {
+            enemies >= 1
+        } else {
+            enemies >= 2
+        }
     }
 
-    board
+    /// Labels a new region starting from `point` unless it is a stone or already labeled.
+    fn flood(&mut self, point: Point) {
+        if !self.stones.get_point(point).is_empty() || self.labels.get_point(point) != 0 {
+            return;
+        }
+
+        let label = self.free.pop().unwrap_or(self.regions.len() as u32 + 1);
+        let mut colors = GroupVec::<Color>::new();
+        let mut points = Vec::new();
+        let mut boundary = Vec::new();
+        let mut stack = VecDeque::new();
+
+        *self.labels.point_mut(point) = label;
+        stack.push_back(point);
+
+        let sides = [
+            (-1, 0, Side::Left),
+            (1, 0, Side::Right),
+            (0, -1, Side::Top),
+            (0, 1, Side::Bottom),
+        ];
+        while let Some(point) = stack.pop_front() {
+            points.push(point);
+            for &(dx, dy, side) in &sides {
+                let neighbor = self
+                    .stones
+                    .try_get_point(point.x as i32 + dx, point.y as i32 + dy);
+                if neighbor.is_none_or(|c| !c.is_empty()) {
+                    boundary.push(BoundaryEdge { point, side });
+                }
+            }
+            for point in self.stones.surrounding_points(point) {
+                match self.stones.get_point(point) {
+                    Color(0) => {
+                        if self.labels.get_point(point) == 0 {
+                            *self.labels.point_mut(point) = label;
+                            stack.push_back(point);
+                        }
+                    }
+                    c => {
+                        if !colors.contains(&c) {
+                            colors.push(c);
+                        }
+                    }
+                }
+            }
+        }
+
+        // The floodfill touched only a single color -> this must be their territory
+        let owner = match colors[..] {
+            [color] => Some(color),
+            _ => None,
+        };
+        colors.sort_by_key(|c| c.0);
+        let disputed = match owner {
+            Some(owner) => points.iter().any(|&p| self.is_false_eye(p, owner)),
+            None => false,
+        };
+
+        let region = Region {
+            points,
+            colors,
+            owner,
+            disputed,
+            boundary,
+        };
+        match self.regions.get_mut(label as usize - 1) {
+            Some(slot) => *slot = region,
+            None => self.regions.push(region),
+        }
+    }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,1274 @@
+use crate::game::clock::Millisecond;
+use crate::game::*;
+use crate::states::scoring::{
+    apply_area_scores, dame_parity, estimate_score, find_empty_regions, score_board, BoundaryEdge,
+    RegionCache, Side, TerritoryOutline,
+};
+use crate::states::{ScoreRejection, ScoringEvent, ScoringState};
+use crate::test_support::board_from;
+
+/// Creates a game where seat `n` is held by player `n + 1`.
+fn game_with(seats: &[u8], komis: &[i32], size: (u8, u8), mods: GameModifier) -> Game {
+    let mut game = Game::standard(seats, GroupVec::from(komis), size, mods, 0).unwrap();
+    for idx in 0..seats.len() {
+        game.take_seat(idx as u64 + 1, idx).unwrap();
+    }
+    game
+}
+
+fn two_player_game(komis: &[i32], size: (u8, u8)) -> Game {
+    game_with(&[1, 2], komis, size, GameModifier::default())
+}
+
+fn play(game: &mut Game, player_id: u64, action: ActionKind) {
+    game.make_action(player_id, action, Millisecond(0))
+        .expect("Action failed");
+}
+
+#[test]
+fn komi_adjusts_every_color() {
+    // Black +2.5, White +7.5
+    let mut game = two_player_game(&[5, 15], (3, 3));
+
+    play(&mut game, 1, ActionKind::Place(1, 1));
+    play(&mut game, 2, ActionKind::Pass);
+    play(&mut game, 1, ActionKind::Pass);
+
+    let scoring = game.state.assume::<ScoringState>();
+    // Black owns the whole 3x3 board.
+    assert_eq!(&scoring.scores[..], &[9 * 2 + 5, 15]);
+}
+
+#[test]
+fn scoring_state_equality_ignores_the_cache() {
+    let mut game = two_player_game(&[0, 15], (3, 3));
+    play(&mut game, 1, ActionKind::Place(1, 1));
+    play(&mut game, 2, ActionKind::Pass);
+    play(&mut game, 1, ActionKind::Pass);
+
+    let scoring = game.state.assume::<ScoringState>();
+    assert!(scoring.region_cache.is_some());
+    let bytes = serde_cbor::to_vec(scoring).unwrap();
+    let loaded: ScoringState = serde_cbor::from_slice(&bytes).unwrap();
+    assert!(loaded.region_cache.is_none());
+    assert_eq!(&loaded, scoring);
+}
+
+#[test]
+fn region_cache_matches_full_rescore() {
+    let board = board_from(
+        &[
+            ".x.o.....",
+            "xx.oo.x..",
+            "...o.xox.",
+            "oooo..x..",
+            "....x....",
+            ".xx...oo.",
+            ".xo.x.o.o",
+            "xo.....oo",
+            ".x..x....",
+        ],
+        false,
+    );
+    let mut groups = find_groups(&board);
+    let mut cache = RegionCache::new(&board, &groups);
+    assert_eq!(cache.paint(), score_board(&board, &groups));
+
+    // Toggle groups dead, then revive some of them again.
+    let order = (0..groups.len()).chain((0..groups.len()).step_by(3));
+    for idx in order {
+        groups[idx].alive = !groups[idx].alive;
+        cache.toggle(&groups[idx]);
+        assert_eq!(cache.paint(), score_board(&board, &groups));
+    }
+}
+
+#[test]
+fn separate_territories_of_one_color_all_count() {
+    let count = |rows: &[&str]| {
+        let board = board_from(rows, false);
+        let painted = score_board(&board, &find_groups(&board));
+        apply_area_scores(&painted, &[0, 0], 1)
+    };
+
+    // Black encloses both corners, the middle touches black twice but is still open.
+    let two_black = [
+        ".x.....", //
+        "xx.....", //
+        ".......", //
+        ".......", //
+        ".....xx", //
+        ".....x.", //
+    ];
+    let board = board_from(&two_black, false);
+    let painted = score_board(&board, &find_groups(&board));
+    assert_eq!(painted.get_point(Point::new(0, 0)), Color(1));
+    assert_eq!(painted.get_point(Point::new(6, 5)), Color(1));
+    // A single color around every empty point owns the whole board.
+    assert_eq!(&count(&two_black)[..], &[42, 0]);
+
+    let mixed = [
+        ".x...o.", //
+        "xx...oo", //
+        ".......", //
+        ".......", //
+        ".....xx", //
+        ".....x.", //
+    ];
+    let board = board_from(&mixed, false);
+    let painted = score_board(&board, &find_groups(&board));
+    assert_eq!(painted.get_point(Point::new(0, 0)), Color(1));
+    assert_eq!(painted.get_point(Point::new(6, 5)), Color(1));
+    assert_eq!(painted.get_point(Point::new(6, 0)), Color(2));
+    assert_eq!(painted.get_point(Point::new(3, 3)), Color::empty());
+    assert_eq!(&count(&mixed)[..], &[8, 4]);
+}
+
+#[test]
+fn false_eye_is_disputed() {
+    let board = board_from(&[".xo..", "x.xo.", ".xo..", ".....", "....."], false);
+    let groups = find_groups(&board);
+    let cache = RegionCache::new(&board, &groups);
+    assert_eq!(cache.paint().get_point(Point::new(1, 1)), Color(1));
+    assert_eq!(cache.disputed(), vec![Point::new(1, 1)]);
+
+    // A single enemy diagonal doesn't make a false eye in the middle of the board.
+    let board = board_from(&[".x...", "x.xo.", ".xo..", ".....", "....."], false);
+    let groups = find_groups(&board);
+    assert_eq!(RegionCache::new(&board, &groups).disputed(), vec![]);
+}
+
+#[test]
+fn scoring_does_not_depend_on_visit_order() {
+    use rand::prelude::*;
+    use rand_pcg::Lcg64Xsh32;
+
+    let board = board_from(
+        &[
+            ".x.o.....",
+            "xx.oo.x..",
+            "...o.xox.",
+            "oooo..x..",
+            "....x....",
+            ".xx...oo.",
+            ".xo.x.o.o",
+            "xo.....oo",
+            ".x..x....",
+        ],
+        true,
+    );
+    let mut groups = find_groups(&board);
+    for idx in (0..groups.len()).step_by(4) {
+        groups[idx].alive = false;
+    }
+    let expected = RegionCache::new(&board, &groups);
+    let mut points = (0..board.points.len())
+        .filter_map(|idx| board.idx_to_coord(idx))
+        .collect::<Vec<_>>();
+
+    for seed in 0..20 {
+        let mut rng = Lcg64Xsh32::seed_from_u64(seed);
+        points.shuffle(&mut rng);
+        groups.shuffle(&mut rng);
+        let cache = RegionCache::flooded_in_order(&board, &groups, points.iter().copied());
+        assert_eq!(cache.paint(), expected.paint());
+        assert_eq!(cache.territory(), expected.territory());
+        assert_eq!(cache.disputed(), expected.disputed());
+        assert_eq!(cache.dame(), expected.dame());
+    }
+}
+
+#[test]
+fn seat_status_shows_acceptance() {
+    let mut game = two_player_game(&[0, 15], (5, 5));
+    play(&mut game, 1, ActionKind::Pass);
+    let status = game.seat_status();
+    assert_eq!(status[0].player, Some(1));
+    assert_eq!(status[1].team, Color(2));
+    assert_eq!(status[0].accepted, None);
+    assert_eq!(status[0].time_left, None);
+
+    play(&mut game, 2, ActionKind::Pass);
+    play(&mut game, 1, ActionKind::Pass);
+    assert!(matches!(game.state, GameState::Scoring(_)));
+    let accepted = game
+        .seat_status()
+        .iter()
+        .map(|s| s.accepted)
+        .collect::<Vec<_>>();
+    assert_eq!(accepted, vec![Some(true), Some(false)]);
+}
+
+#[test]
+fn preview_names_the_leader_without_scoring() {
+    // Black gets half a point of komi, the board is split evenly.
+    let mut game = two_player_game(&[1, 0], (4, 5));
+    for y in 0..5 {
+        play(&mut game, 1, ActionKind::Place(1, y));
+        play(&mut game, 2, ActionKind::Place(2, y));
+    }
+    // A hopeless white stone in black's area, counted as alive it would win for white.
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Place(0, 2));
+
+    let history_len = game.shared.board_history.len();
+    let (scores, result) = game.preview_score();
+    assert_eq!(&scores[..], &[21, 20]);
+    assert_eq!(result.kind, ResultKind::Counted);
+    assert_eq!(result.winner, Some(Color(1)));
+    assert_eq!(result.margin, 1);
+
+    // Play goes on as if nothing happened.
+    assert!(matches!(game.state, GameState::Play(_)));
+    assert_eq!(game.shared.board_history.len(), history_len);
+    play(&mut game, 1, ActionKind::Place(0, 1));
+}
+
+#[test]
+fn tromp_taylor_counts_the_board_as_is() {
+    let mods = GameModifier {
+        tromp_taylor: true,
+        // Ignored, every stone is alive.
+        auto_dead_stones: true,
+        ..GameModifier::default()
+    };
+    let mut game = game_with(&[1, 2], &[0, 0], (5, 5), mods);
+    for y in 0..5 {
+        play(&mut game, 1, ActionKind::Place(1, y));
+        play(&mut game, 2, ActionKind::Place(3, y));
+    }
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Place(0, 2));
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+
+    // The white stone on the left makes that side neutral, the right side is white's.
+    let done = match &game.state {
+        GameState::Done(done) => done,
+        _ => panic!("Expected the game to be over"),
+    };
+    assert_eq!(&done.scores[..], &[10, 22]);
+    let result = done.result.as_ref().unwrap();
+    assert_eq!(result.kind, ResultKind::Counted);
+    assert_eq!(result.winner, Some(Color(2)));
+    assert_eq!(result.margin, 12);
+}
+
+#[test]
+fn full_board_is_pure_area() {
+    let board = board_from(&["xxo", "xoo", "xxo"], false);
+    let seats = [Color(1), Color(2)]
+        .iter()
+        .map(|&team| Seat {
+            team,
+            ..Seat::default()
+        })
+        .collect::<Vec<_>>();
+    let mods = GameModifier {
+        dame_policy: DamePolicy::Parity,
+        ..GameModifier::default()
+    };
+    let scoring = ScoringState::new(&board, &seats, &[0, 0], &mods, 0);
+    assert_eq!(scoring.dame, 0);
+    assert_eq!(&scoring.scores[..], &[10, 8]);
+    assert_eq!(score_board(&board, &scoring.groups), board);
+}
+
+#[test]
+fn out_of_bounds_points_are_rejected() {
+    let mut game = two_player_game(&[0, 0], (3, 3));
+    let place =
+        |game: &mut Game, x, y| game.make_action(1, ActionKind::Place(x, y), Millisecond(0));
+
+    assert_eq!(place(&mut game, 3, 0), Err(MakeActionError::OutOfBounds));
+    assert_eq!(place(&mut game, 0, 7), Err(MakeActionError::OutOfBounds));
+    assert_eq!(game.move_number(), 0);
+
+    play(&mut game, 1, ActionKind::Place(1, 1));
+    play(&mut game, 2, ActionKind::Pass);
+    play(&mut game, 1, ActionKind::Pass);
+    assert!(matches!(game.state, GameState::Scoring(_)));
+
+    assert_eq!(place(&mut game, 3, 3), Err(MakeActionError::OutOfBounds));
+    assert_eq!(
+        place(&mut game, u32::MAX, 1),
+        Err(MakeActionError::OutOfBounds)
+    );
+    // An empty point is still just ignored.
+    assert_eq!(place(&mut game, 0, 0), Ok(()));
+}
+
+#[test]
+fn empty_regions_list_their_borders() {
+    let board = board_from(
+        &[
+            ".x.o.", //
+            "xx.oo", //
+            "..ooo", //
+        ],
+        false,
+    );
+    let mut regions = find_empty_regions(&board);
+    for region in &mut regions {
+        region.points.sort();
+    }
+    regions.sort_by_key(|r| r.points[0]);
+
+    let summary = regions
+        .iter()
+        .map(|r| (r.points.clone(), r.colors.to_vec()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (vec![Point::new(0, 0)], vec![Color(1)]),
+            (
+                vec![Point::new(2, 0), Point::new(2, 1)],
+                vec![Color(1), Color(2)]
+            ),
+            (vec![Point::new(4, 0)], vec![Color(2)]),
+            (
+                vec![Point::new(0, 2), Point::new(1, 2)],
+                vec![Color(1), Color(2)]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn territory_outline_follows_the_perimeter() {
+    let board = board_from(&["....x.", "....x.", "....x.", "xxxxx.", "......"], false);
+    let cache = RegionCache::new(&board, &find_groups(&board));
+    let outlines = cache.outlines();
+
+    // The 4x3 corner and the L shaped area outside the wall.
+    assert_eq!(outlines.len(), 2);
+    let corner = outlines
+        .iter()
+        .find(|o| o.edges.iter().any(|e| e.point == Point::new(0, 0)))
+        .unwrap();
+    assert_eq!(corner.owner, Color(1));
+    assert_eq!(corner.edges.len(), 2 * (4 + 3));
+    assert!(corner.edges.contains(&BoundaryEdge {
+        point: Point::new(3, 2),
+        side: Side::Bottom,
+    }));
+
+    // Interior points have no edges.
+    assert!(!corner.edges.iter().any(|e| e.point == Point::new(1, 1)));
+}
+
+#[test]
+fn toggled_outlines_match_a_fresh_cache() {
+    let board = board_from(&[".x.o."; 5], false);
+    let mut groups = find_groups(&board);
+    let mut cache = RegionCache::new(&board, &groups);
+    assert_eq!(cache.outlines().len(), 2);
+
+    let white = groups.iter().position(|g| g.team == Color(2)).unwrap();
+    groups[white].alive = false;
+    cache.toggle(&groups[white]);
+
+    let sorted = |mut outlines: Vec<TerritoryOutline>| {
+        for outline in &mut outlines {
+            outline.edges.sort_by_key(|e| (e.point, e.side as u8));
+        }
+        outlines.sort_by_key(|o| o.edges[0].point);
+        outlines
+    };
+    let toggled = sorted(cache.outlines());
+    // Black's column on the left and everything right of it.
+    assert_eq!(toggled.len(), 2);
+    assert_eq!(
+        toggled,
+        sorted(RegionCache::new(&board, &groups).outlines())
+    );
+}
+
+#[test]
+fn estimate_splits_dame() {
+    let board = board_from(&["x.o"], false);
+    let groups = find_groups(&board);
+    assert_eq!(&estimate_score(&board, &groups, 2)[..], &[1.5, 1.5]);
+
+    // Three colors around a region share it evenly, the totals still cover the board.
+    let board = board_from(&["xx.oo", "xx.oo", "..z..", "....."], false);
+    let groups = find_groups(&board);
+    let estimate = estimate_score(&board, &groups, 3);
+    let expected = [4.0 + 11.0 / 3.0, 4.0 + 11.0 / 3.0, 1.0 + 11.0 / 3.0];
+    for (estimate, expected) in estimate.iter().zip(&expected) {
+        assert!((estimate - expected).abs() < 1e-4);
+    }
+    assert!((estimate.iter().sum::<f32>() - 20.0).abs() < 1e-4);
+}
+
+#[test]
+fn odd_dame_favor_the_player_to_move() {
+    let board = board_from(&["x.o", "x.o", "x.o"], false);
+    let dame = RegionCache::new(&board, &find_groups(&board)).dame();
+    assert_eq!(dame, 3);
+
+    let seats = [Color(1), Color(2)]
+        .iter()
+        .map(|&team| Seat {
+            team,
+            ..Seat::default()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(dame_parity(dame, &seats, 0), Some(Color(1)));
+    assert_eq!(dame_parity(dame, &seats, 1), Some(Color(2)));
+    // With one dame filled it evens out.
+    assert_eq!(dame_parity(dame - 1, &seats, 0), None);
+}
+
+#[test]
+fn tied_standings_go_by_captures_then_seats() {
+    // Red has the first seat, so it ranks ahead of the tied black.
+    let seats = [Color(3), Color(2), Color(1)]
+        .iter()
+        .map(|&team| Seat {
+            team,
+            ..Seat::default()
+        })
+        .collect::<Vec<_>>();
+    let result = GameResult::new(ResultKind::Counted, &[20, 10, 20], &[0, 0, 0], &seats);
+    assert_eq!(result.winner, None);
+    assert_eq!(result.margin, 0);
+    assert_eq!(result.standings, vec![Color(3), Color(1), Color(2)]);
+
+    // Captures come before the seats.
+    let result = GameResult::new(ResultKind::Counted, &[20, 10, 20], &[2, 5, 1], &seats);
+    assert_eq!(result.winner, None);
+    assert_eq!(result.standings, vec![Color(1), Color(3), Color(2)]);
+
+    // Resigned colors come last whatever their score.
+    let mut seats = seats;
+    seats[0].resigned = true;
+    let result = GameResult::new(ResultKind::Counted, &[20, 10, 20], &[0, 0, 0], &seats);
+    assert_eq!(result.winner, Some(Color(1)));
+    assert_eq!(result.margin, 10);
+    assert_eq!(result.standings, vec![Color(1), Color(2), Color(3)]);
+}
+
+fn rengo_scoring(policy: TeamAcceptPolicy) -> Game {
+    let mods = GameModifier {
+        team_accept_policy: policy,
+        ..GameModifier::default()
+    };
+    let mut game = game_with(&[1, 2, 1, 2], &[0, 15], (5, 5), mods);
+    // Passing marks the whole team as passed.
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+    assert!(matches!(game.state, GameState::Scoring(_)));
+    game
+}
+
+#[test]
+fn team_accept_all_members() {
+    let mut game = rengo_scoring(TeamAcceptPolicy::AllMembers);
+
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(scoring.players_accepted, vec![true, true, false, false]);
+
+    play(&mut game, 3, ActionKind::Pass);
+    assert!(matches!(game.state, GameState::Scoring(_)));
+    play(&mut game, 4, ActionKind::Pass);
+    assert!(matches!(game.state, GameState::Done(_)));
+}
+
+#[test]
+fn team_accept_any_member() {
+    let mut game = rengo_scoring(TeamAcceptPolicy::AnyMember);
+
+    play(&mut game, 1, ActionKind::Pass);
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(scoring.players_accepted, vec![true, false, true, false]);
+
+    play(&mut game, 4, ActionKind::Pass);
+    assert!(matches!(game.state, GameState::Done(_)));
+}
+
+#[test]
+fn resign_only_affects_one_color() {
+    let mut game = Game::standard(
+        &[1, 2, 3],
+        GroupVec::from(&[0, 0, 0][..]),
+        (5, 5),
+        GameModifier::default(),
+        0,
+    )
+    .unwrap();
+    // Player 1 holds both black and white.
+    game.take_seat(1, 0).unwrap();
+    game.take_seat(1, 1).unwrap();
+    game.take_seat(2, 2).unwrap();
+
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+    assert!(matches!(game.state, GameState::Scoring(_)));
+
+    // Black is to move, so black resigns.
+    play(&mut game, 1, ActionKind::Resign);
+    let resigned = game
+        .shared
+        .seats
+        .iter()
+        .map(|s| s.resigned)
+        .collect::<Vec<_>>();
+    assert_eq!(resigned, vec![true, false, false]);
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(scoring.players_accepted, vec![true, false, false]);
+
+    // Resigning again resigns the remaining color.
+    play(&mut game, 1, ActionKind::Resign);
+    assert!(game.shared.seats[1].resigned);
+}
+
+#[test]
+fn scoring_timeout_on_empty_board_gives_komi() {
+    let mut game = two_player_game(&[0, 15], (9, 9));
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+
+    game.scoring_timeout().unwrap();
+
+    let done = match &game.state {
+        GameState::Done(state) => state,
+        _ => panic!("Game should be over"),
+    };
+    assert_eq!(&done.scores[..], &[0, 15]);
+    assert_eq!(
+        done.result,
+        Some(GameResult {
+            kind: ResultKind::ScoringTimeout,
+            winner: Some(Color(2)),
+            margin: 15,
+            standings: vec![Color(2), Color(1)],
+        })
+    );
+
+    // The timeout survives a replay.
+    let game = Game::load(&game.dump()).unwrap();
+    assert!(matches!(game.state, GameState::Done(_)));
+}
+
+#[test]
+fn reject_clears_acceptances() {
+    let mut game = two_player_game(&[0, 15], (5, 5));
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+
+    play(&mut game, 1, ActionKind::AcceptScore);
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(scoring.players_accepted, vec![true, false]);
+
+    play(
+        &mut game,
+        2,
+        ActionKind::RejectScore(Some("The corner is alive".to_string())),
+    );
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(scoring.players_accepted, vec![false, false]);
+    assert_eq!(
+        scoring.rejection,
+        Some(ScoreRejection {
+            player_id: 2,
+            reason: Some("The corner is alive".to_string()),
+        })
+    );
+
+    // Accepting clears the rejection, and pass still works as an accept.
+    play(&mut game, 1, ActionKind::AcceptScore);
+    assert_eq!(game.state.assume::<ScoringState>().rejection, None);
+    play(&mut game, 2, ActionKind::Pass);
+    assert!(matches!(game.state, GameState::Done(_)));
+}
+
+#[test]
+fn accept_score_finalizes_when_all_agree() {
+    let mut game = two_player_game(&[0, 15], (5, 5));
+    assert_eq!(
+        game.make_action(1, ActionKind::AcceptScore, Millisecond(0)),
+        Err(MakeActionError::Illegal)
+    );
+
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+    play(&mut game, 1, ActionKind::AcceptScore);
+    assert!(matches!(game.state, GameState::Scoring(_)));
+    play(&mut game, 2, ActionKind::AcceptScore);
+    assert!(matches!(game.state, GameState::Done(_)));
+
+    // Replays store the new actions.
+    let game = Game::load(&game.dump()).unwrap();
+    assert!(matches!(game.state, GameState::Done(_)));
+}
+
+fn seki_scores(seki_territory: SekiTerritory) -> GroupVec<i32> {
+    // The inner black and white groups share the two empty points as their only liberties.
+    let board = board_from(
+        &[
+            ".ooooo.", //
+            "oxxxxoo", //
+            "ox..oxx", //
+            "xoooox.", //
+            ".xxxx..", //
+            ".......", //
+        ],
+        false,
+    );
+    let seats = [Color(1), Color(2)].map(|team| Seat {
+        player: None,
+        team,
+        resigned: false,
+    });
+    let mods = GameModifier {
+        seki_territory,
+        ..GameModifier::default()
+    };
+    ScoringState::new(&board, &seats, &[0, 0], &mods, 0).scores
+}
+
+#[test]
+fn seki_territory_nobody() {
+    let scores = seki_scores(SekiTerritory::Nobody);
+    // Black: 13 stones + 11 territory, white: 14 stones + 2 territory.
+    assert_eq!(&scores[..], &[24 * 2, 16 * 2]);
+}
+
+#[test]
+fn seki_territory_split() {
+    let scores = seki_scores(SekiTerritory::Split);
+    // Both get half of the two shared points.
+    assert_eq!(&scores[..], &[24 * 2 + 2, 16 * 2 + 2]);
+}
+
+#[test]
+fn seki_across_the_seam_stays_alive() {
+    // The two empty points are only connected through the wrap, as are the
+    // black stones of the top and bottom rows.
+    let board = board_from(
+        &[
+            ".xxxx.", //
+            "oxxxxo", //
+            "oooooo", //
+            "xxxxxx", //
+        ],
+        true,
+    );
+    let seats = [Color(1), Color(2)].map(|team| Seat {
+        player: None,
+        team,
+        resigned: false,
+    });
+    let mods = GameModifier {
+        auto_dead_stones: true,
+        ..GameModifier::default()
+    };
+    let scoring = ScoringState::new(&board, &seats, &[0, 0], &mods, 0);
+    assert_eq!(scoring.groups.len(), 2);
+    assert!(scoring.groups.iter().all(|g| g.alive));
+    assert_eq!(scoring.stones, board);
+    for point in [Point::new(0, 0), Point::new(5, 0)] {
+        assert_eq!(scoring.territory.get_point(point), Color(0));
+    }
+    // Seki points aren't dame.
+    assert_eq!(scoring.dame, 0);
+    assert_eq!(&scoring.scores[..], &[14 * 2, 8 * 2]);
+}
+
+#[test]
+fn abandoned_game_gets_a_provisional_result() {
+    let mut game = two_player_game(&[0, 1], (5, 5));
+    // Black walls off three columns, white two.
+    for y in 0..5 {
+        play(&mut game, 1, ActionKind::Place(2, y));
+        play(&mut game, 2, ActionKind::Place(3, y));
+    }
+    // A white stone that can't live inside black's area.
+    play(&mut game, 1, ActionKind::Place(1, 1));
+    play(&mut game, 2, ActionKind::Place(0, 0));
+    play(&mut game, 1, ActionKind::Place(1, 2));
+    play(&mut game, 2, ActionKind::Place(4, 0));
+    play(&mut game, 1, ActionKind::Place(0, 2));
+
+    let result = game.estimate_result();
+    assert_eq!(result.kind, ResultKind::Provisional);
+    assert_eq!(result.winner, Some(Color(1)));
+    assert_eq!(result.margin, (15 - 10) * 2 - 1);
+    assert!(matches!(game.state, GameState::Play(_)));
+}
+
+#[test]
+fn dynamic_komi_narrows_one_sided_games() {
+    let board = board_from(&["...xo"; 5], false);
+    let seats = [Color(1), Color(2)].map(|team| Seat {
+        player: None,
+        team,
+        resigned: false,
+    });
+    let scores = |dynamic_komi| {
+        let mods = GameModifier {
+            dynamic_komi,
+            ..GameModifier::default()
+        };
+        ScoringState::new(&board, &seats, &[0, 0], &mods, 0).scores
+    };
+
+    // Black leads by 15 points.
+    assert_eq!(&scores(None)[..], &[40, 10]);
+    let half = |cap| Some(DynamicKomi { percent: 50, cap });
+    assert_eq!(&scores(half(100))[..], &[40, 25]);
+    assert_eq!(&scores(half(10))[..], &[40, 20]);
+}
+
+#[test]
+fn toggling_back_keeps_initial_scores() {
+    let mut game = two_player_game(&[0, 15], (5, 5));
+    play(&mut game, 1, ActionKind::Place(1, 1));
+    play(&mut game, 2, ActionKind::Place(3, 3));
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+
+    let initial = game.state.assume::<ScoringState>().clone();
+    assert_eq!(
+        initial.scores,
+        apply_area_scores(
+            &initial.territory,
+            &apply_area_scores(&initial.stones, &game.shared.points, 2),
+            2
+        )
+    );
+
+    play(&mut game, 1, ActionKind::Place(3, 3));
+    play(&mut game, 1, ActionKind::Place(3, 3));
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(scoring.stones, initial.stones);
+    assert_eq!(scoring.territory, initial.territory);
+    assert_eq!(scoring.scores, initial.scores);
+}
+
+#[test]
+fn stalling_in_scoring_runs_out_the_clock() {
+    let mods = GameModifier {
+        clock: Some(Clock {
+            rule: clock::ClockRule::Fischer(clock::FischerClock {
+                main_time: Millisecond(60_000),
+                increment: Millisecond(0),
+            }),
+        }),
+        scoring_uses_clock: true,
+        ..GameModifier::default()
+    };
+    let mut game = game_with(&[1, 2], &[0, 15], (5, 5), mods);
+    let act = |game: &mut Game, player_id, action, time| {
+        game.make_action(player_id, action, Millisecond(time))
+            .expect("Action failed");
+    };
+    act(&mut game, 1, ActionKind::Place(1, 1), 0);
+    act(&mut game, 2, ActionKind::Place(3, 3), 1_000);
+    act(&mut game, 1, ActionKind::Pass, 2_000);
+    act(&mut game, 2, ActionKind::Pass, 3_000);
+
+    // Black marks the white stone dead and accepts, spending 3 seconds.
+    act(&mut game, 1, ActionKind::Place(3, 3), 5_000);
+    act(&mut game, 1, ActionKind::AcceptScore, 6_000);
+    let clock = game.shared.clock.as_ref().unwrap();
+    assert_eq!(
+        clock.clocks[0],
+        clock::PlayerClock::Plain {
+            last_time: Millisecond(6_000),
+            time_left: Millisecond(60_000 - 1_000 - 3_000),
+        }
+    );
+    let scores = game.state.assume::<ScoringState>().scores.clone();
+
+    // White stalls past their time, so the toggle is turned into an accept.
+    act(&mut game, 2, ActionKind::Place(3, 3), 70_000);
+    let (scoring, result) = finished(&game);
+    assert_eq!(scoring.scores, scores);
+    assert_eq!(result.kind, ResultKind::Counted);
+    assert_eq!(result.winner, Some(Color(1)));
+    assert_eq!(
+        game.actions.last().unwrap().action,
+        ReplayActionKind::Play(ActionKind::AcceptScore)
+    );
+}
+
+#[test]
+fn toggling_reports_events() {
+    let mut game = two_player_game(&[0, 15], (5, 5));
+    play(&mut game, 1, ActionKind::Place(1, 1));
+    play(&mut game, 2, ActionKind::Place(3, 3));
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+    assert!(game.state.assume::<ScoringState>().events.is_empty());
+
+    play(&mut game, 1, ActionKind::Place(3, 3));
+    let group = GroupId(Point::new(3, 3));
+    assert_eq!(
+        game.state.assume::<ScoringState>().events,
+        vec![
+            ScoringEvent::GroupMarkedDead {
+                group,
+                color: Color(2),
+                stones: 1
+            },
+            // Black owns the whole board against white's komi.
+            ScoringEvent::ScoreChanged {
+                leader: Some(Color(1)),
+                margin: 25 * 2 - 15
+            },
+        ]
+    );
+
+    play(&mut game, 1, ActionKind::Place(3, 3));
+    let events = &game.state.assume::<ScoringState>().events;
+    assert_eq!(
+        events[0],
+        ScoringEvent::GroupRevived {
+            group,
+            color: Color(2),
+            stones: 1
+        }
+    );
+    assert_eq!(
+        events[1],
+        ScoringEvent::ScoreChanged {
+            leader: Some(Color(2)),
+            margin: 15
+        }
+    );
+
+    play(&mut game, 2, ActionKind::AcceptScore);
+    assert_eq!(
+        game.state.assume::<ScoringState>().events,
+        vec![ScoringEvent::PlayerAccepted { player_id: 2 }]
+    );
+}
+
+#[test]
+fn forgotten_dead_stone_still_gives_territory() {
+    let forget_stone = |mods: GameModifier| {
+        let mut game = game_with(&[1, 2], &[0, 0], (5, 5), mods);
+        for y in 0..5 {
+            play(&mut game, 1, ActionKind::Place(1, y));
+            if y == 0 {
+                play(&mut game, 2, ActionKind::Place(0, 2));
+            } else {
+                play(&mut game, 2, ActionKind::Pass);
+            }
+        }
+        play(&mut game, 1, ActionKind::Pass);
+        play(&mut game, 2, ActionKind::Pass);
+        game
+    };
+
+    let game = forget_stone(GameModifier::default());
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(
+        scoring.territory.get_point(Point::new(0, 0)),
+        Color::empty()
+    );
+    assert_eq!(&scoring.scores[..], &[40, 2]);
+
+    let game = forget_stone(GameModifier {
+        auto_dead_stones: true,
+        ..GameModifier::default()
+    });
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(scoring.territory.get_point(Point::new(0, 0)), Color(1));
+    assert_eq!(scoring.territory.get_point(Point::new(0, 2)), Color(1));
+    assert_eq!(&scoring.scores[..], &[50, 0]);
+}
+
+#[test]
+fn resuming_play_needs_everyone() {
+    let mut game = two_player_game(&[0, 0], (5, 5));
+    play(&mut game, 1, ActionKind::Place(1, 1));
+    play(&mut game, 2, ActionKind::Place(3, 3));
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+    let board = game.shared.board.clone();
+
+    play(&mut game, 1, ActionKind::ResumePlay);
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(scoring.resume_requested, vec![true, false]);
+
+    play(&mut game, 2, ActionKind::ResumePlay);
+    assert!(matches!(game.state, GameState::Play(_)));
+    assert_eq!(game.shared.board, board);
+
+    // Play goes on and needs everyone to pass again.
+    play(&mut game, 1, ActionKind::Place(0, 0));
+    play(&mut game, 2, ActionKind::Pass);
+    assert!(matches!(game.state, GameState::Play(_)));
+    play(&mut game, 1, ActionKind::Pass);
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(scoring.resume_requested, vec![false, false]);
+}
+
+#[test]
+fn scoring_after_resume_starts_over() {
+    let mut game = two_player_game(&[0, 0], (5, 5));
+    play(&mut game, 1, ActionKind::Place(1, 0));
+    play(&mut game, 2, ActionKind::Place(0, 0));
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+
+    // White disagrees that the corner stone is dead.
+    play(&mut game, 1, ActionKind::Place(0, 0));
+    play(&mut game, 1, ActionKind::AcceptScore);
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(scoring.players_accepted, vec![true, false]);
+    assert_eq!(scoring.groups_of(Color(2)).filter(|g| !g.alive).count(), 1);
+
+    // So black proves it by capturing.
+    play(&mut game, 1, ActionKind::ResumePlay);
+    play(&mut game, 2, ActionKind::ResumePlay);
+    play(&mut game, 1, ActionKind::Place(0, 1));
+    play(&mut game, 2, ActionKind::Pass);
+    play(&mut game, 1, ActionKind::Pass);
+
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(
+        game.shared.board.get_point(Point::new(0, 0)),
+        Color::empty()
+    );
+    assert_eq!(scoring.groups_of(Color(2)).count(), 0);
+    assert!(scoring.groups.iter().all(|g| g.alive));
+    assert_eq!(scoring.players_accepted, vec![false, false]);
+    assert_eq!(scoring.resume_requested, vec![false, false]);
+    assert_eq!(scoring.territory.get_point(Point::new(0, 0)), Color(1));
+    assert_eq!(&scoring.scores[..], &[50, 0]);
+}
+
+#[test]
+fn accepting_with_many_dead_stones_needs_confirmation() {
+    let mods = GameModifier {
+        confirm_dead_stones: Some(ConfirmDeadStones { stones: 3 }),
+        ..GameModifier::default()
+    };
+    let mut game = game_with(&[1, 2], &[0, 0], (5, 5), mods);
+    for y in 0..5 {
+        play(&mut game, 1, ActionKind::Place(1, y));
+        play(&mut game, 2, ActionKind::Place(3, y));
+    }
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+    // Black misclicks their own wall.
+    play(&mut game, 1, ActionKind::Place(1, 0));
+
+    play(&mut game, 1, ActionKind::AcceptScore);
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(
+        scoring.events,
+        vec![ScoringEvent::LargeSwingWarning {
+            player_id: 1,
+            dead_stones: 5
+        }]
+    );
+    assert_eq!(scoring.players_accepted, vec![false, false]);
+
+    // White has nothing dead, so accepts right away.
+    play(&mut game, 2, ActionKind::AcceptScore);
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(scoring.players_accepted, vec![false, true]);
+
+    play(&mut game, 1, ActionKind::AcceptScore);
+    let (_, result) = finished(&game);
+    assert_eq!(result.winner, Some(Color(2)));
+}
+
+#[test]
+fn komi_beyond_the_board_area() {
+    let one_stone = |komis: &[i32]| {
+        let mut game = two_player_game(komis, (3, 3));
+        play(&mut game, 1, ActionKind::Place(1, 1));
+        for _ in 0..2 {
+            play(&mut game, 2, ActionKind::Pass);
+            play(&mut game, 1, ActionKind::Pass);
+        }
+        game
+    };
+
+    // Black owns all 9 points but gives 15 points of komi.
+    let game = one_stone(&[-30, 0]);
+    let (scoring, result) = finished(&game);
+    assert_eq!(&scoring.scores[..], &[-12, 0]);
+    assert_eq!(result.winner, Some(Color(2)));
+    assert_eq!(result.margin, 12);
+
+    // Both totals negative, the least negative still wins.
+    let game = one_stone(&[-40, -30]);
+    let (scoring, result) = finished(&game);
+    assert_eq!(&scoring.scores[..], &[-22, -30]);
+    assert_eq!(result.winner, Some(Color(1)));
+    assert_eq!(result.margin, 8);
+
+    let game = one_stone(&[0, 2001]);
+    let (_, result) = finished(&game);
+    assert_eq!(result.winner, Some(Color(2)));
+    assert_eq!(result.margin, 1983);
+}
+
+#[test]
+fn abandoned_seat_is_scored_at_the_timeout() {
+    let mut game = game_with(&[1, 2, 3], &[0, 0, 1], (5, 5), GameModifier::default());
+    play(&mut game, 1, ActionKind::Place(0, 0));
+    play(&mut game, 2, ActionKind::Place(4, 4));
+    play(&mut game, 3, ActionKind::Place(2, 2));
+    for player in 1..=3 {
+        play(&mut game, player, ActionKind::Pass);
+    }
+    game.leave_seat(3, 2).unwrap();
+
+    play(&mut game, 1, ActionKind::AcceptScore);
+    play(&mut game, 2, ActionKind::AcceptScore);
+    // The empty seat hasn't accepted, so the count is finalized by the timeout.
+    assert!(matches!(game.state, GameState::Scoring(_)));
+    game.scoring_timeout().unwrap();
+
+    // Blue's stone still counts, and with komi blue wins.
+    let (scoring, result) = finished(&game);
+    assert_eq!(&scoring.scores[..], &[2, 2, 3]);
+    assert_eq!(result.winner, Some(Color(3)));
+}
+
+/// Plays a black wall on column `black` and a white wall on column `white`,
+/// then passes and accepts the count.
+fn walls_game(komis: &[i32], black: u32, white: u32) -> Game {
+    let mut game = two_player_game(komis, (5, 5));
+    for y in 0..5 {
+        play(&mut game, 1, ActionKind::Place(black, y));
+        play(&mut game, 2, ActionKind::Place(white, y));
+    }
+    for _ in 0..2 {
+        play(&mut game, 1, ActionKind::Pass);
+        play(&mut game, 2, ActionKind::Pass);
+    }
+    game
+}
+
+fn finished(game: &Game) -> (&ScoringState, &GameResult) {
+    match &game.state {
+        GameState::Done(scoring) => (scoring, scoring.result.as_ref().unwrap()),
+        _ => panic!("Game not done"),
+    }
+}
+
+#[test]
+fn territory_is_separate_from_stones() {
+    let mut game = two_player_game(&[0, 0], (5, 3));
+    for y in 0..3 {
+        play(&mut game, 1, ActionKind::Place(1, y));
+        play(&mut game, 2, ActionKind::Place(3, y));
+    }
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Place(0, 1));
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+    // Mark the white invader dead.
+    play(&mut game, 1, ActionKind::Place(0, 1));
+
+    let scoring = game.state.assume::<ScoringState>();
+    let at = |board: &Board, x, y| board.get_point(Point::new(x, y));
+
+    assert_eq!(at(&scoring.territory, 4, 1), Color(2));
+    assert_eq!(at(&scoring.stones, 4, 1), Color::empty());
+    assert_eq!(at(&scoring.stones, 1, 1), Color(1));
+    assert_eq!(at(&scoring.territory, 1, 1), Color::empty());
+
+    // The dead stone is still on the board but only counts as territory.
+    assert_eq!(at(&game.shared.board, 0, 1), Color(2));
+    assert_eq!(at(&scoring.stones, 0, 1), Color::empty());
+    assert_eq!(at(&scoring.territory, 0, 1), Color(1));
+
+    // Dame is in neither.
+    assert_eq!(at(&scoring.stones, 2, 1), Color::empty());
+    assert_eq!(at(&scoring.territory, 2, 1), Color::empty());
+}
+
+#[test]
+fn reverse_komi_flips_winner() {
+    // Black 10 points, white 15 points on the board.
+    let game = walls_game(&[0, 0], 1, 2);
+    assert_eq!(finished(&game).1.winner, Some(Color(2)));
+
+    // Black gets 7.5 komi.
+    let game = walls_game(&[15, 0], 1, 2);
+    let (scoring, result) = finished(&game);
+    assert_eq!(&scoring.scores[..], &[35, 30]);
+    assert_eq!(result.winner, Some(Color(1)));
+    assert_eq!(result.margin, 5);
+
+    // The same with a negative adjustment for white.
+    let game = walls_game(&[0, -15], 1, 2);
+    let (scoring, result) = finished(&game);
+    assert_eq!(&scoring.scores[..], &[20, 15]);
+    assert_eq!(result.winner, Some(Color(1)));
+    assert_eq!(result.margin, 5);
+}
+
+#[test]
+fn rescoring_with_new_komi_flips_winner() {
+    let mut game = walls_game(&[0, 0], 1, 2);
+    let scoring = finished(&game).0.clone();
+    assert_eq!(&scoring.scores[..], &[20, 30]);
+
+    // The game should have had 7.5 komi for black.
+    let scores = scoring.rescore_with_komi(&game.shared, &[15, 0]);
+    assert_eq!(&scores[..], &[35, 30]);
+    assert_eq!(finished(&game).0, &scoring);
+
+    let scoring = match &mut game.state {
+        GameState::Done(scoring) => scoring,
+        _ => unreachable!(),
+    };
+    scoring.commit_komi(&mut game.shared, &[15, 0]);
+    let result = scoring.result.as_ref().unwrap();
+    assert_eq!(result.winner, Some(Color(1)));
+    assert_eq!(result.margin, 5);
+    assert_eq!(&game.shared.komis[..], &[15, 0]);
+}
+
+#[test]
+fn zero_komi_can_draw() {
+    // Both own 10 points with a line of dame between them.
+    let game = walls_game(&[0, 0], 1, 3);
+    let (scoring, result) = finished(&game);
+    assert_eq!(&scoring.scores[..], &[20, 20]);
+    assert_eq!(result.winner, None);
+    assert_eq!(result.margin, 0);
+}
+
+#[test]
+fn integer_scoring_is_not_doubled() {
+    let mods = GameModifier {
+        integer_scoring: true,
+        ..GameModifier::default()
+    };
+    // White gets 7 points komi.
+    let mut game = game_with(&[1, 2], &[0, 7], (5, 5), mods);
+    for y in 0..5 {
+        play(&mut game, 1, ActionKind::Place(1, y));
+        play(&mut game, 2, ActionKind::Place(2, y));
+    }
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+
+    // Black: 5 stones + 5 territory, white: 5 stones + 10 territory + komi.
+    let scoring = game.state.assume::<ScoringState>();
+    assert_eq!(&scoring.scores[..], &[10, 22]);
+
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+    let (_, result) = finished(&game);
+    assert_eq!(result.winner, Some(Color(2)));
+    assert_eq!(result.margin, 12);
+}
+
+#[test]
+fn marking_a_whole_color_dead_warns() {
+    let mut game = two_player_game(&[0, 15], (5, 5));
+    play(&mut game, 1, ActionKind::Place(1, 1));
+    play(&mut game, 2, ActionKind::Place(3, 3));
+    play(&mut game, 1, ActionKind::Place(1, 3));
+    play(&mut game, 2, ActionKind::Pass);
+    play(&mut game, 1, ActionKind::Pass);
+
+    // Black still has a living group.
+    play(&mut game, 1, ActionKind::Place(1, 1));
+    assert_eq!(game.state.assume::<ScoringState>().all_dead, vec![]);
+
+    play(&mut game, 1, ActionKind::Place(1, 3));
+    assert_eq!(game.state.assume::<ScoringState>().all_dead, vec![Color(1)]);
+
+    // The warning doesn't block accepting.
+    play(&mut game, 1, ActionKind::AcceptScore);
+    play(&mut game, 2, ActionKind::AcceptScore);
+    let (scoring, result) = finished(&game);
+    assert_eq!(scoring.all_dead, vec![Color(1)]);
+    assert_eq!(result.winner, Some(Color(2)));
+}
+
+#[test]
+fn territory_does_not_leak_diagonally() {
+    // A diamond only has diagonal gaps, which don't connect regions.
+    let rows = &[
+        "o......", //
+        "...x...", //
+        "..x.x..", //
+        ".x...x.", //
+        "..x.x..", //
+        "...x...", //
+        ".......", //
+    ];
+    for &toroidal in &[false, true] {
+        let board = board_from(rows, toroidal);
+        let scored = score_board(&board, &find_groups(&board));
+        let inside = [(3, 2), (2, 3), (3, 3), (4, 3), (3, 4)];
+        for &(x, y) in &inside {
+            assert_eq!(
+                scored.get_point(Point::new(x, y)),
+                Color(1),
+                "toroidal {}",
+                toroidal
+            );
+        }
+        // The outside touches both colors.
+        assert_eq!(scored.get_point(Point::new(2, 1)), Color::empty());
+        assert_eq!(scored.get_point(Point::new(6, 6)), Color::empty());
+        let black = scored.points.iter().filter(|&&c| c == Color(1)).count();
+        assert_eq!(black, 8 + inside.len());
+    }
+}
+
+/// Black and white walls with a column of five dame between them, black to move.
+fn dame_scores(dame_policy: DamePolicy) -> (GroupVec<i32>, u32) {
+    let mods = GameModifier {
+        dame_policy,
+        ..GameModifier::default()
+    };
+    let mut game = game_with(&[1, 2], &[0, 0], (5, 5), mods);
+    for y in 0..5 {
+        play(&mut game, 1, ActionKind::Place(1, y));
+        play(&mut game, 2, ActionKind::Place(3, y));
+    }
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+
+    let scoring = game.state.assume::<ScoringState>();
+    (scoring.scores.clone(), scoring.dame)
+}
+
+#[test]
+fn dame_policy_ignore() {
+    let (scores, dame) = dame_scores(DamePolicy::Ignore);
+    assert_eq!(dame, 5);
+    assert_eq!(&scores[..], &[20, 20]);
+}
+
+#[test]
+fn dame_policy_parity() {
+    // Black fills first, getting three of the five dame.
+    let (scores, dame) = dame_scores(DamePolicy::Parity);
+    assert_eq!(dame, 5);
+    assert_eq!(&scores[..], &[26, 24]);
+}
+
+#[test]
+fn dame_policy_button() {
+    // Black passed first and takes the button.
+    let (scores, _) = dame_scores(DamePolicy::Button);
+    assert_eq!(&scores[..], &[21, 20]);
+}
+
+#[test]
+fn dame_policy_split() {
+    let (scores, _) = dame_scores(DamePolicy::Split);
+    assert_eq!(&scores[..], &[25, 25]);
+}
@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod tests;
+
+use crate::game::{
+    find_groups, ActionChange, ActionKind, BoardHistory, CollisionRule, Color, GameState, GroupVec,
+    MakeActionError, MakeActionResult, Point, SharedState,
+};
+use serde::{Deserialize, Serialize};
+
+use rand::prelude::*;
+use rand_pcg::Lcg64Xsh32;
+
+use super::scoring::ResultKind;
+use super::ScoringState;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PendingMove {
+    Place(Point),
+    Pass,
+}
+
+/// Every seat picks a move in secret, then they are all played at once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimultaneousPlayState {
+    /// The move of each seat for this turn. Hidden from the other players.
+    pub pending: Vec<Option<PendingMove>>,
+    /// Stones placed on the last turn.
+    pub last_stones: GroupVec<Point>,
+    /// Points picked by more than one seat on the last turn.
+    pub collisions: Vec<Point>,
+}
+
+impl SimultaneousPlayState {
+    pub fn new(seat_count: usize) -> Self {
+        SimultaneousPlayState {
+            pending: vec![None; seat_count],
+            last_stones: GroupVec::new(),
+            collisions: Vec::new(),
+        }
+    }
+
+    fn submit(
+        &mut self,
+        shared: &mut SharedState,
+        player_id: u64,
+        pending: PendingMove,
+    ) -> MakeActionResult {
+        let seat_idx = get_seat(shared, player_id)?;
+
+        if let PendingMove::Place(point) = pending {
+            if !shared.board.point_within(point) {
+                return Err(MakeActionError::OutOfBounds);
+            }
+            if !shared.board.get_point(point).is_empty() {
+                return Err(MakeActionError::PointOccupied);
+            }
+        }
+
+        // Changing your mind is fine until everyone has moved.
+        self.pending[seat_idx] = Some(pending);
+
+        if self.all_moved(shared) {
+            self.resolve(shared)
+        } else {
+            Ok(ActionChange::None)
+        }
+    }
+
+    fn all_moved(&self, shared: &SharedState) -> bool {
+        shared
+            .seats
+            .iter()
+            .zip(&self.pending)
+            .all(|(seat, pending)| seat.resigned || pending.is_some())
+    }
+
+    /// Plays every pending move at once.
+    /// 1. Points picked by several seats are decided by the `CollisionRule`.
+    /// 2. The stones are placed and old groups left without liberties are captured.
+    /// 3. Groups that still have no liberties are removed too, so suicide kills the group.
+    fn resolve(&mut self, shared: &mut SharedState) -> MakeActionResult {
+        let rule = shared
+            .mods
+            .simultaneous
+            .as_ref()
+            .map(|r| r.collision)
+            .unwrap_or_default();
+
+        let mut claims: Vec<(Point, Vec<Color>)> = Vec::new();
+        for (seat, pending) in shared.seats.iter().zip(&self.pending) {
+            if seat.resigned {
+                continue;
+            }
+            if let Some(PendingMove::Place(point)) = pending {
+                match claims.iter_mut().find(|(p, _)| p == point) {
+                    Some((_, colors)) => colors.push(seat.team),
+                    None => claims.push((*point, vec![seat.team])),
+                }
+            }
+        }
+        let all_passed = claims.is_empty();
+
+        // Seeded by the position, so replays flip the same coins.
+        let mut rng =
+            Lcg64Xsh32::seed_from_u64(shared.board.hash() ^ shared.board_history.len() as u64);
+        self.last_stones.clear();
+        self.collisions.clear();
+        for (point, colors) in claims {
+            let color = if colors.len() == 1 {
+                Some(colors[0])
+            } else {
+                self.collisions.push(point);
+                match rule {
+                    CollisionRule::BothRejected => None,
+                    CollisionRule::CoinFlip => colors.choose(&mut rng).copied(),
+                }
+            };
+            if let Some(color) = color {
+                *shared.board.point_mut(point) = color;
+                self.last_stones.push(point);
+            }
+        }
+
+        let last_stones = &self.last_stones;
+        let captured = find_groups(&shared.board)
+            .into_iter()
+            .filter(|g| g.liberties == 0 && !g.points.iter().any(|p| last_stones.contains(p)));
+        for group in captured {
+            for point in &group.points {
+                *shared.board.point_mut(*point) = Color::empty();
+            }
+        }
+        for group in find_groups(&shared.board) {
+            if group.liberties == 0 {
+                for point in &group.points {
+                    *shared.board.point_mut(*point) = Color::empty();
+                }
+            }
+        }
+        self.last_stones
+            .retain(|&p| !shared.board.get_point(p).is_empty());
+
+        for pending in &mut self.pending {
+            *pending = None;
+        }
+
+        shared.board_history.push(BoardHistory {
+            hash: shared.board.hash(),
+            board: shared.board.clone(),
+            board_visibility: shared.board_visibility.clone(),
+            state: GameState::Simultaneous(self.clone()),
+            points: shared.points.clone(),
+            turn: shared.turn,
+            traitor: shared.traitor.clone(),
+        });
+
+        if all_passed {
+            return Ok(ActionChange::PushState(GameState::scoring(
+                &shared.board,
+                &shared.seats,
+                &shared.points,
+                &shared.mods,
+                shared.turn,
+            )));
+        }
+
+        Ok(ActionChange::None)
+    }
+
+    fn make_action_resign(&mut self, shared: &mut SharedState, player_id: u64) -> MakeActionResult {
+        let seat_idx = get_seat(shared, player_id)?;
+        shared.seats[seat_idx].resigned = true;
+        self.pending[seat_idx] = None;
+
+        if shared.seats.iter().filter(|s| !s.resigned).count() <= 1 {
+            let state = ScoringState::new(
+                &shared.board,
+                &shared.seats,
+                &shared.points,
+                &shared.mods,
+                shared.turn,
+            );
+            return Ok(ActionChange::PushState(
+                state.finish(shared, ResultKind::Resignation),
+            ));
+        }
+
+        // The players left may have been waiting for the one who resigned.
+        if self.all_moved(shared) {
+            return self.resolve(shared);
+        }
+
+        Ok(ActionChange::None)
+    }
+
+    pub fn make_action(
+        &mut self,
+        shared: &mut SharedState,
+        player_id: u64,
+        action: ActionKind,
+    ) -> MakeActionResult {
+        match action {
+            ActionKind::Place(x, y) => {
+                self.submit(shared, player_id, PendingMove::Place(Point::new(x, y)))
+            }
+            ActionKind::Pass => self.submit(shared, player_id, PendingMove::Pass),
+            ActionKind::Cancel => {
+                let seat_idx = get_seat(shared, player_id)?;
+                self.pending[seat_idx] = None;
+                Ok(ActionChange::None)
+            }
+            ActionKind::Resign => self.make_action_resign(shared, player_id),
+            ActionKind::AcceptScore
+            | ActionKind::RejectScore(_)
+            | ActionKind::ResumePlay
+            | ActionKind::ProposeKomi(_)
+            | ActionKind::ChooseColor(_) => Err(MakeActionError::Illegal),
+        }
+    }
+}
+
+/// Like free placement, a player is assumed to hold a single seat.
+fn get_seat(shared: &SharedState, player_id: u64) -> Result<usize, MakeActionError> {
+    shared
+        .seats
+        .iter()
+        .position(|s| s.player == Some(player_id) && !s.resigned)
+        .ok_or(MakeActionError::NotPlayer)
+}
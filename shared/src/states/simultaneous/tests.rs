@@ -0,0 +1,99 @@
+use crate::game::clock::Millisecond;
+use crate::game::*;
+use crate::states::SimultaneousPlayState;
+
+fn simultaneous_game(collision: CollisionRule) -> Game {
+    let mods = GameModifier {
+        simultaneous: Some(SimultaneousGo { collision }),
+        ..GameModifier::default()
+    };
+    let mut game = GameBuilder::new()
+        .board_size(5, 5)
+        .rules(mods)
+        .build()
+        .unwrap();
+    game.take_seat(1, 0).unwrap();
+    game.take_seat(2, 1).unwrap();
+    game
+}
+
+fn play(game: &mut Game, player_id: u64, action: ActionKind) {
+    game.make_action(player_id, action, Millisecond(0))
+        .expect("Action failed");
+}
+
+#[test]
+fn separate_moves_are_played_together() {
+    let mut game = simultaneous_game(CollisionRule::BothRejected);
+
+    play(&mut game, 1, ActionKind::Place(1, 1));
+    assert!(game.shared.board.points.iter().all(|c| c.is_empty()));
+    match game.get_view(2).state {
+        GameStateView::Simultaneous(view) => assert_eq!(view.players_moved, vec![true, false]),
+        state => panic!("Unexpected state {:?}", state),
+    }
+
+    play(&mut game, 2, ActionKind::Place(3, 3));
+    assert_eq!(game.shared.board.get_point(Point::new(1, 1)), Color(1));
+    assert_eq!(game.shared.board.get_point(Point::new(3, 3)), Color(2));
+    assert_eq!(game.summary().move_number, 1);
+
+    let state = game.state.assume::<SimultaneousPlayState>();
+    assert_eq!(state.pending, vec![None, None]);
+    assert!(state.collisions.is_empty());
+}
+
+#[test]
+fn same_point_is_rejected_for_both() {
+    let mut game = simultaneous_game(CollisionRule::BothRejected);
+    play(&mut game, 1, ActionKind::Place(2, 2));
+    play(&mut game, 2, ActionKind::Place(2, 2));
+
+    assert!(game.shared.board.points.iter().all(|c| c.is_empty()));
+    let state = game.state.assume::<SimultaneousPlayState>();
+    assert_eq!(state.collisions, vec![Point::new(2, 2)]);
+    assert!(state.last_stones.is_empty());
+}
+
+#[test]
+fn coin_flip_gives_the_point_to_one() {
+    let mut game = simultaneous_game(CollisionRule::CoinFlip);
+    play(&mut game, 1, ActionKind::Place(2, 2));
+    play(&mut game, 2, ActionKind::Place(2, 2));
+
+    assert!(!game.shared.board.get_point(Point::new(2, 2)).is_empty());
+    assert_eq!(
+        game.shared
+            .board
+            .points
+            .iter()
+            .filter(|c| !c.is_empty())
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn combined_placement_captures() {
+    let mut game = simultaneous_game(CollisionRule::BothRejected);
+    play(&mut game, 1, ActionKind::Place(1, 0));
+    play(&mut game, 2, ActionKind::Place(0, 0));
+
+    // Black takes the corner stone while white plays elsewhere.
+    play(&mut game, 1, ActionKind::Place(0, 1));
+    play(&mut game, 2, ActionKind::Place(4, 4));
+    assert_eq!(
+        game.shared.board.get_point(Point::new(0, 0)),
+        Color::empty()
+    );
+    assert_eq!(game.shared.board.get_point(Point::new(0, 1)), Color(1));
+    assert_eq!(game.shared.board.get_point(Point::new(4, 4)), Color(2));
+}
+
+#[test]
+fn everyone_passing_ends_the_game() {
+    let mut game = simultaneous_game(CollisionRule::BothRejected);
+    play(&mut game, 1, ActionKind::Pass);
+    play(&mut game, 2, ActionKind::Pass);
+    assert!(matches!(game.state, GameState::Scoring(_)));
+}
@@ -0,0 +1,79 @@
+use crate::game::{
+    ActionChange, ActionKind, GameState, MakeActionError, MakeActionResult, SharedState,
+};
+use serde::{Deserialize, Serialize};
+
+/// Every seat signals it's ready before the game starts, so clocks don't start
+/// before the players have loaded the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaitingState {
+    pub players_ready: Vec<bool>,
+}
+
+impl WaitingState {
+    pub fn new(seat_count: usize) -> Self {
+        WaitingState {
+            players_ready: vec![false; seat_count],
+        }
+    }
+
+    fn set_ready(&mut self, shared: &SharedState, player_id: u64, ready: bool) {
+        // A single player can hold multiple seats, they are ready for all of them.
+        for (seat, seat_ready) in shared.seats.iter().zip(&mut self.players_ready) {
+            if seat.player == Some(player_id) {
+                *seat_ready = ready;
+            }
+        }
+    }
+
+    fn make_action_ready(&mut self, shared: &mut SharedState, player_id: u64) -> MakeActionResult {
+        self.set_ready(shared, player_id, true);
+
+        let empty_seats_block = shared
+            .mods
+            .ready_check
+            .as_ref()
+            .is_none_or(|r| r.empty_seats_block);
+        let all_ready = shared
+            .seats
+            .iter()
+            .zip(&self.players_ready)
+            .all(|(seat, &ready)| ready || (seat.player.is_none() && !empty_seats_block));
+
+        if all_ready {
+            Ok(ActionChange::SwapState(GameState::initial(shared)))
+        } else {
+            Ok(ActionChange::None)
+        }
+    }
+
+    /// A player left the seat, so it's no longer ready.
+    pub fn seat_left(&mut self, seat_idx: usize) {
+        self.players_ready[seat_idx] = false;
+    }
+
+    pub fn make_action(
+        &mut self,
+        shared: &mut SharedState,
+        player_id: u64,
+        action: ActionKind,
+    ) -> MakeActionResult {
+        match action {
+            ActionKind::Pass => self.make_action_ready(shared, player_id),
+            ActionKind::Cancel => {
+                self.set_ready(shared, player_id, false);
+                Ok(ActionChange::None)
+            }
+            ActionKind::Resign => {
+                // We don't allow resigning before the game starts
+                Ok(ActionChange::None)
+            }
+            ActionKind::Place(..)
+            | ActionKind::AcceptScore
+            | ActionKind::RejectScore(_)
+            | ActionKind::ResumePlay
+            | ActionKind::ProposeKomi(_)
+            | ActionKind::ChooseColor(_) => Err(MakeActionError::Illegal),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! Fixtures shared by the unit tests of several modules.
+
+use crate::game::{Board, Color, Game, GameBuilder, Point};
+
+/// Reads a board from rows of text: `x` is black, `o` white, `z` the third color
+/// and anything else an empty point.
+pub(crate) fn board_from(rows: &[&str], toroidal: bool) -> Board {
+    let mut board = Board::empty(rows[0].len() as u32, rows.len() as u32, toroidal);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            *board.point_mut(Point::new(x as u32, y as u32)) = match c {
+                'x' => Color(1),
+                'o' => Color(2),
+                'z' => Color(3),
+                _ => Color::empty(),
+            };
+        }
+    }
+    board
+}
+
+/// A two color game starting from `rows`, see `board_from`.
+/// Player 100 plays black and player 200 white.
+pub(crate) fn game_with_position(rows: &[&str], toroidal: bool) -> Game {
+    let mut game = GameBuilder::new()
+        .board_size(rows[0].len() as u8, rows.len() as u8)
+        .toroidal(toroidal)
+        .build()
+        .unwrap();
+    game.take_seat(100, 0).unwrap();
+    game.take_seat(200, 1).unwrap();
+
+    game.shared.board = board_from(rows, toroidal);
+    let history = &mut game.shared.board_history[0];
+    history.board = game.shared.board.clone();
+    history.hash = history.board.hash();
+    game
+}